@@ -1,18 +1,26 @@
-use crate::ease;
+use crate::ease::Easing;
 
 use std::time::{Duration, Instant};
 
+/// All lengths are in chars. Edits happen just before the last suffix_len chars of the string,
+/// which are left untouched
 pub enum AnimationReq {
     Delete {
         desired_len: usize,
+        suffix_len: usize,
         animation_duration: Duration,
+        easing: Easing,
     },
     Wait {
         wait_time: Duration,
     },
+    /// Keep the string as is until Animation::release is called
+    Hold,
     Append {
         additional_chars: String,
+        suffix_len: usize,
         animation_duration: Duration,
+        easing: Easing,
     },
 }
 
@@ -20,6 +28,7 @@ pub enum Animation {
     Delete(DeleteOverTime),
     Append(AppendOverTime),
     Wait(String, Instant),
+    Hold(String),
     None(String),
 }
 
@@ -29,13 +38,22 @@ impl Animation {
             Animation::Delete(d) => d.finished(now),
             Animation::Append(d) => d.finished(now),
             Animation::Wait(_, t) => now > *t,
+            Animation::Hold(_) => false,
             Animation::None(_) => true,
         }
     }
 
+    /// Ends a hold so the next request can start, other animations are left alone
+    pub fn release(&mut self) {
+        if let Animation::Hold(s) = self {
+            *self = Animation::None(std::mem::take(s));
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             Animation::Wait(s, _) => s,
+            Animation::Hold(s) => s,
             Animation::None(s) => s,
             Animation::Delete(d) => d.as_str(),
             Animation::Append(a) => a.as_str(),
@@ -45,6 +63,7 @@ impl Animation {
     pub fn into_finished_string(self) -> String {
         match self {
             Animation::Wait(s, _) => s,
+            Animation::Hold(s) => s,
             Animation::None(s) => s,
             Animation::Delete(d) => d.into_finished_string(),
             Animation::Append(a) => a.into_finished_string(),
@@ -60,23 +79,45 @@ impl Animation {
     }
 }
 
+/// Byte offset of the start of the last suffix_len chars
+fn suffix_start(s: &str, suffix_len: usize) -> usize {
+    if suffix_len == 0 {
+        return s.len();
+    }
+
+    s.char_indices()
+        .rev()
+        .nth(suffix_len - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
 pub struct DeleteOverTime {
     s: String,
+    // Bytes at the end of s that are not part of the animation
+    suffix_bytes: usize,
     start_len: usize,
     desired_len: usize,
     animation_start: Instant,
     animation_duration: Duration,
+    easing: Easing,
 }
 
 impl DeleteOverTime {
     pub fn update(&mut self, now: Instant) {
         let time_factor = self.time_factor(now);
 
-        let delete_factor = ease::in_sine(time_factor);
+        let delete_factor = self.easing.apply(time_factor);
         let deleted_chars = ((self.start_len - self.desired_len) as f32 * delete_factor) as usize;
         let desired_current_len = self.start_len - deleted_chars;
 
-        self.s.truncate(desired_current_len);
+        let head_end = self.s.len() - self.suffix_bytes;
+        let delete_start = self.s[..head_end]
+            .char_indices()
+            .nth(desired_current_len)
+            .map(|(i, _)| i)
+            .unwrap_or(head_end);
+        self.s.replace_range(delete_start..head_end, "");
     }
 
     pub fn time_factor(&self, now: Instant) -> f32 {
@@ -102,28 +143,30 @@ use std::collections::VecDeque;
 
 pub struct AppendOverTime {
     s: String,
-    start_len: usize,
+    // Bytes at the end of s that are not part of the animation
+    suffix_bytes: usize,
+    appended: usize,
     additional_characters: VecDeque<char>,
     animation_start: Instant,
     animation_duration: Duration,
+    easing: Easing,
 }
 
 impl AppendOverTime {
     pub fn update(&mut self, now: Instant) {
         let time_factor = self.time_factor(now);
 
-        let append_factor = ease::in_sine(time_factor);
-        let current_len = self.s.chars().count();
-        let final_len = current_len + self.additional_characters.len();
-        let desired_len =
-            ((final_len - self.start_len) as f32 * append_factor) as usize + self.start_len;
+        let append_factor = self.easing.apply(time_factor);
+        let total = self.appended + self.additional_characters.len();
+        let desired_appended = (total as f32 * append_factor) as usize;
 
-        while self.s.len() < desired_len {
-            self.s.push(
-                self.additional_characters
-                    .pop_front()
-                    .expect("Attempted to pop too many"),
-            );
+        while self.appended < desired_appended {
+            let c = self
+                .additional_characters
+                .pop_front()
+                .expect("Attempted to pop too many");
+            self.s.insert(self.s.len() - self.suffix_bytes, c);
+            self.appended += 1;
         }
     }
 
@@ -146,56 +189,386 @@ impl AppendOverTime {
     }
 }
 
+/// Glides the cursor between text positions instead of letting it jump
+pub struct CaretMove {
+    from: (f32, f32),
+    to: (f32, f32),
+    animation_start: Instant,
+    animation_duration: Duration,
+    easing: Easing,
+}
+
+impl CaretMove {
+    /// At rest at pos. A zero duration makes every move instant
+    pub fn new(pos: (f32, f32), duration: Duration, easing: Easing, now: Instant) -> CaretMove {
+        CaretMove {
+            from: pos,
+            to: pos,
+            animation_start: now,
+            animation_duration: duration,
+            easing,
+        }
+    }
+
+    /// Heads for to from wherever the caret is now. Asking for the current target again keeps
+    /// the move going undisturbed
+    pub fn move_to(&mut self, to: (f32, f32), now: Instant) {
+        if to == self.to {
+            return;
+        }
+        self.from = self.position(now);
+        self.to = to;
+        self.animation_start = now;
+    }
+
+    pub fn time_factor(&self, now: Instant) -> f32 {
+        if self.animation_duration.is_zero() || self.from == self.to {
+            return 1.0;
+        }
+        let duration_since_start = now.saturating_duration_since(self.animation_start);
+        (duration_since_start.as_secs_f32() / self.animation_duration.as_secs_f32()).clamp(0.0, 1.0)
+    }
+
+    pub fn finished(&self, now: Instant) -> bool {
+        self.time_factor(now) >= 1.0f32
+    }
+
+    pub fn position(&self, now: Instant) -> (f32, f32) {
+        let t = self.easing.apply(self.time_factor(now));
+        (
+            self.from.0 + (self.to.0 - self.from.0) * t,
+            self.from.1 + (self.to.1 - self.from.1) * t,
+        )
+    }
+}
+
+/// Starts the next queued request once current finishes, then moves current along to now. With
+/// nothing left in the queue a finished animation holds its string until released
+pub fn step(current: &mut Animation, queue: &mut VecDeque<AnimationReq>, now: Instant) {
+    if current.finished(now) {
+        let finished = std::mem::replace(current, Animation::None(String::new()));
+        let s = finished.into_finished_string();
+        let req = queue.pop_front().unwrap_or(AnimationReq::Hold);
+        *current = apply_animation_req(req, s, now);
+    }
+    current.update(now);
+}
+
 pub fn apply_animation_req(req: AnimationReq, s: String, now: Instant) -> Animation {
-    let s_len = s.len();
     match req {
         AnimationReq::Delete {
             desired_len,
+            suffix_len,
             animation_duration,
-        } => Animation::Delete(DeleteOverTime {
-            s,
-            start_len: s_len,
-            desired_len,
-            animation_start: now,
-            animation_duration,
-        }),
+            easing,
+        } => {
+            let suffix_start = suffix_start(&s, suffix_len);
+            Animation::Delete(DeleteOverTime {
+                start_len: s[..suffix_start].chars().count(),
+                suffix_bytes: s.len() - suffix_start,
+                s,
+                desired_len,
+                animation_start: now,
+                animation_duration,
+                easing,
+            })
+        }
         AnimationReq::Append {
             additional_chars,
+            suffix_len,
             animation_duration,
+            easing,
         } => Animation::Append(AppendOverTime {
+            suffix_bytes: s.len() - suffix_start(&s, suffix_len),
             s,
-            start_len: s_len,
+            appended: 0,
             additional_characters: additional_chars.chars().collect(),
             animation_start: now,
             animation_duration,
+            easing,
         }),
         AnimationReq::Wait { wait_time } => Animation::Wait(s, now + wait_time),
+        AnimationReq::Hold => Animation::Hold(s),
     }
 }
 
-pub fn construct_animation_requests(current: &str, desired: &str) -> VecDeque<AnimationReq> {
-    let mut ret = VecDeque::new();
-    let first_differing_char = current
+// Time digits ticking over get a quick retype without the pause beforehand
+const TICK_DURATION: Duration = Duration::from_millis(150);
+const WAIT_DURATION: Duration = Duration::from_millis(1500);
+const EDIT_DURATION: Duration = Duration::from_millis(1500);
+
+/// Number of chars in the common (prefix, suffix) of the two strings
+fn common_affixes(current: &str, desired: &str) -> (usize, usize) {
+    let prefix_len = current
         .chars()
         .zip(desired.chars())
-        .position(|(a, b)| a != b);
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    // Don't let the suffix eat into the prefix, e.g. "aa" -> "aaa"
+    let max_suffix_len = current.chars().count().min(desired.chars().count()) - prefix_len;
+    let suffix_len = current
+        .chars()
+        .rev()
+        .zip(desired.chars().rev())
+        .take(max_suffix_len)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    (prefix_len, suffix_len)
+}
+
+fn is_time_char(c: char) -> bool {
+    c.is_ascii_digit() || c == ':' || c.is_whitespace()
+}
 
-    let first_differing_char = first_differing_char.unwrap_or(0);
+/// Only the section between the common prefix and common suffix of the two strings is retyped.
+/// Identical strings need no animation, and changes that only touch clock digits are retyped
+/// quickly without waiting first so the per second updates don't look frantic
+pub fn construct_animation_requests(
+    current: &str,
+    desired: &str,
+    easing: Easing,
+) -> VecDeque<AnimationReq> {
+    let mut ret = VecDeque::new();
+
+    if current == desired {
+        return ret;
+    }
+
+    let (prefix_len, suffix_len) = common_affixes(current, desired);
+
+    let middle = |s: &str| -> String {
+        let len = s.chars().count();
+        s.chars()
+            .skip(prefix_len)
+            .take(len - prefix_len - suffix_len)
+            .collect()
+    };
+    let removed = middle(current);
+    let added = middle(desired);
+
+    let is_tick = removed.chars().chain(added.chars()).all(is_time_char);
+    let edit_duration = if is_tick {
+        TICK_DURATION
+    } else {
+        EDIT_DURATION
+    };
 
     if !current.is_empty() {
-        ret.push_back(AnimationReq::Wait {
-            wait_time: Duration::from_secs_f32(1.5),
-        });
+        if !is_tick {
+            ret.push_back(AnimationReq::Wait {
+                wait_time: WAIT_DURATION,
+            });
+        }
         ret.push_back(AnimationReq::Delete {
-            desired_len: first_differing_char,
-            animation_duration: Duration::from_secs_f32(1.5),
+            desired_len: prefix_len,
+            suffix_len,
+            animation_duration: edit_duration,
+            easing,
         });
     }
 
     ret.push_back(AnimationReq::Append {
-        additional_chars: desired.chars().skip(first_differing_char).collect(),
-        animation_duration: Duration::from_secs_f32(1.5),
+        additional_chars: added,
+        suffix_len,
+        animation_duration: edit_duration,
+        easing,
     });
 
     ret
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run_requests(s: &str, reqs: VecDeque<AnimationReq>) -> String {
+        let now = Instant::now();
+        reqs.into_iter().fold(s.to_string(), |s, req| {
+            apply_animation_req(req, s, now).into_finished_string()
+        })
+    }
+
+    /// Steps the animation through virtual time from start, giving the string after each step
+    fn drive(animation: &mut Animation, start: Instant, step: Duration, steps: u32) -> Vec<String> {
+        (0..=steps)
+            .map(|i| {
+                animation.update(start + step * i);
+                animation.as_str().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_append_frames_follow_easing() {
+        let start = Instant::now();
+        let req = AnimationReq::Append {
+            additional_chars: "abcdefghij".to_string(),
+            suffix_len: 1,
+            animation_duration: Duration::from_secs(1),
+            easing: Easing::InOutQuad,
+        };
+        let mut animation = apply_animation_req(req, "[]".to_string(), start);
+
+        let step = Duration::from_millis(250);
+        assert_eq!(
+            drive(&mut animation, start, step, 4),
+            ["[]", "[a]", "[abcde]", "[abcdefgh]", "[abcdefghij]"]
+        );
+        assert!(!animation.finished(start + step * 3));
+        assert!(animation.finished(start + step * 5));
+    }
+
+    #[test]
+    fn test_delete_frames_follow_easing() {
+        let start = Instant::now();
+        let req = AnimationReq::Delete {
+            desired_len: 2,
+            suffix_len: 0,
+            animation_duration: Duration::from_secs(1),
+            easing: Easing::InQuad,
+        };
+        let mut animation = apply_animation_req(req, "abcdefghij".to_string(), start);
+
+        assert_eq!(
+            drive(&mut animation, start, Duration::from_millis(250), 4),
+            ["abcdefghij", "abcdefghij", "abcdefgh", "abcdef", "ab"]
+        );
+    }
+
+    #[test]
+    fn test_caret_move_midpoint() {
+        let now = Instant::now();
+        let duration = Duration::from_millis(200);
+        let mut caret = CaretMove::new((0.0, 0.0), duration, Easing::InOutQuad, now);
+        assert!(caret.finished(now));
+
+        caret.move_to((1.0, 2.0), now);
+        assert!(!caret.finished(now));
+        assert_eq!(caret.position(now + duration / 2), (0.5, 1.0));
+        assert_eq!(caret.position(now + duration), (1.0, 2.0));
+
+        // Retargeting mid move starts from where the caret got to
+        let mid = now + duration / 2;
+        caret.move_to((0.0, 0.0), mid);
+        assert_eq!(caret.position(mid), (0.5, 1.0));
+
+        let mut instant = CaretMove::new((0.0, 0.0), Duration::ZERO, Easing::InOutQuad, now);
+        instant.move_to((1.0, 1.0), now);
+        assert_eq!(instant.position(now), (1.0, 1.0));
+    }
+
+    #[test]
+    fn test_hold_until_released() {
+        let now = Instant::now();
+        let mut animation = apply_animation_req(AnimationReq::Hold, "held".to_string(), now);
+
+        assert!(!animation.finished(now));
+        assert!(!animation.finished(now + Duration::from_secs(60 * 60 * 24)));
+        assert_eq!(animation.as_str(), "held");
+
+        animation.release();
+        assert!(animation.finished(now));
+        assert_eq!(animation.into_finished_string(), "held");
+    }
+
+    #[test]
+    fn test_middle_change_uses_suffix() {
+        let current = "Topic: foo at 14:00:00";
+        let desired = "Topic: bar at 14:00:00";
+        let reqs = construct_animation_requests(current, desired, Easing::default());
+
+        match &reqs[1] {
+            AnimationReq::Delete {
+                desired_len,
+                suffix_len,
+                ..
+            } => {
+                assert_eq!(*desired_len, "Topic: ".len());
+                assert_eq!(*suffix_len, " at 14:00:00".len());
+            }
+            _ => panic!("Expected delete"),
+        }
+
+        match &reqs[2] {
+            AnimationReq::Append {
+                additional_chars,
+                suffix_len,
+                ..
+            } => {
+                assert_eq!(additional_chars, "bar");
+                assert_eq!(*suffix_len, " at 14:00:00".len());
+            }
+            _ => panic!("Expected append"),
+        }
+
+        assert_eq!(run_requests(current, reqs), desired);
+    }
+
+    #[test]
+    fn test_clock_tick_is_minimal() {
+        let current = "Stream starting at 14:00:00";
+        let desired = "Stream starting at 14:30:00";
+        let reqs = construct_animation_requests(current, desired, Easing::default());
+        assert_eq!(reqs.len(), 2);
+
+        match &reqs[0] {
+            AnimationReq::Delete {
+                desired_len,
+                suffix_len,
+                animation_duration,
+                ..
+            } => {
+                assert_eq!(*desired_len, "Stream starting at 14:".len());
+                assert_eq!(*suffix_len, "0:00".len());
+                assert_eq!(*animation_duration, TICK_DURATION);
+            }
+            _ => panic!("Expected delete"),
+        }
+
+        match &reqs[1] {
+            AnimationReq::Append {
+                additional_chars, ..
+            } => assert_eq!(additional_chars, "3"),
+            _ => panic!("Expected append"),
+        }
+
+        assert_eq!(run_requests(current, reqs), desired);
+    }
+
+    #[test]
+    fn test_unchanged_string_has_no_requests() {
+        assert!(construct_animation_requests("same", "same", Easing::default()).is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_prefix_and_suffix() {
+        let reqs = construct_animation_requests("aa", "aaa", Easing::default());
+        match &reqs[2] {
+            AnimationReq::Append {
+                additional_chars, ..
+            } => assert_eq!(additional_chars, "a"),
+            _ => panic!("Expected append"),
+        }
+        assert_eq!(run_requests("aa", reqs), "aaa");
+
+        let reqs = construct_animation_requests("abcabc", "abc", Easing::default());
+        assert_eq!(run_requests("abcabc", reqs), "abc");
+    }
+
+    #[test]
+    fn test_multibyte_middle_change() {
+        let current = "topic: café 10";
+        let desired = "topic: naïve 10";
+        let reqs = construct_animation_requests(current, desired, Easing::default());
+        assert_eq!(run_requests(current, reqs), desired);
+    }
+
+    #[test]
+    fn test_initial_append() {
+        let reqs = construct_animation_requests("", "hello", Easing::default());
+        assert_eq!(reqs.len(), 1);
+        assert_eq!(run_requests("", reqs), "hello");
+    }
+}
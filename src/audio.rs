@@ -0,0 +1,247 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, SampleFormat, SizedSample,
+};
+use thiserror::Error;
+
+// Power of two for the fft, ~23ms of audio at 44.1kHz
+const FFT_SIZE: usize = 1024;
+// Lowest frequency we bother showing, everything below is mostly rumble
+const MIN_FREQ: f32 = 40.0;
+
+#[derive(Debug, Error)]
+pub enum AudioCaptureError {
+    #[error("no default input device")]
+    NoDefaultDevice,
+    #[error("failed to list input devices")]
+    ListDevices(#[source] cpal::Error),
+    #[error("no input device named {0}")]
+    DeviceNotFound(String),
+    #[error("failed to get input config")]
+    GetConfig(#[source] cpal::Error),
+    #[error("unsupported sample format {0}")]
+    UnsupportedFormat(SampleFormat),
+    #[error("failed to build input stream")]
+    BuildStream(#[source] cpal::Error),
+    #[error("failed to start input stream")]
+    Play(#[source] cpal::Error),
+}
+
+/// Captures mono audio from an input device, channels are mixed down as they arrive
+pub struct AudioCapture {
+    // Capture stops when the stream is dropped
+    _stream: cpal::Stream,
+    sample_rate: u32,
+    samples: Arc<Mutex<VecDeque<f32>>>,
+}
+
+impl AudioCapture {
+    /// Device is an input device name as the host reports it, e.g. a pulse or pipewire monitor
+    /// for loopback of an output. None uses the default input
+    pub fn new(device: Option<&str>) -> Result<AudioCapture, AudioCaptureError> {
+        let host = cpal::default_host();
+        let device = match device {
+            Some(name) => host
+                .input_devices()
+                .map_err(AudioCaptureError::ListDevices)?
+                .find(|d| d.to_string() == name)
+                .ok_or_else(|| AudioCaptureError::DeviceNotFound(name.to_string()))?,
+            None => host
+                .default_input_device()
+                .ok_or(AudioCaptureError::NoDefaultDevice)?,
+        };
+
+        let config = device
+            .default_input_config()
+            .map_err(AudioCaptureError::GetConfig)?;
+        let sample_rate = config.sample_rate();
+        let channels = config.channels() as usize;
+
+        let samples = Arc::new(Mutex::new(VecDeque::with_capacity(FFT_SIZE)));
+        let stream = match config.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, config.config(), channels, &samples),
+            SampleFormat::I16 => build_stream::<i16>(&device, config.config(), channels, &samples),
+            SampleFormat::U16 => build_stream::<u16>(&device, config.config(), channels, &samples),
+            SampleFormat::I32 => build_stream::<i32>(&device, config.config(), channels, &samples),
+            format => return Err(AudioCaptureError::UnsupportedFormat(format)),
+        }?;
+        stream.play().map_err(AudioCaptureError::Play)?;
+
+        Ok(AudioCapture {
+            _stream: stream,
+            sample_rate,
+            samples,
+        })
+    }
+
+    /// Magnitude of num_bars logarithmically spaced frequency bands, roughly in [0, 1]
+    pub fn spectrum(&self, num_bars: usize) -> Vec<f32> {
+        let mut re = vec![0.0f32; FFT_SIZE];
+        let mut im = vec![0.0f32; FFT_SIZE];
+        {
+            let samples = self.samples.lock().unwrap();
+            for (i, sample) in samples.iter().enumerate() {
+                // Hann window to stop the edges of the buffer smearing across all bins
+                let window =
+                    0.5 - 0.5 * f32::cos(2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32);
+                re[i] = sample * window;
+            }
+        }
+
+        fft(&mut re, &mut im);
+
+        let bin_width = self.sample_rate as f32 / FFT_SIZE as f32;
+        let max_freq = self.sample_rate as f32 / 2.0;
+        (0..num_bars)
+            .map(|bar| {
+                let freq_at = |bar: usize| {
+                    MIN_FREQ * (max_freq / MIN_FREQ).powf(bar as f32 / num_bars as f32)
+                };
+                let start = (freq_at(bar) / bin_width) as usize;
+                let end = ((freq_at(bar + 1) / bin_width) as usize).clamp(start + 1, FFT_SIZE / 2);
+
+                let peak = (start..end)
+                    .map(|i| f32::sqrt(re[i] * re[i] + im[i] * im[i]))
+                    .fold(0.0, f32::max);
+
+                // Log scale so quiet bands are still visible, -60dB -> 0, 0dB -> 1
+                let db = 20.0 * f32::log10(peak * 2.0 / FFT_SIZE as f32 + 1e-6);
+                ((db + 60.0) / 60.0).clamp(0.0, 1.0)
+            })
+            .collect()
+    }
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: cpal::StreamConfig,
+    channels: usize,
+    samples: &Arc<Mutex<VecDeque<f32>>>,
+) -> Result<cpal::Stream, AudioCaptureError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let samples = Arc::clone(samples);
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &_| push_frames(&mut samples.lock().unwrap(), data, channels),
+            |e| println!("Audio capture error: {e}"),
+            None,
+        )
+        .map_err(AudioCaptureError::BuildStream)
+}
+
+/// Appends the mono mix of interleaved frames, keeping only the last FFT_SIZE samples
+fn push_frames<T>(samples: &mut VecDeque<f32>, data: &[T], channels: usize)
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    for frame in data.chunks_exact(channels) {
+        let sum: f32 = frame.iter().map(|s| s.to_sample::<f32>()).sum();
+        samples.push_back(sum / channels as f32);
+    }
+    while samples.len() > FFT_SIZE {
+        samples.pop_front();
+    }
+}
+
+/// In place iterative radix-2 fft. Input length must be a power of two
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    assert!(n.is_power_of_two());
+    assert_eq!(n, im.len());
+
+    // Bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f32::consts::PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_im, w_re) = f32::sin_cos(angle * k as f32);
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_push_frames_mixes_to_mono() {
+        let mut samples = VecDeque::new();
+        push_frames(&mut samples, &[i16::MAX, 0, i16::MIN, i16::MIN], 2);
+        assert_eq!(samples.len(), 2);
+        assert!((samples[0] - 0.5).abs() < 0.001);
+        assert!((samples[1] + 1.0).abs() < 0.001);
+
+        push_frames(&mut samples, &[0.25f32; FFT_SIZE], 1);
+        assert_eq!(samples.len(), FFT_SIZE);
+        assert!(samples.iter().all(|s| *s == 0.25));
+    }
+
+    #[test]
+    fn test_fft_impulse() {
+        let mut re = [0.0f32; 8];
+        let mut im = [0.0f32; 8];
+        re[0] = 1.0;
+        fft(&mut re, &mut im);
+        for (re, im) in re.iter().zip(im) {
+            assert!((re - 1.0).abs() < 0.0001);
+            assert!(im.abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_fft_sine_peak() {
+        const N: usize = 64;
+        let mut re = [0.0f32; N];
+        let mut im = [0.0f32; N];
+        for (i, v) in re.iter_mut().enumerate() {
+            *v = f32::sin(2.0 * std::f32::consts::PI * 5.0 * i as f32 / N as f32);
+        }
+        fft(&mut re, &mut im);
+
+        let magnitudes: Vec<f32> = re
+            .iter()
+            .zip(im)
+            .map(|(re, im)| f32::sqrt(re * re + im * im))
+            .collect();
+        let peak = (0..N / 2)
+            .max_by(|a, b| magnitudes[*a].total_cmp(&magnitudes[*b]))
+            .unwrap();
+        assert_eq!(peak, 5);
+        assert!((magnitudes[5] - N as f32 / 2.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,335 @@
+use std::io::{BufRead, Error as IoError};
+
+use thiserror::Error;
+
+use crate::{
+    ease::{Easing, ParseEasingError},
+    mat::{Axis, Quat, Transform, Vec3},
+};
+
+#[derive(Debug, Error)]
+pub enum CameraPathParseError {
+    #[error("failed to read camera path")]
+    FileRead(#[source] IoError),
+    #[error("line {0}: expected time, position x/y/z and target x/y/z")]
+    MissingValue(usize),
+    #[error("line {0}: invalid number")]
+    NonFloatValue(usize, #[source] std::num::ParseFloatError),
+    #[error("line {0}: invalid easing")]
+    InvalidEasing(usize, #[source] ParseEasingError),
+    #[error("line {0}: too many values")]
+    TrailingValue(usize),
+    #[error("line {0}: keyframe times must increase")]
+    TimeNotIncreasing(usize),
+    #[error("camera path has no keyframes")]
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe {
+    pub time: f32,
+    pub position: Vec3,
+    pub target: Vec3,
+    /// Shapes the move to the next keyframe, None is linear
+    pub easing: Option<Easing>,
+}
+
+impl Keyframe {
+    fn orientation(&self) -> Quat {
+        let look_at = Transform::look_at(self.position, self.target, [0.0, 1.0, 0.0].into());
+        Quat::from_rotation(&look_at)
+    }
+}
+
+/// Keyframed camera movement. Positions are interpolated linearly, orientations with slerp so
+/// the camera turns smoothly between targets
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+    looped: bool,
+}
+
+impl CameraPath {
+    /// None if there are no keyframes or their times don't increase
+    pub fn new(keyframes: Vec<Keyframe>, looped: bool) -> Option<CameraPath> {
+        if keyframes.is_empty() || keyframes.windows(2).any(|w| w[1].time <= w[0].time) {
+            return None;
+        }
+
+        Some(CameraPath { keyframes, looped })
+    }
+
+    /// Slow wobble around the desk, the path the camera has always taken
+    pub fn default_orbit() -> CameraPath {
+        const NUM_KEYFRAMES: usize = 32;
+        let period = 16.0 * std::f32::consts::PI;
+
+        let keyframes = (0..=NUM_KEYFRAMES)
+            .map(|i| {
+                let time = period * i as f32 / NUM_KEYFRAMES as f32;
+                let z_offs = f32::sin(time / 8.0) * 0.05;
+                let y_offs = f32::cos(time / 8.0) * 0.05;
+                Keyframe {
+                    time,
+                    position: [0.6, 0.20 + y_offs, -0.05 + z_offs].into(),
+                    target: [0.16, 0.045, 0.0].into(),
+                    easing: None,
+                }
+            })
+            .collect();
+
+        CameraPath {
+            keyframes,
+            looped: true,
+        }
+    }
+
+    /// One keyframe per line as `time px py pz tx ty tz [easing]`, where easing is one of the
+    /// Easing names and shapes the move to the next keyframe. Blank lines and lines starting
+    /// with # are ignored, a line containing only `loop` makes the path restart after the last
+    /// keyframe
+    pub fn from_reader<R: BufRead>(r: R) -> Result<CameraPath, CameraPathParseError> {
+        let mut keyframes: Vec<Keyframe> = Vec::new();
+        let mut looped = false;
+
+        for (i, line) in r.lines().enumerate() {
+            let line = line.map_err(CameraPathParseError::FileRead)?;
+            let line_num = i + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line == "loop" {
+                looped = true;
+                continue;
+            }
+
+            let mut values = [0.0f32; 7];
+            let mut it = line.split_whitespace();
+            for v in &mut values {
+                *v = it
+                    .next()
+                    .ok_or(CameraPathParseError::MissingValue(line_num))?
+                    .parse()
+                    .map_err(|e| CameraPathParseError::NonFloatValue(line_num, e))?;
+            }
+
+            let easing = it
+                .next()
+                .map(|v| v.parse())
+                .transpose()
+                .map_err(|e| CameraPathParseError::InvalidEasing(line_num, e))?;
+
+            if it.next().is_some() {
+                return Err(CameraPathParseError::TrailingValue(line_num));
+            }
+
+            let keyframe = Keyframe {
+                time: values[0],
+                position: [values[1], values[2], values[3]].into(),
+                target: [values[4], values[5], values[6]].into(),
+                easing,
+            };
+
+            if let Some(last) = keyframes.last() {
+                if keyframe.time <= last.time {
+                    return Err(CameraPathParseError::TimeNotIncreasing(line_num));
+                }
+            }
+
+            keyframes.push(keyframe);
+        }
+
+        if keyframes.is_empty() {
+            return Err(CameraPathParseError::Empty);
+        }
+
+        Ok(CameraPath { keyframes, looped })
+    }
+
+    /// Camera to world transform at the given time, callers invert it for a view matrix
+    pub fn sample(&self, time: f32) -> Transform {
+        let first = &self.keyframes[0];
+        let last = &self.keyframes[self.keyframes.len() - 1];
+
+        let duration = last.time - first.time;
+        let time = if self.looped && duration > 0.0 {
+            first.time + (time - first.time).rem_euclid(duration)
+        } else {
+            time.clamp(first.time, last.time)
+        };
+
+        let next_idx = self
+            .keyframes
+            .iter()
+            .position(|k| k.time > time)
+            .unwrap_or(self.keyframes.len() - 1);
+        let prev_idx = next_idx.saturating_sub(1);
+
+        let prev = &self.keyframes[prev_idx];
+        let next = &self.keyframes[next_idx];
+
+        let t = if next.time > prev.time {
+            ((time - prev.time) / (next.time - prev.time)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let t = prev.easing.map_or(t, |easing| easing.apply(t));
+
+        let position = prev.position.lerp(next.position, t);
+        let orientation = prev.orientation().slerp(&next.orientation(), t);
+
+        Transform::from_translation(position.x(), position.y(), position.z())
+            * Transform::from_quaternion(orientation)
+    }
+}
+
+/// Swings a camera to world transform yaw radians around the world Y axis, then pitches it up tilt
+/// radians in place
+pub fn framed(camera: &Transform, yaw: f32, tilt: f32) -> Transform {
+    // The camera looks down +z, so a positive turn about X would point it down
+    Transform::from_axis_angle(yaw, Axis::Y) * camera * &Transform::from_axis_angle(-tilt, Axis::X)
+}
+
+/// Moves a camera to world transform towards rest, position with lerp and orientation with
+/// slerp. t of 0 gives camera, 1 gives rest
+pub fn blend(camera: &Transform, rest: &Transform, t: f32) -> Transform {
+    let origin: Vec3 = [0.0, 0.0, 0.0].into();
+    let position = camera
+        .transform_point(origin)
+        .lerp(rest.transform_point(origin), t);
+    let orientation = Quat::from_rotation(camera).slerp(&Quat::from_rotation(rest), t);
+
+    Transform::from_translation(position.x(), position.y(), position.z())
+        * Transform::from_quaternion(orientation)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util::assert_transforms_eq;
+
+    #[test]
+    fn test_sample_at_keyframe_matches_look_at() {
+        let path = CameraPath::from_reader(
+            b"# time pos target\n0 0 0 0 0 0 1\n2 1 0 0 1 0 1\n".as_slice(),
+        )
+        .unwrap();
+
+        assert_transforms_eq(
+            &path.sample(2.0),
+            &Transform::look_at(
+                [1.0, 0.0, 0.0].into(),
+                [1.0, 0.0, 1.0].into(),
+                [0.0, 1.0, 0.0].into(),
+            ),
+        );
+
+        // Past the end of a non looping path we stay put
+        assert_transforms_eq(&path.sample(5.0), &path.sample(2.0));
+
+        let halfway = path.sample(1.0);
+        assert!((halfway.arr[0][3] - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_eased_segment() {
+        let path =
+            CameraPath::from_reader(b"0 0 0 0 0 0 1 in-out-sine\n4 1 0 0 1 0 1\n".as_slice())
+                .unwrap();
+
+        for time in [1.0, 2.0, 3.0] {
+            let t = crate::ease::in_out_sine(time / 4.0);
+            let x = path.sample(time).arr[0][3];
+            assert!((x - t).abs() < 0.001, "{x} != {t}");
+        }
+        // Slow out of the first keyframe
+        assert!(path.sample(1.0).arr[0][3] < 0.25);
+    }
+
+    #[test]
+    fn test_looped_path_wraps() {
+        let path =
+            CameraPath::from_reader(b"loop\n0 0 0 0 0 0 1\n4 1 0 0 1 0 1\n".as_slice()).unwrap();
+        assert_transforms_eq(&path.sample(5.0), &path.sample(1.0));
+    }
+
+    #[test]
+    fn test_default_orbit_matches_wobble() {
+        let path = CameraPath::default_orbit();
+        let time = 3.0f32;
+        let expected = Transform::look_at(
+            [
+                0.6,
+                0.20 + f32::cos(time / 8.0) * 0.05,
+                -0.05 + f32::sin(time / 8.0) * 0.05,
+            ]
+            .into(),
+            [0.16, 0.045, 0.0].into(),
+            [0.0, 1.0, 0.0].into(),
+        );
+        assert_transforms_eq(&path.sample(time), &expected);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            CameraPath::from_reader(b"".as_slice()),
+            Err(CameraPathParseError::Empty)
+        ));
+        assert!(matches!(
+            CameraPath::from_reader(b"0 1 2\n".as_slice()),
+            Err(CameraPathParseError::MissingValue(1))
+        ));
+        assert!(matches!(
+            CameraPath::from_reader(b"0 0 0 0 0 0 1\n0 0 0 0 0 0 1\n".as_slice()),
+            Err(CameraPathParseError::TimeNotIncreasing(2))
+        ));
+        assert!(matches!(
+            CameraPath::from_reader(b"0 0 0 0 0 0 1 sideways\n".as_slice()),
+            Err(CameraPathParseError::InvalidEasing(1, _))
+        ));
+        assert!(matches!(
+            CameraPath::from_reader(b"0 0 0 0 0 0 1 in-sine 2\n".as_slice()),
+            Err(CameraPathParseError::TrailingValue(1))
+        ));
+    }
+
+    #[test]
+    fn test_framed() {
+        let camera = Transform::from_translation(1.0, 2.0, 0.0);
+        assert_transforms_eq(&framed(&camera, 0.0, 0.0), &camera);
+
+        // Yaw orbits the world origin, tilt only turns the camera in place
+        let quarter = std::f32::consts::FRAC_PI_2;
+        let swung = framed(&camera, quarter, 0.3);
+        let pos = swung.transform_point([0.0, 0.0, 0.0].into());
+        assert!((pos.x() - 0.0).abs() < 0.001);
+        assert!((pos.y() - 2.0).abs() < 0.001);
+        assert!((pos.z() + 1.0).abs() < 0.001);
+
+        let tilted = framed(&camera, 0.0, 0.3);
+        let forward = tilted.transform_point([0.0, 0.0, 1.0].into());
+        assert!(forward.y() > 2.0);
+    }
+
+    #[test]
+    fn test_blend() {
+        let camera = Transform::from_translation(1.0, 0.0, 0.0)
+            * Transform::from_axis_angle(std::f32::consts::FRAC_PI_2, Axis::Y);
+        let rest = Transform::from_translation(0.0, 0.0, 2.0);
+
+        assert_transforms_eq(&blend(&camera, &rest, 0.0), &camera);
+        assert_transforms_eq(&blend(&camera, &rest, 1.0), &rest);
+
+        let halfway = blend(&camera, &rest, 0.5);
+        let pos = halfway.transform_point([0.0, 0.0, 0.0].into());
+        assert!((pos.x() - 0.5).abs() < 0.001);
+        assert!((pos.z() - 1.0).abs() < 0.001);
+        let expected = Transform::from_translation(0.5, 0.0, 1.0)
+            * Transform::from_axis_angle(std::f32::consts::FRAC_PI_4, Axis::Y);
+        assert_transforms_eq(&halfway, &expected);
+    }
+}
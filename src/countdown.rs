@@ -0,0 +1,147 @@
+use thiserror::Error;
+
+use std::time::{Duration, Instant};
+
+use crate::timestep::PausableClock;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ParseDurationError {
+    #[error("expected a duration like 5m, 90s or 1h30m")]
+    Empty,
+    #[error("invalid number {0:?}")]
+    InvalidNumber(String),
+    #[error("unknown unit {0:?}, expected h, m or s")]
+    UnknownUnit(char),
+    #[error("duration is too long")]
+    Overflow,
+}
+
+/// Numbers followed by h, m or s, in any combination like 1h30m. A bare number is seconds
+pub fn parse_duration(s: &str) -> Result<Duration, ParseDurationError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(ParseDurationError::Empty);
+    }
+
+    let mut total = 0;
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            c => return Err(ParseDurationError::UnknownUnit(c)),
+        };
+        let value: u64 = number
+            .parse()
+            .map_err(|_| ParseDurationError::InvalidNumber(number.clone()))?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|v| v.checked_add(total))
+            .ok_or(ParseDurationError::Overflow)?;
+        number.clear();
+    }
+
+    if !number.is_empty() {
+        let value: u64 = number
+            .parse()
+            .map_err(|_| ParseDurationError::InvalidNumber(number.clone()))?;
+        total = total
+            .checked_add(value)
+            .ok_or(ParseDurationError::Overflow)?;
+    }
+
+    Ok(Duration::from_secs(total))
+}
+
+/// Counts down from a fixed duration on monotonic time, unaffected by wall clock changes. Pausing
+/// holds the remaining time where it is
+pub struct Countdown {
+    duration: Duration,
+    start: Instant,
+    clock: PausableClock,
+}
+
+impl Countdown {
+    pub fn new(duration: Duration, start: Instant) -> Countdown {
+        Countdown {
+            duration,
+            start,
+            clock: PausableClock::default(),
+        }
+    }
+
+    pub fn remaining(&self, real: Instant) -> Duration {
+        let elapsed = self.clock.now(real).saturating_duration_since(self.start);
+        self.duration.saturating_sub(elapsed)
+    }
+
+    pub fn toggle_pause(&mut self, real: Instant) {
+        self.clock.toggle_pause(real);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2m30"), Ok(Duration::from_secs(150)));
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+
+        assert_eq!(parse_duration(""), Err(ParseDurationError::Empty));
+        assert_eq!(
+            parse_duration("5d"),
+            Err(ParseDurationError::UnknownUnit('d'))
+        );
+        assert_eq!(
+            parse_duration("m"),
+            Err(ParseDurationError::InvalidNumber(String::new()))
+        );
+        assert_eq!(
+            parse_duration("9999999999999999h"),
+            Err(ParseDurationError::Overflow)
+        );
+        assert_eq!(
+            parse_duration("18446744073709551615s1"),
+            Err(ParseDurationError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_countdown_remaining() {
+        let start = Instant::now();
+        let mut countdown = Countdown::new(Duration::from_secs(300), start);
+        assert_eq!(countdown.remaining(start), Duration::from_secs(300));
+        assert_eq!(
+            countdown.remaining(start + Duration::from_secs(60)),
+            Duration::from_secs(240)
+        );
+
+        // Held through a 30 second pause
+        countdown.toggle_pause(start + Duration::from_secs(60));
+        assert_eq!(
+            countdown.remaining(start + Duration::from_secs(90)),
+            Duration::from_secs(240)
+        );
+        countdown.toggle_pause(start + Duration::from_secs(90));
+        assert_eq!(
+            countdown.remaining(start + Duration::from_secs(100)),
+            Duration::from_secs(230)
+        );
+
+        assert_eq!(
+            countdown.remaining(start + Duration::from_secs(1000)),
+            Duration::ZERO
+        );
+    }
+}
@@ -27,12 +27,16 @@ unsafe fn generate_square_buffer(gl: &glow::Context) -> NativeBuffer {
     vbo
 }
 
+/// Flat colored quads in text space. Translucent colors need the blending from
+/// gl_util::begin_pass, overlays turn the depth test off around their draws
 pub struct CursorRenderer<'a> {
     program: NativeProgram,
     vao: NativeVertexArray,
     vbo: NativeBuffer,
     gl: &'a glow::Context,
     aspect_loc: <glow::Context as HasContext>::UniformLocation,
+    color_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    smooth_edges_loc: Option<<glow::Context as HasContext>::UniformLocation>,
 }
 
 impl<'a> CursorRenderer<'a> {
@@ -42,7 +46,7 @@ impl<'a> CursorRenderer<'a> {
                 gl,
                 include_str!("glsl/color_vertex.glsl"),
                 include_str!("glsl/color_fragment.glsl"),
-            );
+            )?;
 
             let vao = gl.create_vertex_array().map_err(GlError)?;
             gl.bind_vertex_array(Some(vao));
@@ -61,17 +65,38 @@ impl<'a> CursorRenderer<'a> {
                 .get_uniform_location(program, "aspect_ratio")
                 .expect("Invalid vertex shader");
 
+            let color_loc = gl.get_uniform_location(program, "color");
+            let smooth_edges_loc = gl.get_uniform_location(program, "smooth_edges");
+
             Ok(CursorRenderer {
                 program,
                 vao,
                 vbo,
                 gl,
                 aspect_loc,
+                color_loc,
+                smooth_edges_loc,
             })
         }
     }
 
+    /// Fades the outermost pixel of every quad drawn after this so edges don't look jagged, off by
+    /// default. Quads lose about half a pixel on each side
+    pub fn set_smooth_edges(&self, smooth: bool) {
+        unsafe {
+            self.gl.use_program(Some(self.program));
+            self.gl
+                .uniform_1_i32(self.smooth_edges_loc.as_ref(), smooth as i32);
+            self.gl.use_program(None);
+        }
+    }
+
     pub fn render(&self, x: f32, y: f32, w: f32, h: f32, aspect: f32) {
+        self.render_colored(x, y, w, h, aspect, [1.0; 4]);
+    }
+
+    /// color is RGBA, blended with whatever is already drawn
+    pub fn render_colored(&self, x: f32, y: f32, w: f32, h: f32, aspect: f32, color: [f32; 4]) {
         let gl = self.gl;
 
         unsafe {
@@ -84,6 +109,7 @@ impl<'a> CursorRenderer<'a> {
             gl.buffer_sub_data_u8_slice(glow::ARRAY_BUFFER, 0, f32_to_u8_slice(verts));
 
             gl.uniform_1_f32(Some(&self.aspect_loc), aspect);
+            gl.uniform_4_f32_slice(self.color_loc.as_ref(), &color);
 
             gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
             gl.bind_vertex_array(None);
@@ -1,4 +1,225 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("unknown easing function")]
+pub struct ParseEasingError;
+
+/// Selects one of the easing functions below, e.g. for an animation to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    InSine,
+    InOutSine,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    InCubic,
+    OutCubic,
+    InOutCubic,
+    InBounce,
+    OutBounce,
+    InOutBounce,
+}
+
+impl Easing {
+    const NAMES: &'static [(&'static str, Easing)] = &[
+        ("in-sine", Easing::InSine),
+        ("in-out-sine", Easing::InOutSine),
+        ("in-quad", Easing::InQuad),
+        ("out-quad", Easing::OutQuad),
+        ("in-out-quad", Easing::InOutQuad),
+        ("in-cubic", Easing::InCubic),
+        ("out-cubic", Easing::OutCubic),
+        ("in-out-cubic", Easing::InOutCubic),
+        ("in-bounce", Easing::InBounce),
+        ("out-bounce", Easing::OutBounce),
+        ("in-out-bounce", Easing::InOutBounce),
+    ];
+
+    pub fn apply(self, val: f32) -> f32 {
+        match self {
+            Easing::InSine => in_sine(val),
+            Easing::InOutSine => in_out_sine(val),
+            Easing::InQuad => in_quad(val),
+            Easing::OutQuad => out_quad(val),
+            Easing::InOutQuad => in_out_quad(val),
+            Easing::InCubic => in_cubic(val),
+            Easing::OutCubic => out_cubic(val),
+            Easing::InOutCubic => in_out_cubic(val),
+            Easing::InBounce => in_bounce(val),
+            Easing::OutBounce => out_bounce(val),
+            Easing::InOutBounce => in_out_bounce(val),
+        }
+    }
+
+    /// Comma separated list of names accepted by from_str, for help text
+    pub fn names() -> String {
+        Self::NAMES
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl std::str::FromStr for Easing {
+    type Err = ParseEasingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, easing)| *easing)
+            .ok_or(ParseEasingError)
+    }
+}
+
 pub fn in_sine(val: f32) -> f32 {
     use std::f32::consts::PI;
     1.0 - f32::cos((val * PI) / 2.0)
 }
+
+pub fn in_out_sine(val: f32) -> f32 {
+    use std::f32::consts::PI;
+    (1.0 - f32::cos(val * PI)) / 2.0
+}
+
+pub fn in_quad(val: f32) -> f32 {
+    val * val
+}
+
+pub fn out_quad(val: f32) -> f32 {
+    1.0 - (1.0 - val) * (1.0 - val)
+}
+
+pub fn in_out_quad(val: f32) -> f32 {
+    if val < 0.5 {
+        2.0 * val * val
+    } else {
+        1.0 - (-2.0 * val + 2.0).powi(2) / 2.0
+    }
+}
+
+pub fn in_cubic(val: f32) -> f32 {
+    val * val * val
+}
+
+pub fn out_cubic(val: f32) -> f32 {
+    1.0 - (1.0 - val).powi(3)
+}
+
+pub fn in_out_cubic(val: f32) -> f32 {
+    if val < 0.5 {
+        4.0 * val * val * val
+    } else {
+        1.0 - (-2.0 * val + 2.0).powi(3) / 2.0
+    }
+}
+
+/// Ball dropped onto the floor, settling at 1 after a few smaller bounces
+pub fn out_bounce(val: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if val < 1.0 / D {
+        N * val * val
+    } else if val < 2.0 / D {
+        let val = val - 1.5 / D;
+        N * val * val + 0.75
+    } else if val < 2.5 / D {
+        let val = val - 2.25 / D;
+        N * val * val + 0.9375
+    } else {
+        let val = val - 2.625 / D;
+        N * val * val + 0.984375
+    }
+}
+
+pub fn in_bounce(val: f32) -> f32 {
+    1.0 - out_bounce(1.0 - val)
+}
+
+pub fn in_out_bounce(val: f32) -> f32 {
+    if val < 0.5 {
+        (1.0 - out_bounce(1.0 - 2.0 * val)) / 2.0
+    } else {
+        (1.0 + out_bounce(2.0 * val - 1.0)) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type EaseFn = fn(f32) -> f32;
+
+    const FUNCS: &[EaseFn] = &[
+        in_sine,
+        in_out_sine,
+        in_quad,
+        out_quad,
+        in_out_quad,
+        in_cubic,
+        out_cubic,
+        in_out_cubic,
+        in_bounce,
+        out_bounce,
+        in_out_bounce,
+    ];
+
+    #[test]
+    fn test_endpoints() {
+        for f in FUNCS {
+            assert!(f(0.0).abs() < 0.0001);
+            assert!((f(1.0) - 1.0).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_in_out_symmetric() {
+        let funcs: [EaseFn; 4] = [in_out_sine, in_out_quad, in_out_cubic, in_out_bounce];
+        for f in funcs {
+            assert!((f(0.5) - 0.5).abs() < 0.0001);
+            for i in 0..=10 {
+                let t = i as f32 / 20.0;
+                assert!((f(t) + f(1.0 - t) - 1.0).abs() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_out_mirrors_in() {
+        let pairs: [(EaseFn, EaseFn); 2] = [(in_quad, out_quad), (in_cubic, out_cubic)];
+        for (in_f, out_f) in pairs {
+            for i in 0..=10 {
+                let t = i as f32 / 10.0;
+                assert!((out_f(t) - (1.0 - in_f(1.0 - t))).abs() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bounce() {
+        for i in 0..=1000 {
+            let v = out_bounce(i as f32 / 1000.0);
+            assert!((0.0..=1.0001).contains(&v), "{v}");
+        }
+
+        // Each bounce bottoms out at these heights
+        const D: f32 = 2.75;
+        for (t, expected) in [(1.5 / D, 0.75), (2.25 / D, 0.9375), (2.625 / D, 0.984375)] {
+            assert!((out_bounce(t) - expected).abs() < 0.0001);
+            assert!(out_bounce(t - 0.01) > out_bounce(t));
+            assert!(out_bounce(t + 0.01) > out_bounce(t));
+        }
+    }
+
+    #[test]
+    fn test_easing_from_str() {
+        assert_eq!("out-bounce".parse::<Easing>().unwrap(), Easing::OutBounce);
+        assert!("sideways".parse::<Easing>().is_err());
+        for (name, easing) in Easing::NAMES {
+            assert_eq!(name.parse::<Easing>().unwrap(), *easing);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::mat::{Transform, Vec3};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn from_bounding_box(min: Vec3, max: Vec3) -> BoundingSphere {
+        let center = [
+            (min.x() + max.x()) / 2.0,
+            (min.y() + max.y()) / 2.0,
+            (min.z() + max.z()) / 2.0,
+        ]
+        .into();
+        let radius = max.distance(&min) / 2.0;
+        BoundingSphere { center, radius }
+    }
+
+    /// Move the sphere into the space of the given transform. Non uniform scales grow the sphere
+    /// by the largest axis so that it still contains the transformed object
+    pub fn transformed(&self, transform: &Transform) -> BoundingSphere {
+        BoundingSphere {
+            center: transform.transform_point(self.center),
+            radius: self.radius * transform.max_axis_scale(),
+        }
+    }
+}
+
+/// Axis aligned box
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl BoundingBox {
+    fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        std::array::from_fn(|i| {
+            [
+                if i & 1 == 0 { min.x() } else { max.x() },
+                if i & 2 == 0 { min.y() } else { max.y() },
+                if i & 4 == 0 { min.z() } else { max.z() },
+            ]
+            .into()
+        })
+    }
+
+    /// The axis aligned box containing the transformed corners, so rotations grow it
+    pub fn transformed(&self, transform: &Transform) -> BoundingBox {
+        let mut min = Vec3::from([f32::MAX; 3]);
+        let mut max = Vec3::from([f32::MIN; 3]);
+        for corner in self.corners() {
+            let p = transform.transform_point(corner);
+            min = min.min(&p);
+            max = max.max(&p);
+        }
+        BoundingBox { min, max }
+    }
+
+    /// The 12 edges as line segments
+    pub fn edges(&self) -> Vec<[Vec3; 2]> {
+        let corners = self.corners();
+        let mut edges = Vec::with_capacity(12);
+        // Corner indices are xyz bit masks, edges join corners one bit apart
+        for a in 0..8 {
+            for bit in [1, 2, 4] {
+                if a & bit == 0 {
+                    edges.push([corners[a], corners[a | bit]]);
+                }
+            }
+        }
+        edges
+    }
+}
+
+/// Six clip planes stored as [a, b, c, d] where a point is inside if ax + by + cz + d >= 0
+pub struct Frustum {
+    planes: [[f32; 4]; 6],
+}
+
+impl Frustum {
+    /// Extract the clip planes from a combined view-projection transform
+    ///
+    /// After projection a point is visible if -w <= x, y, z <= w. Each of those inequalities is a
+    /// plane in world space, e.g. x >= -w becomes (row3 + row0) . p >= 0
+    pub fn from_transform(transform: &Transform) -> Frustum {
+        let m = &transform.arr;
+        let mut planes = [[0.0f32; 4]; 6];
+
+        for axis in 0..3 {
+            for i in 0..4 {
+                planes[axis * 2][i] = m[3][i] + m[axis][i];
+                planes[axis * 2 + 1][i] = m[3][i] - m[axis][i];
+            }
+        }
+
+        for plane in &mut planes {
+            let len = Vec3::from([plane[0], plane[1], plane[2]]).length();
+            for v in plane.iter_mut() {
+                *v /= len;
+            }
+        }
+
+        Frustum { planes }
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        let c = sphere.center;
+        self.planes.iter().all(|plane| {
+            let dist = plane[0] * c.x() + plane[1] * c.y() + plane[2] * c.z() + plane[3];
+            dist >= -sphere.radius
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sphere(center: [f32; 3], radius: f32) -> BoundingSphere {
+        BoundingSphere {
+            center: center.into(),
+            radius,
+        }
+    }
+
+    #[test]
+    fn test_identity_planes() {
+        let frustum = Frustum::from_transform(&Transform::identity());
+        let expected = [
+            [1.0, 0.0, 0.0, 1.0],
+            [-1.0, 0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0, 1.0],
+            [0.0, -1.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0, 1.0],
+            [0.0, 0.0, -1.0, 1.0],
+        ];
+
+        for (plane, expected) in frustum.planes.iter().zip(expected) {
+            for (a, b) in plane.iter().zip(expected) {
+                assert!((a - b).abs() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sphere_in_identity_frustum() {
+        let frustum = Frustum::from_transform(&Transform::identity());
+        assert!(frustum.intersects_sphere(&sphere([0.0, 0.0, 0.0], 0.1)));
+        assert!(frustum.intersects_sphere(&sphere([1.5, 0.0, 0.0], 1.0)));
+        assert!(!frustum.intersects_sphere(&sphere([3.0, 0.0, 0.0], 1.0)));
+        assert!(!frustum.intersects_sphere(&sphere([0.0, -2.5, 0.0], 1.0)));
+    }
+
+    #[test]
+    fn test_sphere_in_perspective_frustum() {
+        let frustum =
+            Frustum::from_transform(&Transform::perspective(90.0f32.to_radians(), 0.1, 10.0));
+
+        assert!(frustum.intersects_sphere(&sphere([0.0, 0.0, 5.0], 0.5)));
+        // Behind the camera
+        assert!(!frustum.intersects_sphere(&sphere([0.0, 0.0, -5.0], 0.5)));
+        // Past the far plane
+        assert!(!frustum.intersects_sphere(&sphere([0.0, 0.0, 20.0], 0.5)));
+        // 90 degree fov, so at z = 5 the frustum is 5 wide in each direction
+        assert!(frustum.intersects_sphere(&sphere([4.5, 0.0, 5.0], 0.1)));
+        assert!(!frustum.intersects_sphere(&sphere([6.0, 0.0, 5.0], 0.1)));
+    }
+
+    #[test]
+    fn test_box_edges() {
+        let b = BoundingBox {
+            min: [0.0, 0.0, 0.0].into(),
+            max: [1.0, 2.0, 3.0].into(),
+        };
+        let edges = b.edges();
+        assert_eq!(edges.len(), 12);
+
+        let total_length: f32 = edges.iter().map(|[a, b]| a.distance(b)).sum();
+        assert!((total_length - 4.0 * (1.0 + 2.0 + 3.0)).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_transformed_box() {
+        let b = BoundingBox {
+            min: [-1.0, -1.0, -1.0].into(),
+            max: [1.0, 1.0, 1.0].into(),
+        }
+        .transformed(
+            &(Transform::from_translation(5.0, 0.0, 0.0)
+                * Transform::from_axis_angle(45.0f32.to_radians(), crate::mat::Axis::Z)),
+        );
+
+        let half_diagonal = 2.0f32.sqrt();
+        assert!((b.min.x() - (5.0 - half_diagonal)).abs() < 0.0001);
+        assert!((b.max.y() - half_diagonal).abs() < 0.0001);
+        assert!((b.max.z() - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_transformed_sphere() {
+        let s = sphere([1.0, 0.0, 0.0], 1.0).transformed(
+            &(Transform::from_translation(0.0, 2.0, 0.0) * Transform::scale(2.0, 1.0, 3.0)),
+        );
+        assert!((s.center.x() - 2.0).abs() < 0.0001);
+        assert!((s.center.y() - 2.0).abs() < 0.0001);
+        assert!((s.radius - 3.0).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,426 @@
+//! Helpers for tests that need a real GL context. Only built with the gl-tests feature as they
+//! need a display to create a window on
+
+use glfw::{fail_on_errors, Context};
+
+use std::sync::Mutex;
+
+// GLFW is not thread safe, and the test runner runs tests in parallel
+static GL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Run f with a current GL context backed by a hidden window
+pub fn with_gl_context<F: FnOnce(&glow::Context)>(f: F) {
+    let _guard = GL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut glfw = glfw::init(fail_on_errors!()).expect("failed to init glfw");
+    glfw.window_hint(glfw::WindowHint::Visible(false));
+
+    let (mut window, _events) = glfw
+        .create_window(64, 64, "gl test", glfw::WindowMode::Windowed)
+        .expect("failed to create hidden window");
+    window.make_current();
+
+    let gl =
+        unsafe { glow::Context::from_loader_function(|s| window.get_proc_address(s) as *const _) };
+
+    f(&gl);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use glow::HasContext;
+
+    use crate::{
+        cursor_renderer::CursorRenderer,
+        glyph_cache::{GlyphCache, GlyphCacheStats, PRINTABLE_ASCII},
+        glyph_renderer::{GlyphRenderer, TextDirection, TextOverflow, TextRegion, TextStyle},
+        line_renderer::LineRenderer,
+        mat::Transform,
+        mesh_renderer::MeshRenderer,
+        obj_parser::Mesh,
+        postprocess::{PostprocessChain, PostprocessPass},
+        rng::Rng,
+        screen_tex_postprocess::ScreenTexPostprocessor,
+    };
+
+    const TRIANGLE_OBJ: &str = "\
+        v 0.0 0.0 0.0\n\
+        v 1.0 0.0 0.0\n\
+        v 0.0 1.0 0.0\n\
+        vt 0.0 0.0\n\
+        vn 0.0 0.0 1.0\n\
+        f 1/1/1 2/1/1 3/1/1\n";
+
+    #[test]
+    fn test_mesh_renderer_smoke() {
+        with_gl_context(|gl| {
+            let renderer = MeshRenderer::new(gl, true, true).unwrap();
+            let mesh = Mesh::from_obj_file(TRIANGLE_OBJ.as_bytes()).unwrap();
+            let tex = unsafe {
+                crate::gl_util::create_tex_default_params(gl, glow::REPEAT, glow::REPEAT).unwrap()
+            };
+            let gpu_mesh = renderer.upload_mesh(&mesh, Some(tex)).unwrap();
+
+            renderer.set_tint(&[1.0, 0.5, 0.5], 0.5);
+            renderer.set_soft_shadows(8, 3.0);
+            renderer.set_alpha_cutoff(0.5);
+            renderer.render(&gpu_mesh, &Transform::identity());
+            assert_eq!(renderer.draw_calls(), 1);
+
+            let transforms = [
+                Transform::identity(),
+                Transform::from_translation(1.0, 0.0, 0.0),
+                Transform::from_translation(2.0, 0.0, 0.0),
+            ];
+            renderer.render_instanced(&gpu_mesh, &transforms);
+            assert_eq!(renderer.draw_calls(), 2);
+            assert_eq!(renderer.meshes_drawn(), 4);
+
+            renderer.reset_counters();
+            assert_eq!(renderer.draw_calls(), 0);
+            assert_eq!(renderer.meshes_drawn(), 0);
+
+            // The shader has to compile without the light texture too
+            MeshRenderer::new(gl, true, false).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_untextured_mesh() {
+        with_gl_context(|gl| {
+            let renderer = MeshRenderer::new(gl, true, false).unwrap();
+            let mesh = Mesh::from_obj_file(TRIANGLE_OBJ.as_bytes()).unwrap();
+            let gpu_mesh = renderer.upload_mesh(&mesh, None).unwrap();
+            assert!(gpu_mesh.tex.is_none());
+
+            renderer.set_untextured_color(&[0.2, 0.4, 0.6]);
+            renderer.set_emissive(&[1.0, 1.0, 1.0]);
+            renderer.render(&gpu_mesh, &Transform::identity());
+            renderer.render_instanced(&gpu_mesh, &[Transform::identity()]);
+            assert_eq!(renderer.draw_calls(), 2);
+        });
+    }
+
+    #[test]
+    fn test_failed_reload_keeps_shader() {
+        with_gl_context(|gl| {
+            let renderer = MeshRenderer::new(gl, true, true).unwrap();
+            let mesh = Mesh::from_obj_file(TRIANGLE_OBJ.as_bytes()).unwrap();
+            let tex = unsafe {
+                crate::gl_util::create_tex_default_params(gl, glow::REPEAT, glow::REPEAT).unwrap()
+            };
+            let gpu_mesh = renderer.upload_mesh(&mesh, Some(tex)).unwrap();
+
+            assert!(renderer.reload_fragment_shader("not glsl").is_err());
+            renderer.render(&gpu_mesh, &Transform::identity());
+            assert_eq!(renderer.draw_calls(), 1);
+
+            renderer
+                .reload_fragment_shader(include_str!("glsl/3d_fragment.glsl"))
+                .unwrap();
+            renderer.render(&gpu_mesh, &Transform::identity());
+            assert_eq!(renderer.draw_calls(), 2);
+        });
+    }
+
+    #[test]
+    fn test_glyph_renderer_smoke() {
+        with_gl_context(|gl| {
+            let mut glyph_cache = GlyphCache::new(32, None, 0).unwrap();
+            glyph_cache.get_character(gl, 'a').unwrap();
+            glyph_cache.preload(gl, "abc").unwrap();
+            assert_eq!(glyph_cache.stats().entries, 3);
+
+            let mut renderer = GlyphRenderer::new(gl, &mut glyph_cache).unwrap();
+            renderer.render_str(
+                "hello\nworld",
+                0.0,
+                0.5,
+                TextRegion::FULL,
+                1.0,
+                TextStyle::default(),
+            );
+            assert_eq!(renderer.glyphs_drawn(), 10);
+            assert_eq!(renderer.draw_calls(), 10);
+            renderer.reset_counters();
+
+            let style = TextStyle {
+                direction: TextDirection::Rtl,
+                underline: true,
+                strikethrough: true,
+                no_wrap: false,
+                overflow: TextOverflow::Clip,
+                scale: 0.5,
+                color: [1.0, 0.5, 0.0],
+            };
+            let (pen_x, _) = renderer.render_str("שלום", 1.0, 0.5, TextRegion::FULL, 1.0, style);
+            assert!(pen_x < 1.0);
+        });
+    }
+
+    #[test]
+    fn test_bold_covers_more() {
+        with_gl_context(|gl| {
+            const SIZE: i32 = 1024;
+            let mut glyph_cache = GlyphCache::new(32, None, 0).unwrap();
+            let mut renderer = GlyphRenderer::new(gl, &mut glyph_cache).unwrap();
+
+            let mut covered = |s: &str| unsafe {
+                let (tex, fb) = crate::gl_util::setup_color_texture_render(gl, SIZE, SIZE).unwrap();
+                gl.viewport(0, 0, SIZE, SIZE);
+                gl.clear_color(0.0, 0.0, 0.0, 1.0);
+                gl.clear(glow::COLOR_BUFFER_BIT);
+                renderer.render_str(s, 0.5, 0.5, TextRegion::FULL, 1.0, TextStyle::default());
+
+                let pixels = crate::gl_util::read_framebuffer_rgba(gl, SIZE as u32, SIZE as u32);
+                gl.delete_framebuffer(fb);
+                gl.delete_texture(tex);
+                pixels.chunks(4).filter(|p| p[0] > 0).count()
+            };
+
+            let regular = covered("H");
+            let bold = covered("\x1b[1mH");
+            assert!(regular > 0);
+            assert!(
+                bold > regular,
+                "bold {bold} should cover more than {regular}"
+            );
+        });
+    }
+
+    #[test]
+    fn test_glyph_cache_stats() {
+        with_gl_context(|gl| {
+            let mut glyph_cache = GlyphCache::new(32, None, 0).unwrap();
+            for c in "abca".chars() {
+                glyph_cache.get_character(gl, c).unwrap();
+            }
+            assert_eq!(
+                glyph_cache.stats(),
+                GlyphCacheStats {
+                    hits: 1,
+                    misses: 3,
+                    entries: 3,
+                    pages: 1,
+                }
+            );
+
+            glyph_cache.clear(gl);
+            glyph_cache.get_character(gl, 'a').unwrap();
+            assert_eq!(
+                glyph_cache.stats(),
+                GlyphCacheStats {
+                    hits: 1,
+                    misses: 4,
+                    entries: 1,
+                    pages: 1,
+                }
+            );
+        });
+    }
+
+    #[test]
+    fn test_glyph_atlas_overflow() {
+        with_gl_context(|gl| {
+            let mut glyph_cache = GlyphCache::new(32, None, 0).unwrap();
+            // Only a handful of glyphs fit in a page this small
+            glyph_cache.set_atlas_page_size(128);
+            glyph_cache.preload(gl, PRINTABLE_ASCII).unwrap();
+            assert!(glyph_cache.stats().pages > 1);
+
+            // Glyphs keep their own place after later pages are added
+            let a = glyph_cache.get_character(gl, 'a').unwrap();
+            let (a_tex, a_uv) = (a.texture, a.uv);
+            let z = glyph_cache.get_character(gl, 'z').unwrap();
+            assert!(a_tex != z.texture || a_uv != z.uv);
+            for [u, v] in a_uv {
+                assert!((0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v));
+            }
+
+            let mut renderer = GlyphRenderer::new(gl, &mut glyph_cache).unwrap();
+            renderer.render_str(
+                PRINTABLE_ASCII,
+                -1.0,
+                0.5,
+                TextRegion::FULL,
+                1.0,
+                TextStyle::default(),
+            );
+        });
+    }
+
+    #[test]
+    fn test_cursor_renderer_smoke() {
+        with_gl_context(|gl| {
+            let renderer = CursorRenderer::new(gl).unwrap();
+            renderer.render(0.0, 0.0, 0.1, 0.1, 1.0);
+            renderer.set_smooth_edges(true);
+            renderer.render(0.0, 0.0, 0.1, 0.1, 1.0);
+        });
+    }
+
+    #[test]
+    fn test_smooth_quad_edges() {
+        with_gl_context(|gl| unsafe {
+            let (tex, fb) = crate::gl_util::setup_color_texture_render(gl, 16, 16).unwrap();
+            crate::gl_util::begin_pass(gl, 16, 16);
+            let renderer = CursorRenderer::new(gl).unwrap();
+
+            let row = |smooth| {
+                renderer.set_smooth_edges(smooth);
+                gl.clear_color(0.0, 0.0, 0.0, 1.0);
+                gl.clear(glow::COLOR_BUFFER_BIT);
+                // Covers pixels 4 to 11 on each axis
+                renderer.render(0.25, 0.25, 0.5, 0.5, 1.0);
+                let pixels = crate::gl_util::read_framebuffer_rgba(gl, 16, 16);
+                let y = 8;
+                (0..16)
+                    .map(|x| pixels[(y * 16 + x) * 4])
+                    .collect::<Vec<_>>()
+            };
+
+            let hard = row(false);
+            assert_eq!(hard[4], 255);
+            assert_eq!(hard[8], 255);
+
+            let smooth = row(true);
+            assert!(smooth[4] > 0 && smooth[4] < 255, "edge is {}", smooth[4]);
+            assert_eq!(smooth[8], 255);
+            assert_eq!(smooth[3], 0);
+
+            gl.delete_framebuffer(fb);
+            gl.delete_texture(tex);
+        });
+    }
+
+    #[test]
+    fn test_screen_tex_postprocessor_smoke() {
+        with_gl_context(|gl| {
+            let renderer = ScreenTexPostprocessor::new(gl).unwrap();
+            let tex = unsafe {
+                crate::gl_util::create_tex_default_params(
+                    gl,
+                    glow::CLAMP_TO_EDGE,
+                    glow::CLAMP_TO_EDGE,
+                )
+                .unwrap()
+            };
+            renderer.render(tex, 0.0, 1.0);
+        });
+    }
+
+    #[test]
+    fn test_color_depth_target() {
+        with_gl_context(|gl| unsafe {
+            let (color, depth, fb) = crate::gl_util::setup_color_depth_target(gl, 16, 16).unwrap();
+            assert_ne!(color, depth);
+
+            gl.clear_color(1.0, 0.0, 0.0, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+            let pixels = crate::gl_util::read_framebuffer_rgba(gl, 16, 16);
+            assert_eq!(&pixels[..4], &[255, 0, 0, 255]);
+
+            gl.delete_framebuffer(fb);
+            gl.delete_texture(color);
+            gl.delete_texture(depth);
+        });
+    }
+
+    #[test]
+    fn test_blit_flipped() {
+        with_gl_context(|gl| unsafe {
+            let (src_tex, src) = crate::gl_util::setup_color_texture_render(gl, 4, 2).unwrap();
+            gl.clear_color(0.0, 0.0, 0.0, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            // Red in the bottom left pixel only
+            gl.enable(glow::SCISSOR_TEST);
+            gl.scissor(0, 0, 1, 1);
+            gl.clear_color(1.0, 0.0, 0.0, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            gl.disable(glow::SCISSOR_TEST);
+
+            let (dst_tex, dst) = crate::gl_util::setup_color_texture_render(gl, 4, 2).unwrap();
+            let red_pixel = |horizontal, vertical| {
+                crate::gl_util::blit_flipped(gl, src, Some(dst), (4, 2), horizontal, vertical);
+                let pixels = crate::gl_util::read_framebuffer_rgba(gl, 4, 2);
+                pixels.chunks(4).position(|p| p[0] == 255).unwrap()
+            };
+
+            assert_eq!(red_pixel(false, false), 0);
+            assert_eq!(red_pixel(true, false), 3);
+            assert_eq!(red_pixel(false, true), 4);
+            assert_eq!(red_pixel(true, true), 7);
+
+            gl.delete_framebuffer(src);
+            gl.delete_framebuffer(dst);
+            gl.delete_texture(src_tex);
+            gl.delete_texture(dst_tex);
+        });
+    }
+
+    #[test]
+    fn test_begin_pass_resets_state() {
+        with_gl_context(|gl| unsafe {
+            gl.viewport(0, 0, 4096, 4096);
+            gl.disable(glow::BLEND);
+            gl.enable(glow::SCISSOR_TEST);
+            gl.depth_mask(false);
+            gl.active_texture(glow::TEXTURE1);
+
+            crate::gl_util::begin_pass(gl, 16, 8);
+
+            let mut viewport = [0; 4];
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut viewport);
+            assert_eq!(viewport, [0, 0, 16, 8]);
+            assert!(gl.is_enabled(glow::BLEND));
+            assert!(gl.is_enabled(glow::DEPTH_TEST));
+            assert!(!gl.is_enabled(glow::SCISSOR_TEST));
+            assert_eq!(gl.get_parameter_i32(glow::DEPTH_WRITEMASK), 1);
+            assert_eq!(
+                gl.get_parameter_i32(glow::ACTIVE_TEXTURE),
+                glow::TEXTURE0 as i32
+            );
+        });
+    }
+
+    #[test]
+    fn test_postprocess_chain_smoke() {
+        with_gl_context(|gl| {
+            let mut chain = PostprocessChain::new(gl).unwrap();
+            chain.push(PostprocessPass::chromatic_aberration(gl, 0.01).unwrap());
+            chain.push(PostprocessPass::noise(gl, 0.1, Rng::new(0)).unwrap());
+            chain.push(PostprocessPass::vignette(gl, 0.75, 0.45).unwrap());
+            let lut = crate::lut::Lut {
+                size: 2,
+                data: vec![0; 4 * 2 * 3],
+            };
+            chain.push(PostprocessPass::lut(gl, &lut).unwrap());
+
+            let (tex, fb) =
+                unsafe { crate::gl_util::setup_color_texture_render(gl, 16, 16) }.unwrap();
+            unsafe { gl.delete_framebuffer(fb) };
+
+            let output = chain.render(tex, 16, 16, 0.0, 1.0).unwrap();
+            assert_ne!(output, tex);
+        });
+    }
+
+    #[test]
+    fn test_line_renderer_smoke() {
+        with_gl_context(|gl| {
+            let renderer = LineRenderer::new(gl).unwrap();
+            let bounds = crate::frustum::BoundingBox {
+                min: [-0.5, -0.5, -0.5].into(),
+                max: [0.5, 0.5, 0.5].into(),
+            };
+            renderer.render(
+                &Transform::identity(),
+                &bounds.edges(),
+                [0.0, 1.0, 0.0, 1.0],
+            );
+            renderer.render(&Transform::identity(), &[], [0.0, 1.0, 0.0, 1.0]);
+        });
+    }
+}
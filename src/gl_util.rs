@@ -7,7 +7,7 @@ pub unsafe fn setup_depth_texture_render(
     width: i32,
     height: i32,
 ) -> Result<(NativeTexture, NativeFramebuffer), GlError> {
-    let tex = create_tex_default_params(gl)?;
+    let tex = create_tex_default_params(gl, glow::CLAMP_TO_EDGE, glow::CLAMP_TO_EDGE)?;
     gl.bind_texture(glow::TEXTURE_2D, Some(tex));
     gl.tex_image_2d(
         glow::TEXTURE_2D,
@@ -36,13 +36,56 @@ pub unsafe fn setup_depth_texture_render(
     Ok((tex, fb))
 }
 
+/// Puts the state every pass starts from back in place after setting up its target. Renderers
+/// assume this while drawing:
+/// - the viewport covers the whole target
+/// - depth test on with LESS, depth writes on
+/// - blending on with SRC_ALPHA, ONE_MINUS_SRC_ALPHA for color and ONE, ONE_MINUS_SRC_ALPHA for
+///   alpha, so drawing over a transparent clear leaves it as opaque as what was drawn
+/// - no scissor, texture unit 0 active
+///
+/// Anything that needs different state sets it for its own draw and puts it back, so a pass
+/// never depends on which renderer or pass ran before it. Clear colors are up to each pass
+pub unsafe fn begin_pass(gl: &glow::Context, width: i32, height: i32) {
+    gl.viewport(0, 0, width, height);
+    gl.enable(glow::DEPTH_TEST);
+    gl.depth_func(glow::LESS);
+    gl.depth_mask(true);
+    gl.enable(glow::BLEND);
+    gl.blend_func_separate(
+        glow::SRC_ALPHA,
+        glow::ONE_MINUS_SRC_ALPHA,
+        glow::ONE,
+        glow::ONE_MINUS_SRC_ALPHA,
+    );
+    gl.disable(glow::SCISSOR_TEST);
+    gl.active_texture(glow::TEXTURE0);
+}
+
+/// Makes sampler2DShadow lookups of a depth texture compare against the reference depth, lit
+/// where the reference is closer
+pub unsafe fn enable_depth_compare(gl: &glow::Context, tex: NativeTexture) {
+    gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+    gl.tex_parameter_i32(
+        glow::TEXTURE_2D,
+        glow::TEXTURE_COMPARE_MODE,
+        glow::COMPARE_REF_TO_TEXTURE as i32,
+    );
+    gl.tex_parameter_i32(
+        glow::TEXTURE_2D,
+        glow::TEXTURE_COMPARE_FUNC,
+        glow::LEQUAL as i32,
+    );
+    gl.bind_texture(glow::TEXTURE_2D, None);
+}
+
 // FIXME: copy pasta with depth_texture
 pub unsafe fn setup_color_texture_render(
     gl: &glow::Context,
     width: i32,
     height: i32,
 ) -> Result<(NativeTexture, NativeFramebuffer), GlError> {
-    let tex = create_tex_default_params(gl)?;
+    let tex = create_tex_default_params(gl, glow::CLAMP_TO_EDGE, glow::CLAMP_TO_EDGE)?;
     gl.bind_texture(glow::TEXTURE_2D, Some(tex));
     gl.tex_image_2d(
         glow::TEXTURE_2D,
@@ -71,11 +114,153 @@ pub unsafe fn setup_color_texture_render(
     Ok((tex, fb))
 }
 
-pub unsafe fn create_tex_default_params(gl: &glow::Context) -> Result<NativeTexture, GlError> {
+/// Color and depth textures attached to one framebuffer, for rendering a full 3d scene off
+/// screen. Returns (color, depth, framebuffer)
+pub unsafe fn setup_color_depth_target(
+    gl: &glow::Context,
+    width: i32,
+    height: i32,
+) -> Result<(NativeTexture, NativeTexture, NativeFramebuffer), GlError> {
+    let color = create_tex_default_params(gl, glow::CLAMP_TO_EDGE, glow::CLAMP_TO_EDGE)?;
+    gl.bind_texture(glow::TEXTURE_2D, Some(color));
+    gl.tex_image_2d(
+        glow::TEXTURE_2D,
+        0,
+        glow::RGBA as i32,
+        width,
+        height,
+        0,
+        glow::RGBA,
+        glow::UNSIGNED_BYTE,
+        None,
+    );
+
+    let depth = create_tex_default_params(gl, glow::CLAMP_TO_EDGE, glow::CLAMP_TO_EDGE)?;
+    gl.bind_texture(glow::TEXTURE_2D, Some(depth));
+    gl.tex_image_2d(
+        glow::TEXTURE_2D,
+        0,
+        glow::DEPTH_COMPONENT24 as i32,
+        width,
+        height,
+        0,
+        glow::DEPTH_COMPONENT,
+        glow::FLOAT,
+        None,
+    );
+    gl.bind_texture(glow::TEXTURE_2D, None);
+
+    let fb = gl.create_framebuffer().map_err(GlError)?;
+    gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fb));
+    gl.framebuffer_texture(glow::FRAMEBUFFER, glow::COLOR_ATTACHMENT0, Some(color), 0);
+    gl.framebuffer_texture(glow::FRAMEBUFFER, glow::DEPTH_ATTACHMENT, Some(depth), 0);
+
+    let buffers: [u32; 1] = [glow::COLOR_ATTACHMENT0];
+    gl.draw_buffers(&buffers);
+    let fb_status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+    if fb_status != glow::FRAMEBUFFER_COMPLETE {
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        gl.delete_framebuffer(fb);
+        gl.delete_texture(color);
+        gl.delete_texture(depth);
+        return Err(GlError(format!("incomplete framebuffer: {fb_status:#x}")));
+    }
+    gl.viewport(0, 0, width, height);
+
+    Ok((color, depth, fb))
+}
+
+/// Copies the color of src into dst (None for the window), mirrored along the requested axes.
+/// Leaves dst bound
+pub unsafe fn blit_flipped(
+    gl: &glow::Context,
+    src: NativeFramebuffer,
+    dst: Option<NativeFramebuffer>,
+    (width, height): (i32, i32),
+    horizontal: bool,
+    vertical: bool,
+) {
+    let (x0, x1) = if horizontal { (width, 0) } else { (0, width) };
+    let (y0, y1) = if vertical { (height, 0) } else { (0, height) };
+
+    gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(src));
+    gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, dst);
+    gl.blit_framebuffer(
+        0,
+        0,
+        width,
+        height,
+        x0,
+        y0,
+        x1,
+        y1,
+        glow::COLOR_BUFFER_BIT,
+        glow::NEAREST,
+    );
+    gl.bind_framebuffer(glow::FRAMEBUFFER, dst);
+}
+
+/// Stretches rows y0..y1 of a color texture width wide over dst, which is dst_width x dst_height.
+/// dst is left bound afterwards
+pub unsafe fn blit_texture_rows(
+    gl: &glow::Context,
+    tex: NativeTexture,
+    width: i32,
+    (y0, y1): (i32, i32),
+    dst: Option<NativeFramebuffer>,
+    (dst_width, dst_height): (i32, i32),
+) -> Result<(), GlError> {
+    let src = gl.create_framebuffer().map_err(GlError)?;
+    gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(src));
+    gl.framebuffer_texture_2d(
+        glow::READ_FRAMEBUFFER,
+        glow::COLOR_ATTACHMENT0,
+        glow::TEXTURE_2D,
+        Some(tex),
+        0,
+    );
+    gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, dst);
+    gl.blit_framebuffer(
+        0,
+        y0,
+        width,
+        y1,
+        0,
+        0,
+        dst_width,
+        dst_height,
+        glow::COLOR_BUFFER_BIT,
+        glow::LINEAR,
+    );
+    gl.bind_framebuffer(glow::FRAMEBUFFER, dst);
+    gl.delete_framebuffer(src);
+    Ok(())
+}
+
+/// Runs upload with source rows read as tightly packed, then puts back GL's default of 4 byte
+/// aligned rows. None of our pixel data pads its rows, so the default would skew every row whose
+/// size in bytes isn't a multiple of 4
+pub unsafe fn with_packed_rows<T>(gl: &glow::Context, upload: impl FnOnce() -> T) -> T {
+    gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+    let ret = upload();
+    gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+    ret
+}
+
+/// Linear filtering with the given wrap modes. Anything drawn as a single quad wants
+/// CLAMP_TO_EDGE so the filter doesn't pull in texels from the opposite edge
+pub unsafe fn create_tex_default_params(
+    gl: &glow::Context,
+    wrap_s: u32,
+    wrap_t: u32,
+) -> Result<NativeTexture, GlError> {
     let texture = gl.create_texture().map_err(GlError)?;
 
     gl.bind_texture(glow::TEXTURE_2D, Some(texture));
 
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, wrap_s as i32);
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, wrap_t as i32);
+
     gl.tex_parameter_i32(
         glow::TEXTURE_2D,
         glow::TEXTURE_MIN_FILTER,
@@ -92,43 +277,75 @@ pub unsafe fn create_tex_default_params(gl: &glow::Context) -> Result<NativeText
     Ok(texture)
 }
 
+/// The error holds the driver's info log
 pub unsafe fn compile_shader(
     gl: &glow::Context,
     shader_type: u32,
     shader_source: &str,
-) -> NativeShader {
-    let shader = gl.create_shader(shader_type).expect("Cannot create shader");
+) -> Result<NativeShader, GlError> {
+    let shader = gl.create_shader(shader_type).map_err(GlError)?;
     gl.shader_source(shader, shader_source);
     gl.compile_shader(shader);
     if !gl.get_shader_compile_status(shader) {
-        panic!("{}", gl.get_shader_info_log(shader));
+        let log = gl.get_shader_info_log(shader);
+        gl.delete_shader(shader);
+        return Err(GlError(log));
     }
-    shader
+    Ok(shader)
 }
 
+/// Nothing is leaked on failure, so a bad shader can be reported and the old program kept
 pub unsafe fn compile_program(
     gl: &glow::Context,
     vert_source: &str,
     frag_source: &str,
-) -> NativeProgram {
-    let program = gl.create_program().expect("Cannot create program");
+) -> Result<NativeProgram, GlError> {
+    let vertex_shader = compile_shader(gl, glow::VERTEX_SHADER, vert_source)?;
+    let fragment_shader = match compile_shader(gl, glow::FRAGMENT_SHADER, frag_source) {
+        Ok(v) => v,
+        Err(e) => {
+            gl.delete_shader(vertex_shader);
+            return Err(e);
+        }
+    };
 
-    let vertex_shader = compile_shader(gl, glow::VERTEX_SHADER, vert_source);
-    gl.attach_shader(program, vertex_shader);
+    let program = gl.create_program().map_err(GlError);
+    let linked = program.and_then(|program| {
+        gl.attach_shader(program, vertex_shader);
+        gl.attach_shader(program, fragment_shader);
+        gl.link_program(program);
 
-    let fragment_shader = compile_shader(gl, glow::FRAGMENT_SHADER, frag_source);
-    gl.attach_shader(program, fragment_shader);
+        for shader in [vertex_shader, fragment_shader] {
+            gl.detach_shader(program, shader);
+        }
 
-    gl.link_program(program);
-
-    if !gl.get_program_link_status(program) {
-        panic!("{}", gl.get_program_info_log(program));
-    }
+        if !gl.get_program_link_status(program) {
+            let log = gl.get_program_info_log(program);
+            gl.delete_program(program);
+            return Err(GlError(log));
+        }
+        Ok(program)
+    });
 
     for shader in [vertex_shader, fragment_shader] {
-        gl.detach_shader(program, shader);
         gl.delete_shader(shader);
     }
 
-    program
+    linked
+}
+
+/// RGBA contents of the currently bound framebuffer, rows bottom to top
+pub unsafe fn read_framebuffer_rgba(gl: &glow::Context, width: u32, height: u32) -> Vec<u8> {
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+    gl.read_pixels(
+        0,
+        0,
+        width as i32,
+        height as i32,
+        glow::RGBA,
+        glow::UNSIGNED_BYTE,
+        glow::PixelPackData::Slice(&mut pixels),
+    );
+    pixels
 }
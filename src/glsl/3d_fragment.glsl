@@ -3,14 +3,47 @@ precision mediump float;
 
 in vec2 uv;
 in vec3 normal;
+in vec3 vertex_color;
 in vec4 pos;
 
 uniform sampler2D tex;
+#if defined(NO_SHADOWS)
+// No light_tex at all, so nothing samples a texture that was never rendered
+#elif defined(HARDWARE_PCF)
+uniform sampler2DShadow light_tex;
+#else
 uniform sampler2D light_tex;
+#endif
 uniform mat4 view_pos_to_light_pos;
 
 uniform vec3 light_dir = normalize(vec3(0.1, 0.9, 0.1));
 uniform vec3 light_color = vec3(0.0, 0.0, 0.0);
+// Light that reaches everything, shadowed or not
+uniform vec3 ambient = vec3(0.0, 0.0, 0.0);
+uniform vec4 tint = vec4(1.0);
+// Meshes uploaded without a texture use untextured_color instead
+uniform bool untextured = false;
+uniform vec3 untextured_color = vec3(1.0);
+// Share of the surface color given off on its own, added after lighting so shadows can't darken
+// it
+uniform vec3 emissive = vec3(0.0);
+// Texels with less alpha than this are cut out, 0 keeps everything
+uniform float alpha_cutoff = 0.0;
+// Soft shadows average this many taps from POISSON_DISK, spread over light_size texels
+uniform int shadow_samples = 1;
+uniform float light_size = 0.0;
+
+const int MAX_SHADOW_SAMPLES = 16;
+const vec2 POISSON_DISK[MAX_SHADOW_SAMPLES] = vec2[](
+    vec2(-0.94201624, -0.39906216), vec2(0.94558609, -0.76890725),
+    vec2(-0.09418410, -0.92938870), vec2(0.34495938, 0.29387760),
+    vec2(-0.91588581, 0.45771432), vec2(-0.81544232, -0.87912464),
+    vec2(-0.38277543, 0.27676845), vec2(0.97484398, 0.75648379),
+    vec2(0.44323325, -0.97511554), vec2(0.53742981, -0.47373420),
+    vec2(-0.26496911, -0.41893023), vec2(0.79197514, 0.19090188),
+    vec2(-0.24188840, 0.99706507), vec2(-0.81409955, 0.91437590),
+    vec2(0.19984126, 0.78641367), vec2(0.14383161, -0.14100790)
+);
 
 out vec4 out_color;
 
@@ -29,14 +62,41 @@ void main() {
     // Invert Y as texture is top to bottom, but UV coords are bottom to top
     adjusted_uv.y = 1.0 - adjusted_uv.y;
 
+#ifdef NO_SHADOWS
+    float lit_mul = 1.0;
+#else
     vec4 light_pos = pos;
     light_pos = view_pos_to_light_pos * light_pos;
 
-    vec4 light_tex_depth = texture(light_tex, clamp(ndc_to_uv(light_pos.xy), 0.0, 1.0));
-    float lit_mul = (ndc_to_uv(light_pos.z - 0.01) < light_tex_depth.r) ? 1.0 : 0.0;
+    vec2 light_uv = ndc_to_uv(light_pos.xy);
+    float light_ref_depth = ndc_to_uv(light_pos.z - 0.01);
+    vec2 texel = 1.0 / vec2(textureSize(light_tex, 0));
+    int samples = clamp(shadow_samples, 1, MAX_SHADOW_SAMPLES);
+
+    float lit_mul = 0.0;
+    for (int i = 0; i < samples; ++i) {
+        vec2 offset = samples == 1 ? vec2(0.0) : POISSON_DISK[i] * light_size * texel;
+        vec2 sample_uv = clamp(light_uv + offset, 0.0, 1.0);
+#ifdef HARDWARE_PCF
+        // The comparison happens per texel before filtering, giving a 2x2 PCF blend
+        lit_mul += texture(light_tex, vec3(sample_uv, light_ref_depth));
+#else
+        vec4 light_tex_depth = texture(light_tex, sample_uv);
+        lit_mul += (light_ref_depth < light_tex_depth.r) ? 1.0 : 0.0;
+#endif
+    }
+    lit_mul /= float(samples);
+#endif
 
-    out_color = texture(tex, adjusted_uv);
-    vec3 ambient = out_color.xyz * 0.2 * light_color;
+    out_color = untextured ? vec4(untextured_color, 1.0) : texture(tex, adjusted_uv);
+    if (out_color.a < alpha_cutoff) {
+        discard;
+    }
+    // Vertex colors tint the texture, white (the default) leaves it untouched
+    out_color.xyz = mix(vec3(0.0), out_color.xyz, vertex_color);
+    vec3 ambient_lit = out_color.xyz * ambient;
     vec3 diffuse = max(out_color.xyz * -dot(normal, light_dir) * lit_mul * light_color, 0.0);
-    out_color.xyz = min(diffuse + ambient , vec3(1.0));
+    vec3 emitted = out_color.xyz * emissive;
+    out_color.xyz = min(diffuse + ambient_lit + emitted, vec3(1.0));
+    out_color *= tint;
 }
@@ -5,6 +5,9 @@
 in vec4 in_vert;
 in vec2 in_uv;
 in vec3 in_normal;
+in vec3 in_color;
+// Per instance model matrix, only used when instanced is set
+in mat4 in_model;
 
 uniform mat4 model = mat4(
     cos(M_PI / 4), -sin(M_PI / 4), 0, 0,
@@ -19,18 +22,23 @@ uniform mat4 view = mat4(
     0, 0, 0, 1
 );
 
+uniform bool instanced = false;
+
 out vec2 uv;
 out vec3 normal;
+out vec3 vertex_color;
 out vec4 pos;
 
 void main() {
     vec4 out_vert = in_vert;
 
-    out_vert = view * model * out_vert;
+    mat4 model_matrix = instanced ? in_model : model;
+    out_vert = view * model_matrix * out_vert;
 
     gl_Position = out_vert;
     pos = out_vert;
 
     uv = in_uv;
     normal = in_normal;
+    vertex_color = in_color;
 }
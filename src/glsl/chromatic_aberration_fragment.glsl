@@ -0,0 +1,26 @@
+#version 410
+precision mediump float;
+
+in vec2 vert;
+
+out vec4 out_color;
+
+uniform sampler2D in_tex;
+uniform float aspect_ratio;
+uniform float intensity = 0.01;
+
+void main() {
+    // Measure distance in screen space so the effect is the same strength on both axis
+    vec2 from_center = vert - 0.5;
+    from_center.x *= aspect_ratio;
+
+    // Offset grows with distance from the center so the middle of the screen stays sharp
+    vec2 offset = from_center * length(from_center) * intensity * 4.0;
+    offset.x /= aspect_ratio;
+
+    out_color = vec4(
+        texture(in_tex, vert + offset).r,
+        texture(in_tex, vert).g,
+        texture(in_tex, vert - offset).b,
+        texture(in_tex, vert).a);
+}
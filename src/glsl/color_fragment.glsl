@@ -1,10 +1,20 @@
 #version 410
 precision mediump float;
 
+// 0 to 1 across the quad, 0.5 everywhere for anything that isn't a quad
+in vec2 quad_uv;
+
 out vec4 out_color;
 
-uniform vec3 color;
+uniform vec4 color = vec4(1.0);
+// Fades the outermost pixel of the quad instead of cutting it off hard
+uniform bool smooth_edges = false;
 
 void main() {
-    out_color = vec4(1.0);
+    out_color = color;
+    if (smooth_edges) {
+        // Distance to the nearest edge in pixels
+        vec2 edge = min(quad_uv, 1.0 - quad_uv) / fwidth(quad_uv);
+        out_color.a *= clamp(min(edge.x, edge.y), 0.0, 1.0);
+    }
 }
@@ -2,9 +2,14 @@
 
 in vec2 in_vert;
 
+out vec2 quad_uv;
+
 uniform float aspect_ratio;
 
 void main() {
+    // Corners come in triangle strip order, bottom left, bottom right, top left, top right
+    quad_uv = vec2(gl_VertexID & 1, gl_VertexID >> 1);
+
     vec2 v = in_vert * 2.0 - 1.0;
     v.y *= aspect_ratio;
     gl_Position = vec4(v, 0.0, 1.0);
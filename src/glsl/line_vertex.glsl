@@ -0,0 +1,13 @@
+#version 410
+
+in vec3 in_pos;
+
+// Lines have no edges for color_fragment.glsl to smooth
+out vec2 quad_uv;
+
+uniform mat4 view;
+
+void main() {
+    quad_uv = vec2(0.5);
+    gl_Position = view * vec4(in_pos, 1.0);
+}
@@ -0,0 +1,30 @@
+#version 410
+precision mediump float;
+
+in vec2 vert;
+
+out vec4 out_color;
+
+uniform sampler2D in_tex;
+// Strip of blue slices, see lut.rs
+uniform sampler2D lut_tex;
+uniform float lut_size;
+
+vec2 slice_uv(vec2 rg, float b) {
+    // Aim at texel centers so linear filtering blends red and green without leaking between slices
+    return vec2((b * lut_size + rg.x + 0.5) / (lut_size * lut_size), (rg.y + 0.5) / lut_size);
+}
+
+void main() {
+    vec4 color = texture(in_tex, vert);
+    vec3 scaled = clamp(color.rgb, 0.0, 1.0) * (lut_size - 1.0);
+
+    float b0 = floor(scaled.b);
+    float b1 = min(b0 + 1.0, lut_size - 1.0);
+    vec3 graded = mix(
+        texture(lut_tex, slice_uv(scaled.rg, b0)).rgb,
+        texture(lut_tex, slice_uv(scaled.rg, b1)).rgb,
+        scaled.b - b0);
+
+    out_color = vec4(graded, color.a);
+}
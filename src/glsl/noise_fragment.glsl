@@ -0,0 +1,26 @@
+#version 410
+precision mediump float;
+
+in vec2 vert;
+
+out vec4 out_color;
+
+uniform sampler2D in_tex;
+uniform float time;
+uniform float intensity = 0.1;
+uniform float seed = 0.0;
+
+float hash(vec3 p) {
+    p = fract(p * vec3(443.897, 441.423, 437.195));
+    p += dot(p, p.yzx + 19.19);
+    return fract((p.x + p.y) * p.z);
+}
+
+void main() {
+    // Quantize time so the grain changes at a steady rate instead of every rendered frame
+    float frame = floor(time * 24.0) + seed;
+    float noise = hash(vec3(vert * 1024.0, frame)) - 0.5;
+
+    vec4 color = texture(in_tex, vert);
+    out_color = vec4(clamp(color.rgb + noise * intensity, 0.0, 1.0), color.a);
+}
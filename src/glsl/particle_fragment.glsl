@@ -0,0 +1,15 @@
+#version 410
+precision mediump float;
+
+out vec4 out_color;
+
+uniform vec3 color;
+
+void main() {
+    // Round points, fading out towards the edge
+    float dist = length(gl_PointCoord - vec2(0.5)) * 2.0;
+    if (dist > 1.0) {
+        discard;
+    }
+    out_color = vec4(color, 1.0 - dist);
+}
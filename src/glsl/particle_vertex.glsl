@@ -0,0 +1,8 @@
+#version 410
+
+in vec3 in_particle;
+
+void main() {
+    gl_Position = vec4(in_particle.xy, 0.0, 1.0);
+    gl_PointSize = in_particle.z;
+}
@@ -0,0 +1,9 @@
+#version 410
+
+// Full screen quad generated from the vertex id, drawn as a 4 vertex triangle strip
+out vec2 vert;
+
+void main() {
+    vert = vec2(gl_VertexID & 1, gl_VertexID >> 1);
+    gl_Position = vec4(vert * 2.0 - 1.0, 0.0, 1.0);
+}
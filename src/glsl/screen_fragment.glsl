@@ -7,6 +7,13 @@ out vec4 out_color;
 
 uniform sampler2D in_tex;
 uniform float time;
+uniform float aspect_ratio;
+// Peak brightness of the scanline background, [0, 1]
+uniform float scanline_intensity = 0.2;
+// Scanlines per unit of texture height, > 0
+uniform float scanline_frequency = 200.0;
+// Barrel distortion, 0 is flat, [0, 1]
+uniform float curvature = 0.0;
 
 void main() {
     vec2 adjusted_coord = vert.yx;
@@ -14,9 +21,16 @@ void main() {
     adjusted_coord *= 2;
     adjusted_coord -= 0.5;
 
+    // Push coordinates outwards the further they are from the middle of the content. Distance is
+    // measured on screen, where y of the flipped coordinates is the wide axis, so the bulge is
+    // round instead of stretched with the screen. Normalized so the wide axis bulges as before
+    vec2 from_center = adjusted_coord - 0.5;
+    vec2 on_screen = from_center * vec2(1.0, aspect_ratio) / max(aspect_ratio, 1.0);
+    adjusted_coord = 0.5 + from_center * (1.0 + curvature * dot(on_screen, on_screen));
+
     vec4 background = vec4(
         0.0,
-        (sin(adjusted_coord.y * 200.0 + time) + 1.0) / 10.0,
+        (sin(adjusted_coord.y * scanline_frequency + time) + 1.0) / 2.0 * scanline_intensity,
         0.0,
         1.0);
 
@@ -4,20 +4,30 @@ in vec2 vert;
 out vec4 color;
 
 uniform sampler2D ourTexture;
-
-float clamp(float x) {
-  return x > 1.0 ? 1.0 : x < 0.0 ? 0.0 : x;
-}
+uniform vec3 text_color;
+// Lower values take in more of the distance field, synthesizing bold
+uniform float edge = 0.5;
+// How far past edge, in distance field units, alpha takes to reach 1
+uniform float softness = 0.23;
+// Set for glyphs freetype couldn't make a distance field for, the texture holds plain coverage
+uniform bool coverage = false;
 
 void main() {
     float val = texture(ourTexture, vert).r;
 
-    if (val < 0.5) {
-        discard;
+    float alpha;
+    if (coverage) {
+        alpha = val;
+    } else {
+        if (val < edge) {
+            discard;
+        }
+        alpha = smoothstep(edge, edge + softness, val);
     }
 
-    val = (val - 0.5) * 50.0;
-    float alpha = clamp((val - 0.5) * 0.09);
+    if (alpha <= 0.0) {
+        discard;
+    }
 
-    color = vec4(1.0, 1.0, 1.0, alpha);
+    color = vec4(text_color, alpha);
 }
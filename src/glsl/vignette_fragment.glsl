@@ -0,0 +1,25 @@
+#version 410
+precision mediump float;
+
+in vec2 vert;
+
+out vec4 out_color;
+
+uniform sampler2D in_tex;
+uniform float aspect_ratio;
+// Distance from the center where darkening starts, in units of half the screen height
+uniform float radius = 0.75;
+// How far past radius it takes to fade to black
+uniform float softness = 0.45;
+
+void main() {
+    // Scale x so the falloff is a circle on screen instead of following the texture's shape
+    vec2 from_center = vert - 0.5;
+    from_center.x *= aspect_ratio;
+
+    float dist = length(from_center) * 2.0;
+    float vignette = 1.0 - smoothstep(radius, radius + softness, dist);
+
+    vec4 color = texture(in_tex, vert);
+    out_color = vec4(color.rgb * vignette, color.a);
+}
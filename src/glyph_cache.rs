@@ -4,26 +4,78 @@ use glow::{HasContext, NativeTexture};
 
 use thiserror::Error;
 
-use std::collections::hash_map::{Entry, HashMap};
+use std::{
+    borrow::Cow,
+    collections::hash_map::{Entry, HashMap},
+    path::Path,
+};
 
 use super::GlError;
 
 #[allow(unused)]
 pub struct CachedCharacter {
+    /// The atlas page holding the glyph, shared with other glyphs
     pub texture: NativeTexture,
+    /// Top left and bottom right of the glyph in texture coordinates
+    pub uv: [[f32; 2]; 2],
     pub advance_x: i32,
     pub left: i32,
     pub top: i32,
     pub width: i32,
     pub height: i32,
+    /// False when the texture holds plain coverage because freetype couldn't make a distance field
+    pub sdf: bool,
+}
+
+impl CachedCharacter {
+    pub fn metrics(&self) -> GlyphMetrics {
+        GlyphMetrics {
+            advance_x: self.advance_x,
+            left: self.left,
+            top: self.top,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+/// Layout information for a glyph. advance_x is in 26.6 fixed point like freetype gives it, the
+/// rest are in pixels. advance_x comes from the unhinted outline so it keeps its fractional
+/// part instead of snapping to whole pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphMetrics {
+    pub advance_x: i32,
+    pub left: i32,
+    pub top: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+struct LoadedGlyph {
+    metrics: GlyphMetrics,
+    sdf: bool,
+}
+
+/// Lookup counts since the cache was created, clear() doesn't reset them
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GlyphCacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+    pub pages: usize,
 }
 
 #[derive(Error, Debug)]
 enum GlyphCacheCreationErrorRepr {
     #[error("failed to create font library")]
     CreateLibrary(freetype::Error),
+    #[error("failed to read font file")]
+    ReadFont(std::io::Error),
     #[error("failed to create font face")]
     CreateFace(freetype::Error),
+    #[error("font has {num_faces} faces, index {index} is out of range")]
+    FaceIndexOutOfRange { index: usize, num_faces: usize },
     #[error("failed to set font size")]
     SetSize(freetype::Error),
 }
@@ -32,18 +84,90 @@ enum GlyphCacheCreationErrorRepr {
 #[error(transparent)]
 pub struct GlyphCacheCreationError(#[from] GlyphCacheCreationErrorRepr);
 
+/// Width and height of each atlas texture in pixels. Glyphs that don't fit in the current page
+/// start a new one
+pub const ATLAS_PAGE_SIZE: i32 = 1024;
+// Empty texels between glyphs so linear filtering doesn't pick up a neighbour
+const ATLAS_PADDING: i32 = 1;
+
+struct Shelf {
+    y: i32,
+    height: i32,
+    x: i32,
+}
+
+/// Packs rectangles into a square page in rows, each as tall as the first rectangle placed in it
+struct ShelfAllocator {
+    size: i32,
+    shelves: Vec<Shelf>,
+}
+
+impl ShelfAllocator {
+    fn new(size: i32) -> ShelfAllocator {
+        ShelfAllocator {
+            size,
+            shelves: Vec::new(),
+        }
+    }
+
+    /// Top left corner of the reserved space, None if the page is full
+    fn allocate(&mut self, width: i32, height: i32) -> Option<(i32, i32)> {
+        let width = width + ATLAS_PADDING;
+        let height = height + ATLAS_PADDING;
+
+        for shelf in &mut self.shelves {
+            if height <= shelf.height && shelf.x + width <= self.size {
+                let pos = (shelf.x, shelf.y);
+                shelf.x += width;
+                return Some(pos);
+            }
+        }
+
+        let y = self.shelves.last().map(|s| s.y + s.height).unwrap_or(0);
+        if width > self.size || y + height > self.size {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y,
+            height,
+            x: width,
+        });
+        Some((0, y))
+    }
+}
+
+struct AtlasPage {
+    texture: NativeTexture,
+    allocator: ShelfAllocator,
+}
+
+/// Everything that can show up in the default layout
+pub const PRINTABLE_ASCII: &str = " !\"#$%&'()*+,-./0123456789:;<=>?@\
+    ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
 pub struct GlyphCache {
     character_map: HashMap<char, CachedCharacter>,
+    // Glyphs only ever go into the last page
+    pages: Vec<AtlasPage>,
+    page_size: i32,
+    // Glyphs that have been measured but never drawn
+    metrics_map: HashMap<char, GlyphMetrics>,
+    hits: usize,
+    misses: usize,
     pixel_size: u32,
-    face: Face<&'static [u8]>,
+    face: Face<Cow<'static, [u8]>>,
 }
 
 #[derive(Error, Debug)]
 enum GetCharacterErrorRepr {
     #[error("failed to load character")]
     LoadChar(freetype::Error),
+    #[error("failed to render glyph")]
+    RenderGlyph(freetype::Error),
     #[error("failed to create texture")]
     CreateTexture(GlError),
+    #[error("glyph is {0}x{1}, larger than an atlas page")]
+    GlyphTooLarge(i32, i32),
 }
 
 #[derive(Error, Debug)]
@@ -51,13 +175,39 @@ enum GetCharacterErrorRepr {
 pub struct GetCharacterError(#[from] GetCharacterErrorRepr);
 
 impl GlyphCache {
-    pub fn new(pixel_size: u32) -> Result<GlyphCache, GlyphCacheCreationError> {
+    /// Uses the bundled Hack font if no font file is given. face_index picks a face out of
+    /// collections (.ttc), plain font files only have face 0
+    pub fn new(
+        pixel_size: u32,
+        font: Option<&Path>,
+        face_index: usize,
+    ) -> Result<GlyphCache, GlyphCacheCreationError> {
         let lib = Library::init().map_err(GlyphCacheCreationErrorRepr::CreateLibrary)?;
 
         const HACK_TTF: &[u8] = include_bytes!("../res/Hack-Regular.ttf");
 
+        let font_data: Cow<'static, [u8]> = match font {
+            Some(path) => std::fs::read(path)
+                .map_err(GlyphCacheCreationErrorRepr::ReadFont)?
+                .into(),
+            None => HACK_TTF.into(),
+        };
+
+        // Freetype gives a generic error for bad indices, check against the count ourselves
+        let num_faces = lib
+            .new_memory_face2(font_data.clone(), 0)
+            .map_err(GlyphCacheCreationErrorRepr::CreateFace)?
+            .num_faces() as usize;
+        if face_index >= num_faces {
+            return Err(GlyphCacheCreationErrorRepr::FaceIndexOutOfRange {
+                index: face_index,
+                num_faces,
+            }
+            .into());
+        }
+
         let face = lib
-            .new_memory_face2(HACK_TTF, 0)
+            .new_memory_face2(font_data, face_index as isize)
             .map_err(GlyphCacheCreationErrorRepr::CreateFace)?;
 
         face.set_pixel_sizes(pixel_size, pixel_size)
@@ -65,6 +215,11 @@ impl GlyphCache {
 
         Ok(GlyphCache {
             character_map: HashMap::new(),
+            pages: Vec::new(),
+            page_size: ATLAS_PAGE_SIZE,
+            metrics_map: HashMap::new(),
+            hits: 0,
+            misses: 0,
             pixel_size,
             face,
         })
@@ -74,55 +229,337 @@ impl GlyphCache {
         self.pixel_size
     }
 
+    /// Size of pages created from now on, existing pages keep theirs
+    #[allow(unused)]
+    pub fn set_atlas_page_size(&mut self, size: i32) {
+        self.page_size = size;
+    }
+
     pub fn get_character(
         &mut self,
         gl: &glow::Context,
         c: char,
     ) -> Result<&CachedCharacter, GetCharacterError> {
-        let entry = self.character_map.entry(c);
-        let entry = match entry {
-            Entry::Occupied(v) => {
-                return Ok(v.into_mut());
+        if self.count_lookup(c) {
+            return Ok(&self.character_map[&c]);
+        }
+
+        let LoadedGlyph { metrics, sdf } = load_glyph(&self.face, c)?;
+        let glyph_bitmap = self.face.glyph().bitmap();
+        let (width, height) = (glyph_bitmap.pitch(), glyph_bitmap.rows());
+
+        let allocation = self
+            .pages
+            .last_mut()
+            .and_then(|page| Some((page.texture, page.allocator.allocate(width, height)?)));
+        let (texture, (x, y)) = match allocation {
+            Some(v) => v,
+            None => {
+                // Checked before making the texture so a glyph that can't fit doesn't leak it
+                let (allocator, pos) = self.allocate_on_new_page(width, height)?;
+                let page = unsafe { create_page(gl, allocator)? };
+                let texture = page.texture;
+                self.pages.push(page);
+                (texture, pos)
             }
-            Entry::Vacant(v) => v,
         };
 
-        self.face
-            .load_char(c as usize, LoadFlag::RENDER)
-            .map_err(GetCharacterErrorRepr::LoadChar)?;
-        let glyph = self.face.glyph();
-        if let Err(e) = glyph.render_glyph(freetype::RenderMode::Sdf) {
-            println!("Failed to render glyph with sdf for {}: {e}", c);
-        }
-        let glyph_bitmap = glyph.bitmap();
-
-        let texture = unsafe {
-            let texture = crate::gl_util::create_tex_default_params(gl)
-                .map_err(GetCharacterErrorRepr::CreateTexture)?;
-            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
-            gl.tex_image_2d(
-                glow::TEXTURE_2D,
-                0,
-                glow::RGB as i32,
-                glyph_bitmap.pitch(),
-                glyph_bitmap.rows(),
-                0,
-                glow::RED,
-                glow::UNSIGNED_BYTE,
-                Some(glyph_bitmap.buffer()),
-            );
-            gl.bind_texture(glow::TEXTURE_2D, None);
-            texture
-        };
+        // Distance field and coverage bitmaps are one byte per pixel, with rows pitch bytes apart
+        // and nothing between them. Uploading pitch texels per row with packed rows copies the
+        // buffer as is, any padding past the glyph's width lands outside its uv
+        if width > 0 && height > 0 {
+            unsafe {
+                gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+                crate::gl_util::with_packed_rows(gl, || {
+                    gl.tex_sub_image_2d(
+                        glow::TEXTURE_2D,
+                        0,
+                        x,
+                        y,
+                        width,
+                        height,
+                        glow::RED,
+                        glow::UNSIGNED_BYTE,
+                        glow::PixelUnpackData::Slice(glyph_bitmap.buffer()),
+                    )
+                });
+                gl.bind_texture(glow::TEXTURE_2D, None);
+            }
+        }
+
+        // Columns past width are pitch padding, leave them out of the quad
+        let page_size = self
+            .pages
+            .last()
+            .expect("page was just used")
+            .allocator
+            .size as f32;
+        let uv = [
+            [x as f32 / page_size, y as f32 / page_size],
+            [
+                (x + metrics.width) as f32 / page_size,
+                (y + height) as f32 / page_size,
+            ],
+        ];
 
-        let inserted = entry.insert(CachedCharacter {
+        let inserted = self.character_map.entry(c).or_insert(CachedCharacter {
             texture,
-            advance_x: glyph.advance().x as i32,
+            uv,
+            advance_x: metrics.advance_x,
+            left: metrics.left,
+            top: metrics.top,
+            width: metrics.width,
+            height: metrics.height,
+            sdf,
+        });
+        Ok(inserted)
+    }
+
+    /// Places a width x height glyph on an empty allocator for a page that doesn't exist yet
+    fn allocate_on_new_page(
+        &self,
+        width: i32,
+        height: i32,
+    ) -> Result<(ShelfAllocator, (i32, i32)), GetCharacterErrorRepr> {
+        let mut allocator = ShelfAllocator::new(self.page_size);
+        let pos = allocator
+            .allocate(width, height)
+            .ok_or(GetCharacterErrorRepr::GlyphTooLarge(width, height))?;
+        Ok((allocator, pos))
+    }
+
+    /// Counts c as a hit if it's already uploaded, otherwise a miss
+    fn count_lookup(&mut self, c: char) -> bool {
+        let hit = self.character_map.contains_key(&c);
+        if hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        hit
+    }
+
+    /// Renders and uploads chars up front so they don't cause a hitch the first time they're drawn
+    pub fn preload(&mut self, gl: &glow::Context, chars: &str) -> Result<(), GetCharacterError> {
+        for c in chars.chars() {
+            self.get_character(gl, c)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes every atlas page. References from get_character must not outlive this
+    #[allow(unused)]
+    pub fn clear(&mut self, gl: &glow::Context) {
+        for page in self.pages.drain(..) {
+            unsafe {
+                gl.delete_texture(page.texture);
+            }
+        }
+        self.character_map.clear();
+        self.metrics_map.clear();
+    }
+
+    #[allow(unused)]
+    pub fn stats(&self) -> GlyphCacheStats {
+        GlyphCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.character_map.len(),
+            pages: self.pages.len(),
+        }
+    }
+
+    /// Same numbers get_character would give, without needing a GL context
+    pub fn metrics(&mut self, c: char) -> Result<GlyphMetrics, GetCharacterError> {
+        if let Some(cached) = self.character_map.get(&c) {
+            return Ok(cached.metrics());
+        }
+
+        match self.metrics_map.entry(c) {
+            Entry::Occupied(v) => Ok(*v.get()),
+            Entry::Vacant(v) => Ok(*v.insert(load_glyph(&self.face, c)?.metrics)),
+        }
+    }
+}
+
+/// Starts out empty so the padding between glyphs reads as far outside any outline
+unsafe fn create_page(
+    gl: &glow::Context,
+    allocator: ShelfAllocator,
+) -> Result<AtlasPage, GetCharacterErrorRepr> {
+    let size = allocator.size;
+    let texture =
+        crate::gl_util::create_tex_default_params(gl, glow::CLAMP_TO_EDGE, glow::CLAMP_TO_EDGE)
+            .map_err(GetCharacterErrorRepr::CreateTexture)?;
+    gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+    crate::gl_util::with_packed_rows(gl, || {
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGB as i32,
+            size,
+            size,
+            0,
+            glow::RED,
+            glow::UNSIGNED_BYTE,
+            Some(&vec![0; (size * size) as usize]),
+        )
+    });
+    gl.bind_texture(glow::TEXTURE_2D, None);
+
+    Ok(AtlasPage { texture, allocator })
+}
+
+/// Leaves the rendered bitmap in face.glyph(). That's a distance field unless freetype couldn't
+/// make one, then it's plain coverage and sdf is false
+fn load_glyph(
+    face: &Face<Cow<'static, [u8]>>,
+    c: char,
+) -> Result<LoadedGlyph, GetCharacterErrorRepr> {
+    face.load_char(c as usize, LoadFlag::RENDER)
+        .map_err(GetCharacterErrorRepr::LoadChar)?;
+    let glyph = face.glyph();
+    let sdf = match glyph.render_glyph(freetype::RenderMode::Sdf) {
+        Ok(()) => true,
+        Err(e) => {
+            glyph
+                .render_glyph(freetype::RenderMode::Normal)
+                .map_err(GetCharacterErrorRepr::RenderGlyph)?;
+            // Empty outlines like space always fail, there's nothing to draw either way
+            let bitmap = glyph.bitmap();
+            if bitmap.width() > 0 && bitmap.rows() > 0 {
+                println!("Failed to render glyph with sdf for {c:?}, using plain coverage: {e}");
+            }
+            false
+        }
+    };
+    let glyph_bitmap = glyph.bitmap();
+
+    Ok(LoadedGlyph {
+        metrics: GlyphMetrics {
+            // 16.16 -> 26.6
+            advance_x: (glyph.linear_hori_advance() >> 10) as i32,
             left: glyph.bitmap_left(),
             top: glyph.bitmap_top(),
             width: glyph_bitmap.width(),
             height: glyph_bitmap.rows(),
-        });
-        Ok(inserted)
+        },
+        sdf,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_face_index_validated() {
+        assert!(GlyphCache::new(32, None, 0).is_ok());
+
+        match GlyphCache::new(32, None, 1) {
+            Err(GlyphCacheCreationError(GlyphCacheCreationErrorRepr::FaceIndexOutOfRange {
+                index: 1,
+                num_faces: 1,
+            })) => (),
+            Err(e) => panic!("unexpected error {e}"),
+            Ok(_) => panic!("bundled font only has one face"),
+        }
+    }
+
+    #[test]
+    fn test_printable_ascii() {
+        let expected: String = (' '..='~').collect();
+        assert_eq!(PRINTABLE_ASCII, expected);
+    }
+
+    #[test]
+    fn test_shelf_allocator_fills_page() {
+        let mut allocator = ShelfAllocator::new(16);
+        // 7x7 with padding, two fit per row and two rows per page
+        assert_eq!(allocator.allocate(7, 7), Some((0, 0)));
+        assert_eq!(allocator.allocate(7, 3), Some((8, 0)));
+        assert_eq!(allocator.allocate(7, 7), Some((0, 8)));
+        assert_eq!(allocator.allocate(7, 7), Some((8, 8)));
+        assert_eq!(allocator.allocate(1, 1), None);
+
+        assert_eq!(ShelfAllocator::new(16).allocate(16, 1), None);
+    }
+
+    #[test]
+    fn test_glyph_too_large_for_page() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        cache.set_atlas_page_size(16);
+
+        let (_, pos) = cache.allocate_on_new_page(8, 8).unwrap();
+        assert_eq!(pos, (0, 0));
+        assert!(matches!(
+            cache.allocate_on_new_page(16, 8),
+            Err(GetCharacterErrorRepr::GlyphTooLarge(16, 8))
+        ));
+        // Nothing was uploaded for either attempt
+        assert_eq!(cache.stats().pages, 0);
+    }
+
+    #[test]
+    fn test_glyphs_load_as_sdf() {
+        let cache = GlyphCache::new(32, None, 0).unwrap();
+        for c in PRINTABLE_ASCII.chars().filter(|c| *c != ' ') {
+            assert!(load_glyph(&cache.face, c).unwrap().sdf, "{c:?}");
+        }
+
+        // Freetype won't make a distance field from an empty outline, space still loads through
+        // the fallback
+        let space = load_glyph(&cache.face, ' ').unwrap();
+        assert!(!space.sdf);
+        assert!(space.metrics.advance_x > 0);
+    }
+
+    #[test]
+    fn test_stats_count_lookups() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        assert!(!cache.count_lookup('a'));
+
+        // Stand in for what get_character uploads
+        cache.character_map.insert(
+            'a',
+            CachedCharacter {
+                texture: NativeTexture(std::num::NonZeroU32::new(1).unwrap()),
+                uv: [[0.0; 2]; 2],
+                advance_x: 0,
+                left: 0,
+                top: 0,
+                width: 0,
+                height: 0,
+                sdf: true,
+            },
+        );
+        assert!(cache.count_lookup('a'));
+        assert!(cache.count_lookup('a'));
+        assert!(!cache.count_lookup('b'));
+
+        assert_eq!(
+            cache.stats(),
+            GlyphCacheStats {
+                hits: 2,
+                misses: 2,
+                entries: 1,
+                pages: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        let i = cache.metrics('i').unwrap();
+        let w = cache.metrics('W').unwrap();
+
+        // Hack is monospaced
+        assert_eq!(i.advance_x, w.advance_x);
+        assert!(i.advance_x > 0);
+        assert!(w.width > i.width);
+        assert_eq!(cache.metrics('i').unwrap(), i);
+        // Hack's advance is ~19.3px at this size, hinting would round it
+        assert_ne!(i.advance_x % 64, 0);
     }
 }
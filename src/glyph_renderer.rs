@@ -1,6 +1,14 @@
 use glow::{HasContext, NativeBuffer, NativeProgram, NativeVertexArray};
+use thiserror::Error;
 
-use crate::{gl_util, glyph_cache::GlyphCache, GlError};
+use std::{iter::Peekable, str::Chars};
+
+use crate::{
+    cursor_renderer::CursorRenderer,
+    gl_util,
+    glyph_cache::{GetCharacterError, GlyphCache},
+    GlError,
+};
 
 unsafe fn shader_input_to_u8_slice(input: &[ShaderInput]) -> &[u8] {
     core::slice::from_raw_parts(input.as_ptr() as *const u8, std::mem::size_of_val(input))
@@ -59,12 +67,526 @@ macro_rules! shader_input_offset {
     }};
 }
 
-enum CursorMovement {
-    Repeat(f32),
-    Horiz(f32),
-    Vert(f32),
+/// Which way the pen moves along a line. RTL only mirrors the advance, there is no bidi
+/// reordering or contextual shaping, so joined scripts like Arabic show their isolated forms
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextDirection {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+/// Applies to the whole string passed to render_str
+#[derive(Debug, Clone, Copy)]
+pub struct TextStyle {
+    pub direction: TextDirection,
+    pub underline: bool,
+    pub strikethrough: bool,
+    /// Keep going past the edge instead of starting a new line, for text that gets clipped
+    pub no_wrap: bool,
+    pub overflow: TextOverflow,
+    /// Multiplies glyph size and line height, 1 is the size of line_height
+    pub scale: f32,
+    /// Color of text outside any SGR color escape
+    pub color: [f32; 3],
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            direction: TextDirection::default(),
+            underline: false,
+            strikethrough: false,
+            no_wrap: false,
+            overflow: TextOverflow::default(),
+            scale: 1.0,
+            color: DEFAULT_TEXT_COLOR,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("unknown overflow policy, expected clip, scroll or shrink")]
+pub struct ParseTextOverflowError;
+
+/// What happens when the lines run past the bottom of the region
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextOverflow {
+    /// Lines past the bottom are left out
+    #[default]
+    Clip,
+    /// Everything moves up so the last line sits on the bottom, the first lines go instead
+    Scroll,
+    /// Text gets smaller until the last line fits
+    Shrink,
+}
+
+impl std::str::FromStr for TextOverflow {
+    type Err = ParseTextOverflowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clip" => Ok(TextOverflow::Clip),
+            "scroll" => Ok(TextOverflow::Scroll),
+            "shrink" => Ok(TextOverflow::Shrink),
+            _ => Err(ParseTextOverflowError),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("unknown alignment, expected top-left, top, top-right, left, center, right, bottom-left, bottom or bottom-right")]
+pub struct ParseTextAlignError;
+
+/// Point of a block of text that gets placed, e.g. on an anchor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl TextAlign {
+    /// How far across and down the block the point is, 0 to 1 from the top left
+    pub fn fractions(self) -> (f32, f32) {
+        match self {
+            TextAlign::TopLeft => (0.0, 0.0),
+            TextAlign::Top => (0.5, 0.0),
+            TextAlign::TopRight => (1.0, 0.0),
+            TextAlign::Left => (0.0, 0.5),
+            TextAlign::Center => (0.5, 0.5),
+            TextAlign::Right => (1.0, 0.5),
+            TextAlign::BottomLeft => (0.0, 1.0),
+            TextAlign::Bottom => (0.5, 1.0),
+            TextAlign::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+impl std::str::FromStr for TextAlign {
+    type Err = ParseTextAlignError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top-left" => Ok(TextAlign::TopLeft),
+            "top" => Ok(TextAlign::Top),
+            "top-right" => Ok(TextAlign::TopRight),
+            "left" => Ok(TextAlign::Left),
+            "center" => Ok(TextAlign::Center),
+            "right" => Ok(TextAlign::Right),
+            "bottom-left" => Ok(TextAlign::BottomLeft),
+            "bottom" => Ok(TextAlign::Bottom),
+            "bottom-right" => Ok(TextAlign::BottomRight),
+            _ => Err(ParseTextAlignError),
+        }
+    }
 }
 
+/// Area text is laid out in, in render_str units. Lines wrap at the side edges, and lines whose
+/// baseline falls outside the top or bottom are left out
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextRegion {
+    pub x_min: f32,
+    pub y_min: f32,
+    pub x_max: f32,
+    pub y_max: f32,
+}
+
+impl TextRegion {
+    pub const FULL: TextRegion = TextRegion {
+        x_min: 0.0,
+        y_min: 0.0,
+        x_max: 1.0,
+        y_max: 1.0,
+    };
+
+    fn contains_baseline(&self, y: f32) -> bool {
+        (self.y_min..=self.y_max).contains(&y)
+    }
+}
+
+/// Space the lines of some laid out text take up, from the start of each line to where its pen
+/// stopped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextBounds {
+    pub x_min: f32,
+    pub x_max: f32,
+    pub top_baseline: f32,
+    pub bottom_baseline: f32,
+    /// Smaller than GlyphRenderer::line_height when the text was scaled or shrunk to fit
+    pub line_height: f32,
+}
+
+// Distance between baselines in glyph pixels
+const LINE_HEIGHT_PX: f32 = 400.0;
+
+// Decoration placement as a fraction of the line height, relative to the baseline
+const UNDERLINE_OFFSET: f32 = -0.1;
+const STRIKETHROUGH_OFFSET: f32 = 0.25;
+const DECORATION_THICKNESS: f32 = 0.05;
+
+const DEFAULT_TEXT_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+// Colors for SGR codes 30-37, the bright 90-97 range uses the second half
+const ANSI_COLORS: [[f32; 3]; 16] = [
+    [0.0, 0.0, 0.0],
+    [0.8, 0.2, 0.2],
+    [0.3, 0.75, 0.3],
+    [0.8, 0.7, 0.2],
+    [0.3, 0.45, 0.85],
+    [0.7, 0.35, 0.75],
+    [0.3, 0.7, 0.75],
+    [0.8, 0.8, 0.8],
+    [0.5, 0.5, 0.5],
+    [1.0, 0.4, 0.4],
+    [0.5, 0.95, 0.5],
+    [1.0, 0.9, 0.4],
+    [0.5, 0.65, 1.0],
+    [0.9, 0.55, 0.95],
+    [0.5, 0.9, 0.95],
+    [1.0, 1.0, 1.0],
+];
+
+// SDF value the glyph edge sits at, bold moves it outwards to thicken strokes
+const SDF_EDGE: f32 = 0.5;
+const BOLD_SDF_EDGE: f32 = 0.3;
+// Freetype's default, the distance field covers this many texels either side of the edge
+const SDF_SPREAD_PX: f32 = 8.0;
+// Default width of the edge fade as a fraction of the glyph height, so it looks the same at any
+// pixel size
+const EDGE_SOFTNESS_EM: f32 = 0.0145;
+// Horizontal shift per unit of height above the baseline for italics, about 12 degrees
+const ITALIC_SHEAR: f32 = 0.21;
+
+/// How a span of text looks, set through SGR escapes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SgrAttrs {
+    pub color: [f32; 3],
+    /// Synthesized from the regular face by thickening the SDF, see BOLD_SDF_EDGE
+    pub bold: bool,
+    /// Synthesized by shearing the glyph quad, see ITALIC_SHEAR
+    pub italic: bool,
+    /// Takes up its space without being drawn
+    pub hidden: bool,
+}
+
+impl Default for SgrAttrs {
+    fn default() -> Self {
+        SgrAttrs {
+            color: DEFAULT_TEXT_COLOR,
+            bold: false,
+            italic: false,
+            hidden: false,
+        }
+    }
+}
+
+/// Iterates the visible characters of a string along with their attributes, stripping ANSI
+/// escape sequences. Only SGR foreground colors, bold, italic, hidden and resets do anything, other
+/// sequences are dropped.
+/// A sequence cut off by the end of the string is dropped too, so half typed escapes never show
+pub struct SgrChars<'a> {
+    chars: Peekable<Chars<'a>>,
+    attrs: SgrAttrs,
+    default_color: [f32; 3],
+}
+
+impl<'a> SgrChars<'a> {
+    pub fn new(s: &'a str) -> SgrChars<'a> {
+        Self::with_color(s, DEFAULT_TEXT_COLOR)
+    }
+
+    /// Resets go back to color instead of DEFAULT_TEXT_COLOR
+    pub fn with_color(s: &'a str, color: [f32; 3]) -> SgrChars<'a> {
+        SgrChars {
+            chars: s.chars().peekable(),
+            attrs: SgrAttrs {
+                color,
+                ..SgrAttrs::default()
+            },
+            default_color: color,
+        }
+    }
+
+    fn apply_sgr(&mut self, params: &str) {
+        // An empty parameter list means reset
+        for param in params.split(';') {
+            match param.parse::<usize>().unwrap_or(0) {
+                0 => {
+                    self.attrs = SgrAttrs {
+                        color: self.default_color,
+                        ..SgrAttrs::default()
+                    }
+                }
+                1 => self.attrs.bold = true,
+                3 => self.attrs.italic = true,
+                8 => self.attrs.hidden = true,
+                22 => self.attrs.bold = false,
+                23 => self.attrs.italic = false,
+                28 => self.attrs.hidden = false,
+                39 => self.attrs.color = self.default_color,
+                code @ 30..=37 => self.attrs.color = ANSI_COLORS[code - 30],
+                code @ 90..=97 => self.attrs.color = ANSI_COLORS[code - 90 + 8],
+                _ => (),
+            }
+        }
+    }
+
+    /// Called after ESC [, consumes up to and including the final byte
+    fn consume_csi(&mut self) {
+        let mut params = String::new();
+        for c in self.chars.by_ref() {
+            if ('\u{40}'..='\u{7e}').contains(&c) {
+                if c == 'm' {
+                    self.apply_sgr(&params);
+                }
+                return;
+            }
+            params.push(c);
+        }
+    }
+}
+
+impl Iterator for SgrChars<'_> {
+    type Item = (char, SgrAttrs);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let c = self.chars.next()?;
+            if c != '\x1b' {
+                return Some((c, self.attrs));
+            }
+
+            if self.chars.next_if_eq(&'[').is_some() {
+                self.consume_csi();
+            }
+        }
+    }
+}
+
+/// Converts from glyph pixels to the units render_str positions text in
+fn glyph_scale(pixel_size: u32) -> f32 {
+    1.0f32 / 32.0 / pixel_size as f32
+}
+
+/// Width of the widest line of s in render_str units, ignoring wrapping. The pen position is
+/// kept as an unrounded float so the result is exactly the sum of the glyph advances
+pub fn measure_str(glyph_cache: &mut GlyphCache, s: &str) -> Result<f32, GetCharacterError> {
+    let scale = glyph_scale(glyph_cache.pixel_size());
+    let mut widest = 0.0f32;
+    let mut advance = 0.0f32;
+    for (c, _) in SgrChars::new(s) {
+        if c == '\n' {
+            advance = 0.0;
+            continue;
+        }
+        advance += glyph_cache.metrics(c)?.advance_x as f32 / 64.0 * scale;
+        widest = widest.max(advance);
+    }
+    Ok(widest)
+}
+
+struct PlacedGlyph {
+    c: char,
+    attrs: SgrAttrs,
+    baseline: f32,
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+impl PlacedGlyph {
+    /// Quad corners in triangle strip order, bottom left, bottom right, top left, top right.
+    /// Italics lean the quad about the baseline so the glyph stays on its line
+    fn corners(&self) -> [[f32; 2]; 4] {
+        let shear = if self.attrs.italic { ITALIC_SHEAR } else { 0.0 };
+        let lean = |y: f32| (y - self.baseline) * shear;
+        let (x, y, w, h) = (self.x, self.y, self.w, self.h);
+        [
+            [x + lean(y), y],
+            [x + w + lean(y), y],
+            [x + lean(y + h), y + h],
+            [x + w + lean(y + h), y + h],
+        ]
+    }
+}
+
+/// Absolute glyph quads for a string, ready to draw
+struct TextLayout {
+    glyphs: Vec<PlacedGlyph>,
+    /// (x0, x1, baseline) of each line, for decorations
+    lines: Vec<(f32, f32, f32)>,
+    line_height: f32,
+    /// Where the pen ends up after the last glyph, on the new line if that glyph wrapped
+    pen: (f32, f32),
+}
+
+impl TextLayout {
+    fn bottom_baseline(&self) -> f32 {
+        self.lines.last().map(|line| line.2).unwrap_or(0.0)
+    }
+
+    fn offset_y(&mut self, offset: f32) {
+        for glyph in &mut self.glyphs {
+            glyph.baseline += offset;
+            glyph.y += offset;
+        }
+        for line in &mut self.lines {
+            line.2 += offset;
+        }
+        self.pen.1 += offset;
+    }
+
+    /// None when no line left after clipping has anything on it
+    fn bounds(&self) -> Option<TextBounds> {
+        let (x_min, x_max) = self.lines.iter().filter(|&&(x0, x1, _)| x0 != x1).fold(
+            None,
+            |acc: Option<(f32, f32)>, &(x0, x1, _)| {
+                let (lo, hi) = (x0.min(x1), x0.max(x1));
+                Some(acc.map_or((lo, hi), |(a, b)| (a.min(lo), b.max(hi))))
+            },
+        )?;
+        Some(TextBounds {
+            x_min,
+            x_max,
+            top_baseline: self.lines.first()?.2,
+            bottom_baseline: self.bottom_baseline(),
+            line_height: self.line_height,
+        })
+    }
+
+    fn clip(&mut self, region: TextRegion) {
+        self.glyphs
+            .retain(|glyph| region.contains_baseline(glyph.baseline));
+        self.lines
+            .retain(|&(_, _, baseline)| region.contains_baseline(baseline));
+    }
+}
+
+/// Wrapped lines start back at x rather than the region's edge, so x acts as a margin
+fn layout_str(
+    glyph_cache: &mut GlyphCache,
+    s: &str,
+    x: f32,
+    y: f32,
+    region: TextRegion,
+    style: TextStyle,
+) -> Result<TextLayout, GetCharacterError> {
+    let scale = glyph_scale(glyph_cache.pixel_size()) * style.scale;
+    let mut layout = layout_unclipped(glyph_cache, s, x, y, region, style, scale)?;
+
+    let bottom = layout.bottom_baseline();
+    if bottom < region.y_min && y > region.y_min {
+        match style.overflow {
+            TextOverflow::Clip => (),
+            TextOverflow::Scroll => layout.offset_y(region.y_min - bottom),
+            TextOverflow::Shrink => {
+                // Shrinking only ever rewraps into fewer lines, so one pass is enough
+                let shrink = (y - region.y_min) / (y - bottom);
+                layout = layout_unclipped(glyph_cache, s, x, y, region, style, scale * shrink)?;
+                // Rounding can leave the last line a hair under the bottom
+                layout.offset_y((region.y_min - layout.bottom_baseline()).max(0.0));
+            }
+        }
+    }
+
+    layout.clip(region);
+    Ok(layout)
+}
+
+fn layout_unclipped(
+    glyph_cache: &mut GlyphCache,
+    s: &str,
+    x: f32,
+    y: f32,
+    region: TextRegion,
+    style: TextStyle,
+    scale: f32,
+) -> Result<TextLayout, GetCharacterError> {
+    let line_height = LINE_HEIGHT_PX * scale;
+
+    let mut glyphs = Vec::new();
+    let mut lines = Vec::new();
+    let mut advance = 0.0f32;
+    let mut advance_y = 0.0f32;
+
+    for (c, attrs) in SgrChars::with_color(s, style.color) {
+        if c == '\n' {
+            lines.push((x, x + advance, y + advance_y));
+            advance = 0.0;
+            advance_y -= line_height;
+            continue;
+        }
+
+        let metrics = glyph_cache.metrics(c)?;
+        // Pen positions stay fractional, the sdf handles sub pixel placement fine
+        let glyph_advance = metrics.advance_x as f32 / 64.0 * scale;
+        // RTL pens sit at the right edge of the glyph's advance
+        let glyph_x = |advance: f32| {
+            let pen_x = match style.direction {
+                TextDirection::Ltr => x + advance,
+                TextDirection::Rtl => x + advance - glyph_advance,
+            };
+            pen_x + metrics.left as f32 * scale
+        };
+        let w = metrics.width as f32 * scale;
+
+        let mut left = glyph_x(advance);
+        let overflows = match style.direction {
+            _ if style.no_wrap => false,
+            TextDirection::Ltr => left + w > region.x_max,
+            TextDirection::Rtl => left < region.x_min,
+        };
+        // A glyph wider than the whole region can't fit anywhere, it gets a line to itself
+        if overflows && advance != 0.0 {
+            lines.push((x, x + advance, y + advance_y));
+            advance = 0.0;
+            advance_y -= line_height;
+            left = glyph_x(advance);
+        }
+
+        let baseline = y + advance_y;
+        if !attrs.hidden {
+            glyphs.push(PlacedGlyph {
+                c,
+                attrs,
+                baseline,
+                x: left,
+                y: baseline + (metrics.top - metrics.height) as f32 * scale,
+                w,
+                h: metrics.height as f32 * scale,
+            });
+        }
+
+        advance += match style.direction {
+            TextDirection::Ltr => glyph_advance,
+            TextDirection::Rtl => -glyph_advance,
+        };
+    }
+    lines.push((x, x + advance, y + advance_y));
+
+    Ok(TextLayout {
+        glyphs,
+        lines,
+        line_height,
+        pen: (x + advance, y + advance_y),
+    })
+}
+
+/// Edge softness in distance field units for glyphs rasterized at pixel_size
+fn default_edge_softness(pixel_size: u32) -> f32 {
+    let softness_px = EDGE_SOFTNESS_EM * pixel_size as f32;
+    (softness_px / (2.0 * SDF_SPREAD_PX)).min(1.0 - SDF_EDGE)
+}
+
+/// Relies on the blending from gl_util::begin_pass for antialiased glyph edges
 pub struct GlyphRenderer<'a> {
     program: NativeProgram,
     vao: NativeVertexArray,
@@ -72,6 +594,15 @@ pub struct GlyphRenderer<'a> {
     gl: &'a glow::Context,
     glyph_cache: &'a mut GlyphCache,
     aspect_loc: <glow::Context as HasContext>::UniformLocation,
+    text_color_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    edge_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    softness_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    coverage_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    edge_softness: f32,
+    // Underlines and strikethroughs are plain quads
+    quad_renderer: CursorRenderer<'a>,
+    glyphs_drawn: usize,
+    draw_calls: usize,
 }
 
 impl<'a> GlyphRenderer<'a> {
@@ -84,7 +615,7 @@ impl<'a> GlyphRenderer<'a> {
                 gl,
                 include_str!("glsl/vertex.glsl"),
                 include_str!("glsl/sdf_fragment.glsl"),
-            );
+            )?;
 
             let vao = gl.create_vertex_array().map_err(GlError)?;
             gl.bind_vertex_array(Some(vao));
@@ -109,6 +640,13 @@ impl<'a> GlyphRenderer<'a> {
                 .get_uniform_location(program, "aspect_ratio")
                 .expect("Invalid vertex shader");
 
+            let text_color_loc = gl.get_uniform_location(program, "text_color");
+            let edge_loc = gl.get_uniform_location(program, "edge");
+            let softness_loc = gl.get_uniform_location(program, "softness");
+            let coverage_loc = gl.get_uniform_location(program, "coverage");
+            let edge_softness = default_edge_softness(glyph_cache.pixel_size());
+            let quad_renderer = CursorRenderer::new(gl)?;
+
             Ok(GlyphRenderer {
                 program,
                 vao,
@@ -116,37 +654,93 @@ impl<'a> GlyphRenderer<'a> {
                 gl,
                 glyph_cache,
                 aspect_loc,
+                text_color_loc,
+                edge_loc,
+                softness_loc,
+                coverage_loc,
+                edge_softness,
+                quad_renderer,
+                glyphs_drawn: 0,
+                draw_calls: 0,
             })
         }
     }
 
+    /// Swaps in a new sdf fragment shader, the current one stays if it doesn't build
+    pub fn reload_fragment_shader(&mut self, source: &str) -> Result<(), GlError> {
+        unsafe {
+            let program =
+                gl_util::compile_program(self.gl, include_str!("glsl/vertex.glsl"), source)?;
+            let Some(aspect_loc) = self.gl.get_uniform_location(program, "aspect_ratio") else {
+                self.gl.delete_program(program);
+                return Err(GlError(
+                    "aspect_ratio uniform was optimized out".to_string(),
+                ));
+            };
+            self.gl.delete_program(self.program);
+
+            self.program = program;
+            self.aspect_loc = aspect_loc;
+            self.text_color_loc = self.gl.get_uniform_location(program, "text_color");
+            self.edge_loc = self.gl.get_uniform_location(program, "edge");
+            self.softness_loc = self.gl.get_uniform_location(program, "softness");
+            self.coverage_loc = self.gl.get_uniform_location(program, "coverage");
+        }
+        Ok(())
+    }
+
+    /// Width of the fade at glyph edges in distance field units. Around 0.02 is crisp but aliases
+    /// when text is small on screen, 0.2 to 0.3 is soft, and from 0.5 whole glyphs turn
+    /// translucent. The default depends on the glyph cache's pixel size
+    pub fn set_edge_softness(&mut self, softness: f32) {
+        self.edge_softness = softness;
+    }
+
     fn scale(&self) -> f32 {
-        1.0f32 / 32.0 / self.glyph_cache.pixel_size() as f32
+        glyph_scale(self.glyph_cache.pixel_size())
+    }
+
+    pub fn measure_str(&mut self, s: &str) -> f32 {
+        measure_str(self.glyph_cache, s).unwrap()
     }
 
     pub fn line_height(&self) -> f32 {
-        400.0 * self.scale()
+        LINE_HEIGHT_PX * self.scale()
     }
 
-    fn render_char(&mut self, c: char, x: f32, y: f32, aspect: f32) -> CursorMovement {
-        let scale = self.scale();
-        let line_height = self.line_height();
+    /// What render_str with the same arguments would cover, after wrapping and overflow
+    pub fn text_bounds(
+        &mut self,
+        s: &str,
+        x: f32,
+        y: f32,
+        region: TextRegion,
+        style: TextStyle,
+    ) -> Option<TextBounds> {
+        layout_str(self.glyph_cache, s, x, y, region, style)
+            .unwrap()
+            .bounds()
+    }
 
-        if c == '\n' {
-            return CursorMovement::Vert(-line_height);
-        }
+    /// Where render_str with the same arguments leaves the pen, without drawing anything
+    pub fn pen_after(
+        &mut self,
+        s: &str,
+        x: f32,
+        y: f32,
+        region: TextRegion,
+        style: TextStyle,
+    ) -> (f32, f32) {
+        layout_str(self.glyph_cache, s, x, y, region, style)
+            .unwrap()
+            .pen
+    }
 
+    fn render_glyph(&mut self, glyph: &PlacedGlyph, aspect: f32) {
         let gl = self.gl;
-
-        let g_info = self.glyph_cache.get_character(gl, c).unwrap();
-        let x = x + g_info.left as f32 * scale;
-        let y = y + (g_info.top - g_info.height) as f32 * scale;
-        let w = g_info.width as f32 * scale;
-        let h = g_info.height as f32 * scale;
-
-        if x + w > 1.0 {
-            return CursorMovement::Repeat(-line_height);
-        }
+        let g_info = self.glyph_cache.get_character(gl, glyph.c).unwrap();
+        let [bottom_left, bottom_right, top_left, top_right] = glyph.corners();
+        let [[u0, v0], [u1, v1]] = g_info.uv;
 
         unsafe {
             gl.use_program(Some(self.program));
@@ -154,20 +748,20 @@ impl<'a> GlyphRenderer<'a> {
 
             let verts: &[ShaderInput] = &[
                 ShaderInput {
-                    vert_coord: [x, y],
-                    tex_coord: [0.0f32, 1f32],
+                    vert_coord: bottom_left,
+                    tex_coord: [u0, v1],
                 },
                 ShaderInput {
-                    vert_coord: [x + w, y],
-                    tex_coord: [1.0f32, 1.0f32],
+                    vert_coord: bottom_right,
+                    tex_coord: [u1, v1],
                 },
                 ShaderInput {
-                    vert_coord: [x, y + h],
-                    tex_coord: [0.0f32, 0.0f32],
+                    vert_coord: top_left,
+                    tex_coord: [u0, v0],
                 },
                 ShaderInput {
-                    vert_coord: [x + w, y + h],
-                    tex_coord: [1.0f32, 0.0f32],
+                    vert_coord: top_right,
+                    tex_coord: [u1, v0],
                 },
             ];
 
@@ -178,40 +772,90 @@ impl<'a> GlyphRenderer<'a> {
             gl.bind_texture(glow::TEXTURE_2D, Some(g_info.texture));
 
             gl.uniform_1_f32(Some(&self.aspect_loc), aspect);
+            gl.uniform_3_f32_slice(self.text_color_loc.as_ref(), &glyph.attrs.color);
+            let edge = if glyph.attrs.bold {
+                BOLD_SDF_EDGE
+            } else {
+                SDF_EDGE
+            };
+            gl.uniform_1_f32(self.edge_loc.as_ref(), edge);
+            gl.uniform_1_f32(self.softness_loc.as_ref(), self.edge_softness);
+            gl.uniform_1_i32(self.coverage_loc.as_ref(), !g_info.sdf as i32);
 
             gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+            self.glyphs_drawn += 1;
+            self.draw_calls += 1;
             gl.bind_vertex_array(None);
             gl.use_program(None);
         }
-
-        CursorMovement::Horiz(g_info.advance_x as f32 / 64.0f32 * scale)
     }
 
-    pub fn render_str(&mut self, s: &str, x: f32, y: f32, aspect: f32) -> (f32, f32) {
-        let mut advance = 0.0f32;
-        let mut advance_y = 0.0f32;
-        let mut it = s.chars();
-        let mut c = it.next();
-        loop {
-            if c.is_none() {
-                break;
-            }
+    /// Draws the enabled decorations under a line spanning x0 to x1 at the given baseline
+    fn render_decorations(
+        &mut self,
+        (x0, x1, baseline): (f32, f32, f32),
+        line_height: f32,
+        aspect: f32,
+        style: TextStyle,
+    ) {
+        if x0 == x1 {
+            return;
+        }
 
-            match self.render_char(c.unwrap(), x + advance, y + advance_y, aspect) {
-                CursorMovement::Vert(v) => {
-                    advance_y += v;
-                    advance = 0.0;
-                }
-                CursorMovement::Horiz(v) => advance += v,
-                CursorMovement::Repeat(v) => {
-                    advance_y += v;
-                    advance = 0.0;
-                    continue;
-                }
+        let offsets = [
+            (style.underline, UNDERLINE_OFFSET),
+            (style.strikethrough, STRIKETHROUGH_OFFSET),
+        ];
+        for (enabled, offset) in offsets {
+            if enabled {
+                self.quad_renderer.render(
+                    x0.min(x1),
+                    baseline + offset * line_height,
+                    (x1 - x0).abs(),
+                    DECORATION_THICKNESS * line_height,
+                    aspect,
+                );
+                self.draw_calls += 1;
             }
-            c = it.next();
         }
-        (advance, advance_y)
+    }
+
+    /// Glyphs drawn since the last call to [`GlyphRenderer::reset_counters`], each one is a draw
+    /// call
+    pub fn glyphs_drawn(&self) -> usize {
+        self.glyphs_drawn
+    }
+
+    /// Glyphs plus decorations
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+
+    pub fn reset_counters(&mut self) {
+        self.glyphs_drawn = 0;
+        self.draw_calls = 0;
+    }
+
+    /// Starts the pen at x, y and wraps within region, returning where the pen finished, which
+    /// is where a cursor after the text belongs. For RTL x is the right edge lines start from and
+    /// the pen moves left. s may contain ANSI color escapes, see SgrChars
+    pub fn render_str(
+        &mut self,
+        s: &str,
+        x: f32,
+        y: f32,
+        region: TextRegion,
+        aspect: f32,
+        style: TextStyle,
+    ) -> (f32, f32) {
+        let layout = layout_str(self.glyph_cache, s, x, y, region, style).unwrap();
+        for glyph in &layout.glyphs {
+            self.render_glyph(glyph, aspect);
+        }
+        for &line in &layout.lines {
+            self.render_decorations(line, layout.line_height, aspect, style);
+        }
+        layout.pen
     }
 }
 
@@ -224,3 +868,322 @@ impl Drop for GlyphRenderer<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn visible(s: &str) -> String {
+        SgrChars::new(s).map(|(c, _)| c).collect()
+    }
+
+    #[test]
+    fn test_sgr_stripped() {
+        assert_eq!(visible("\x1b[32m$\x1b[0m ls"), "$ ls");
+        assert_eq!(visible("a\x1b[1;31mb\x1b[mc"), "abc");
+        // Non color sequences and cut off sequences take no space either
+        assert_eq!(visible("a\x1b[2Kb\x1b[3"), "ab");
+        assert_eq!(visible("\x1bx"), "x");
+    }
+
+    #[test]
+    fn test_default_edge_softness() {
+        // The same share of the glyph fades at any size
+        let softness_px = |size| default_edge_softness(size) * 2.0 * SDF_SPREAD_PX / size as f32;
+        assert!((softness_px(64) - softness_px(256)).abs() < 0.0001);
+        assert!((default_edge_softness(256) - 0.23).abs() < 0.01);
+
+        // Past the far side of the distance field there's nothing left to fade over
+        assert_eq!(default_edge_softness(100_000), 1.0 - SDF_EDGE);
+    }
+
+    #[test]
+    fn test_measure_str_sums_advances() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        let s = "Hello, world";
+
+        let scale = glyph_scale(cache.pixel_size());
+        let mut expected = 0.0f32;
+        for c in s.chars() {
+            expected += cache.metrics(c).unwrap().advance_x as f32 / 64.0 * scale;
+        }
+
+        assert_eq!(measure_str(&mut cache, s).unwrap(), expected);
+        assert_eq!(
+            measure_str(&mut cache, "ab\n\x1b[32mHello, world").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_style_scale_and_color() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        let layout = |cache: &mut GlyphCache, style| {
+            layout_str(
+                cache,
+                "H\x1b[31mH\x1b[0mH",
+                0.0,
+                0.9,
+                TextRegion::FULL,
+                style,
+            )
+            .unwrap()
+        };
+
+        let regular = layout(&mut cache, TextStyle::default());
+        let color = [0.5, 0.6, 0.7];
+        let small = layout(
+            &mut cache,
+            TextStyle {
+                scale: 0.5,
+                color,
+                ..TextStyle::default()
+            },
+        );
+
+        assert!((small.line_height - regular.line_height / 2.0).abs() < 1e-6);
+        assert!((small.glyphs[0].w - regular.glyphs[0].w / 2.0).abs() < 1e-6);
+        assert!((small.pen.0 - regular.pen.0 / 2.0).abs() < 1e-6);
+
+        // Escapes still override the style color, resets go back to it
+        let colors: Vec<[f32; 3]> = small.glyphs.iter().map(|g| g.attrs.color).collect();
+        assert_eq!(colors, [color, ANSI_COLORS[1], color]);
+    }
+
+    #[test]
+    fn test_narrow_region_wraps_earlier() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        let s = "the quick brown fox jumps over the lazy dog";
+        let line_count = |cache: &mut GlyphCache, x_max| {
+            let region = TextRegion {
+                x_max,
+                ..TextRegion::FULL
+            };
+            let layout = layout_str(cache, s, 0.0, 0.9, region, TextStyle::default()).unwrap();
+            assert!(layout.glyphs.iter().all(|g| g.x + g.w <= x_max));
+            layout.lines.len()
+        };
+
+        let full = line_count(&mut cache, 1.0);
+        let narrow = line_count(&mut cache, 0.3);
+        assert!(narrow > full, "{narrow} lines should be more than {full}");
+    }
+
+    #[test]
+    fn test_bounds_follow_wrapping() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        let advance = measure_str(&mut cache, "m").unwrap();
+        let region = TextRegion {
+            x_max: 0.1 + advance * 4.5,
+            ..TextRegion::FULL
+        };
+        let layout = |cache: &mut GlyphCache, s, style| {
+            layout_str(cache, s, 0.1, 0.5, region, style).unwrap()
+        };
+
+        // Seven glyphs wrap after four, one line's worth wide and two tall
+        let bounds = layout(&mut cache, "mmmmmmm", TextStyle::default())
+            .bounds()
+            .unwrap();
+        assert_eq!(bounds.x_min, 0.1);
+        assert!((bounds.x_max - (0.1 + advance * 4.0)).abs() < 1e-6);
+        assert_eq!(bounds.top_baseline, 0.5);
+        assert_eq!(bounds.bottom_baseline, 0.5 - bounds.line_height);
+
+        let rtl = TextStyle {
+            direction: TextDirection::Rtl,
+            ..TextStyle::default()
+        };
+        let bounds = layout(&mut cache, "mm", rtl).bounds().unwrap();
+        assert!((bounds.x_min - (0.1 - advance * 2.0)).abs() < 1e-6);
+        assert_eq!(bounds.x_max, 0.1);
+
+        assert_eq!(layout(&mut cache, "", TextStyle::default()).bounds(), None);
+        assert_eq!(
+            layout(&mut cache, "\n", TextStyle::default()).bounds(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_region_skips_lines_below() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        let line_height = LINE_HEIGHT_PX * glyph_scale(32);
+        let region = TextRegion {
+            y_min: 0.9 - line_height * 1.5,
+            ..TextRegion::FULL
+        };
+
+        let layout = layout_str(
+            &mut cache,
+            "a\nb\nc",
+            0.0,
+            0.9,
+            region,
+            TextStyle::default(),
+        )
+        .unwrap();
+        let drawn: String = layout.glyphs.iter().map(|g| g.c).collect();
+        assert_eq!(drawn, "ab");
+        assert_eq!(layout.lines.len(), 2);
+        // The pen still moves past the skipped line
+        assert_eq!(layout.pen.1, 0.9 - 2.0 * line_height);
+    }
+
+    #[test]
+    fn test_pen_after_wrap_on_last_line() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        // Hack is monospaced, so every glyph moves the pen the same amount
+        let advance = measure_str(&mut cache, "m").unwrap();
+        let region = TextRegion {
+            x_max: 0.1 + advance * 4.5,
+            ..TextRegion::FULL
+        };
+
+        let layout = layout_str(
+            &mut cache,
+            "mmmmmmm",
+            0.1,
+            0.5,
+            region,
+            TextStyle::default(),
+        )
+        .unwrap();
+        assert_eq!(layout.lines.len(), 2);
+        let (x, y) = layout.pen;
+        assert!((x - (0.1 + advance * 3.0)).abs() < 1e-6, "{x}");
+        assert_eq!(y, 0.5 - layout.line_height);
+
+        // Wrapping on the very last glyph puts the pen one glyph into the new line
+        let layout =
+            layout_str(&mut cache, "mmmmm", 0.1, 0.5, region, TextStyle::default()).unwrap();
+        let (x, y) = layout.pen;
+        assert!((x - (0.1 + advance)).abs() < 1e-6, "{x}");
+        assert_eq!(y, 0.5 - layout.line_height);
+        let last = layout.glyphs.last().unwrap();
+        assert!(last.x < x && last.baseline == y);
+    }
+
+    #[test]
+    fn test_long_text_overflow() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        let s = (0..100)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let region = TextRegion {
+            y_min: 0.2,
+            ..TextRegion::FULL
+        };
+        let mut layout = |overflow| {
+            let style = TextStyle {
+                overflow,
+                ..TextStyle::default()
+            };
+            let layout = layout_str(&mut cache, &s, 0.0, 0.9, region, style).unwrap();
+            assert!(layout
+                .glyphs
+                .iter()
+                .all(|g| region.contains_baseline(g.baseline)));
+            layout
+        };
+
+        let clipped = layout(TextOverflow::Clip);
+        assert_eq!(clipped.glyphs[0].c, 'l');
+        assert!(clipped.lines.len() < 100);
+
+        // Scrolling keeps the tail, the last line sits on the bottom of the region
+        let scrolled = layout(TextOverflow::Scroll);
+        let drawn: String = scrolled.glyphs.iter().map(|g| g.c).collect();
+        assert!(drawn.ends_with("line 99"));
+        assert!(!drawn.starts_with("line 0line 1"));
+        assert!((scrolled.bottom_baseline() - region.y_min).abs() < 0.0001);
+
+        let shrunk = layout(TextOverflow::Shrink);
+        assert_eq!(shrunk.lines.len(), 100);
+        assert!(shrunk.line_height < clipped.line_height);
+    }
+
+    #[test]
+    fn test_sgr_bold_italic() {
+        let attrs: Vec<(bool, bool)> = SgrChars::new("a\x1b[1mb\x1b[3mc\x1b[22md\x1b[0me")
+            .map(|(_, attrs)| (attrs.bold, attrs.italic))
+            .collect();
+        assert_eq!(
+            attrs,
+            [
+                (false, false),
+                (true, false),
+                (true, true),
+                (false, true),
+                (false, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hidden_keeps_advance() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        let mut layout = |s| {
+            layout_str(
+                &mut cache,
+                s,
+                0.0,
+                0.5,
+                TextRegion::FULL,
+                TextStyle::default(),
+            )
+            .unwrap()
+        };
+        let shown = layout("a:b");
+        let hidden = layout("a\x1b[8m:\x1b[28mb");
+
+        assert_eq!(hidden.glyphs.len(), 2);
+        assert_eq!(hidden.glyphs[1].x, shown.glyphs[2].x);
+        assert_eq!(hidden.pen, shown.pen);
+    }
+
+    #[test]
+    fn test_italic_shears_quad() {
+        let mut cache = GlyphCache::new(32, None, 0).unwrap();
+        let layout = layout_str(
+            &mut cache,
+            "l\x1b[3ml",
+            0.0,
+            0.5,
+            TextRegion::FULL,
+            TextStyle::default(),
+        )
+        .unwrap();
+        let [upright, italic] = [&layout.glyphs[0], &layout.glyphs[1]];
+
+        let [bottom_left, _, top_left, _] = upright.corners();
+        assert_eq!(bottom_left[0], top_left[0]);
+
+        let [bottom_left, bottom_right, top_left, top_right] = italic.corners();
+        let top = italic.y + italic.h - italic.baseline;
+        let bottom = italic.y - italic.baseline;
+        assert!((top_left[0] - italic.x - top * ITALIC_SHEAR).abs() < 1e-6);
+        assert!((top_right[0] - top_left[0] - italic.w).abs() < 1e-6);
+        // Descending parts lean back the other way
+        assert!((bottom_left[0] - italic.x - bottom * ITALIC_SHEAR).abs() < 1e-6);
+        assert!((bottom_right[0] - bottom_left[0] - italic.w).abs() < 1e-6);
+        assert_eq!(top_left[1], italic.y + italic.h);
+    }
+
+    #[test]
+    fn test_sgr_colors() {
+        let colors: Vec<[f32; 3]> = SgrChars::new("a\x1b[32mb\x1b[95;4mc\x1b[39md")
+            .map(|(_, attrs)| attrs.color)
+            .collect();
+        assert_eq!(
+            colors,
+            [
+                DEFAULT_TEXT_COLOR,
+                ANSI_COLORS[2],
+                ANSI_COLORS[13],
+                DEFAULT_TEXT_COLOR
+            ]
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::{io::Read, path::Path};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IconError {
+    #[error("failed to read icon file")]
+    Read(#[source] std::io::Error),
+    #[error("failed to decode icon png")]
+    Decode(#[from] png::DecodingError),
+}
+
+pub fn load(path: &Path) -> Result<glfw::PixelImage, IconError> {
+    let f = std::fs::File::open(path).map_err(IconError::Read)?;
+    from_png(std::io::BufReader::new(f))
+}
+
+/// GLFW wants 8 bit RGBA whatever the png holds
+pub fn from_png<R: Read>(r: R) -> Result<glfw::PixelImage, IconError> {
+    let mut decoder = png::Decoder::new(r);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+
+    let mut img_data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut img_data)?;
+    img_data.truncate(info.buffer_size());
+
+    let rgba: Vec<[u8; 4]> = match info.color_type {
+        png::ColorType::Rgba => img_data
+            .chunks_exact(4)
+            .map(|px| [px[0], px[1], px[2], px[3]])
+            .collect(),
+        png::ColorType::Rgb => img_data
+            .chunks_exact(3)
+            .map(|px| [px[0], px[1], px[2], 255])
+            .collect(),
+        png::ColorType::Grayscale => img_data.iter().map(|v| [*v, *v, *v, 255]).collect(),
+        png::ColorType::GrayscaleAlpha => img_data
+            .chunks_exact(2)
+            .map(|px| [px[0], px[0], px[0], px[1]])
+            .collect(),
+        // normalize_to_color8 expands palettes
+        png::ColorType::Indexed => unreachable!(),
+    };
+
+    Ok(glfw::PixelImage {
+        width: info.width,
+        height: info.height,
+        // Handed to GLFW as bytes, so keep them in memory order
+        pixels: rgba.into_iter().map(u32::from_ne_bytes).collect(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_icon_from_png() {
+        let icon = from_png(&include_bytes!("../suz_texture.png")[..]).unwrap();
+        assert_eq!((icon.width, icon.height), (256, 256));
+        assert_eq!(icon.pixels.len(), 256 * 256);
+    }
+}
@@ -0,0 +1,736 @@
+//! Baseline JPEG decoding, enough for photos and textures saved by most tools. Progressive and
+//! arithmetic coded files are rejected
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum JpegError {
+    #[error("not a jpeg")]
+    NotJpeg,
+    #[error("unexpected end of data")]
+    UnexpectedEof,
+    #[error("unsupported jpeg: {0}")]
+    Unsupported(&'static str),
+    #[error("invalid {0}")]
+    Invalid(&'static str),
+}
+
+/// Largest image decoded, 8192x8192. Frame headers give up to 65535x65535, which would take
+/// gigabytes to decode into
+const MAX_PIXELS: usize = 1 << 26;
+
+// Position in the 8x8 block of each coefficient in the order they are stored
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// Decoded pixels, 3 bytes per pixel with rows top to bottom
+pub struct JpegImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
+}
+
+pub fn is_jpeg(data: &[u8]) -> bool {
+    data.starts_with(&[0xff, 0xd8, 0xff])
+}
+
+#[derive(Default, Clone)]
+struct HuffmanTable {
+    // Number of codes of each bit length 1 to 16
+    counts: [u16; 17],
+    values: Vec<u8>,
+}
+
+impl HuffmanTable {
+    fn decode(&self, reader: &mut BitReader) -> Result<u8, JpegError> {
+        // Canonical codes, each length's codes follow on from the previous length's
+        let mut code = 0i32;
+        let mut first = 0i32;
+        let mut index = 0i32;
+        for len in 1..=16 {
+            code |= reader.bit()? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return self
+                    .values
+                    .get((index + code - first) as usize)
+                    .copied()
+                    .ok_or(JpegError::Invalid("huffman code"));
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(JpegError::Invalid("huffman code"))
+    }
+}
+
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant_table: usize,
+    // Samples padded out to whole MCUs
+    stride: usize,
+    samples: Vec<u8>,
+    dc_pred: i32,
+}
+
+struct Frame {
+    width: usize,
+    height: usize,
+    components: Vec<Component>,
+    max_h: usize,
+    max_v: usize,
+    mcus_x: usize,
+    mcus_y: usize,
+}
+
+/// Reads entropy coded data, undoing 0xff00 byte stuffing. Stops at the next marker and feeds
+/// zeros from then on so a short scan decodes as trailing zero bits like libjpeg does
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bits: u32,
+    num_bits: u32,
+}
+
+impl BitReader<'_> {
+    fn bit(&mut self) -> Result<u32, JpegError> {
+        if self.num_bits == 0 {
+            self.bits = self.next_byte()? as u32;
+            self.num_bits = 8;
+        }
+        self.num_bits -= 1;
+        Ok((self.bits >> self.num_bits) & 1)
+    }
+
+    fn next_byte(&mut self) -> Result<u8, JpegError> {
+        let byte = *self.data.get(self.pos).ok_or(JpegError::UnexpectedEof)?;
+        if byte != 0xff {
+            self.pos += 1;
+            return Ok(byte);
+        }
+
+        match self.data.get(self.pos + 1) {
+            Some(0) => {
+                self.pos += 2;
+                Ok(0xff)
+            }
+            Some(_) => Ok(0),
+            None => Err(JpegError::UnexpectedEof),
+        }
+    }
+
+    fn receive(&mut self, len: u8) -> Result<i32, JpegError> {
+        let mut v = 0;
+        for _ in 0..len {
+            v = (v << 1) | self.bit()? as i32;
+        }
+        Ok(v)
+    }
+
+    /// Reads a len bit magnitude category value, where the top half of the range is positive
+    fn receive_extend(&mut self, len: u8) -> Result<i32, JpegError> {
+        if len == 0 {
+            return Ok(0);
+        }
+        let v = self.receive(len)?;
+        if v < 1 << (len - 1) {
+            Ok(v - (1 << len) + 1)
+        } else {
+            Ok(v)
+        }
+    }
+
+    /// Drops the partial byte and steps over the RSTn marker
+    fn restart(&mut self) -> Result<(), JpegError> {
+        self.num_bits = 0;
+        match self.data.get(self.pos..self.pos + 2) {
+            Some([0xff, 0xd0..=0xd7]) => {
+                self.pos += 2;
+                Ok(())
+            }
+            _ => Err(JpegError::Invalid("restart marker")),
+        }
+    }
+
+    /// Skips any leftover bits and padding to the marker after the scan
+    fn skip_to_marker(&mut self) -> usize {
+        while let Some(&[a, b]) = self.data.get(self.pos..self.pos + 2) {
+            let is_marker = a == 0xff && !matches!(b, 0 | 0xd0..=0xd7);
+            if is_marker {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.pos
+    }
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    quant_tables: [[u16; 64]; 4],
+    dc_tables: [HuffmanTable; 4],
+    ac_tables: [HuffmanTable; 4],
+    restart_interval: usize,
+    frame: Option<Frame>,
+    // Adobe's APP14 can say the three channels are RGB rather than YCbCr
+    adobe_transform: Option<u8>,
+    idct_table: [[f32; 8]; 8],
+}
+
+pub fn decode(data: &[u8]) -> Result<JpegImage, JpegError> {
+    if !is_jpeg(data) {
+        return Err(JpegError::NotJpeg);
+    }
+
+    let mut decoder = Decoder {
+        data,
+        pos: 2,
+        quant_tables: [[0; 64]; 4],
+        dc_tables: Default::default(),
+        ac_tables: Default::default(),
+        restart_interval: 0,
+        frame: None,
+        adobe_transform: None,
+        idct_table: idct_table(),
+    };
+    decoder.decode()
+}
+
+impl Decoder<'_> {
+    fn u8(&mut self) -> Result<u8, JpegError> {
+        let v = *self.data.get(self.pos).ok_or(JpegError::UnexpectedEof)?;
+        self.pos += 1;
+        Ok(v)
+    }
+
+    fn u16(&mut self) -> Result<u16, JpegError> {
+        Ok(((self.u8()? as u16) << 8) | self.u8()? as u16)
+    }
+
+    fn segment(&mut self) -> Result<&[u8], JpegError> {
+        let len = self.u16()? as usize;
+        if len < 2 {
+            return Err(JpegError::Invalid("segment length"));
+        }
+        let start = self.pos;
+        let end = start + len - 2;
+        if end > self.data.len() {
+            return Err(JpegError::UnexpectedEof);
+        }
+        self.pos = end;
+        Ok(&self.data[start..end])
+    }
+
+    fn decode(&mut self) -> Result<JpegImage, JpegError> {
+        loop {
+            if self.u8()? != 0xff {
+                return Err(JpegError::Invalid("marker"));
+            }
+            let marker = self.u8()?;
+            match marker {
+                // Fill bytes before a marker
+                0xff => self.pos -= 1,
+                0xd9 => break,
+                0xc0 | 0xc1 => self.read_frame()?,
+                0xc2 | 0xc6 | 0xca | 0xce => return Err(JpegError::Unsupported("progressive")),
+                0xc3 | 0xc7 | 0xcb | 0xcf => return Err(JpegError::Unsupported("lossless")),
+                0xc9 | 0xcd => return Err(JpegError::Unsupported("arithmetic coding")),
+                0xc4 => self.read_huffman_tables()?,
+                0xdb => self.read_quant_tables()?,
+                0xdd => {
+                    let segment = self.segment()?;
+                    let [a, b] = segment else {
+                        return Err(JpegError::Invalid("restart interval"));
+                    };
+                    self.restart_interval = ((*a as usize) << 8) | *b as usize;
+                }
+                0xda => self.read_scan()?,
+                0xee => {
+                    let segment = self.segment()?;
+                    if segment.len() >= 12 && segment.starts_with(b"Adobe") {
+                        self.adobe_transform = Some(segment[11]);
+                    }
+                }
+                _ => {
+                    self.segment()?;
+                }
+            }
+        }
+
+        self.to_rgb()
+    }
+
+    fn read_quant_tables(&mut self) -> Result<(), JpegError> {
+        let segment = self.segment()?;
+        let mut tables = Vec::new();
+        let mut i = 0;
+        while i < segment.len() {
+            let precision = segment[i] >> 4;
+            let id = (segment[i] & 0xf) as usize;
+            i += 1;
+            if id > 3 {
+                return Err(JpegError::Invalid("quantization table"));
+            }
+
+            let mut table = [0u16; 64];
+            for v in &mut table {
+                *v = match precision {
+                    0 => *segment.get(i).ok_or(JpegError::UnexpectedEof)? as u16,
+                    _ => {
+                        let bytes = segment.get(i..i + 2).ok_or(JpegError::UnexpectedEof)?;
+                        i += 1;
+                        ((bytes[0] as u16) << 8) | bytes[1] as u16
+                    }
+                };
+                i += 1;
+            }
+            tables.push((id, table));
+        }
+
+        for (id, table) in tables {
+            self.quant_tables[id] = table;
+        }
+        Ok(())
+    }
+
+    fn read_huffman_tables(&mut self) -> Result<(), JpegError> {
+        let segment = self.segment()?;
+        let mut tables = Vec::new();
+        let mut i = 0;
+        while i < segment.len() {
+            let class = segment[i] >> 4;
+            let id = (segment[i] & 0xf) as usize;
+            if class > 1 || id > 3 {
+                return Err(JpegError::Invalid("huffman table"));
+            }
+
+            let mut table = HuffmanTable::default();
+            let counts = segment.get(i + 1..i + 17).ok_or(JpegError::UnexpectedEof)?;
+            for (out, count) in table.counts[1..].iter_mut().zip(counts) {
+                *out = *count as u16;
+            }
+            i += 17;
+
+            let total: usize = table.counts.iter().map(|v| *v as usize).sum();
+            table.values = segment
+                .get(i..i + total)
+                .ok_or(JpegError::UnexpectedEof)?
+                .to_vec();
+            i += total;
+            tables.push((class, id, table));
+        }
+
+        for (class, id, table) in tables {
+            match class {
+                0 => self.dc_tables[id] = table,
+                _ => self.ac_tables[id] = table,
+            }
+        }
+        Ok(())
+    }
+
+    fn read_frame(&mut self) -> Result<(), JpegError> {
+        let segment = self.segment()?;
+        let [precision, h1, h0, w1, w0, num_components, rest @ ..] = segment else {
+            return Err(JpegError::Invalid("frame header"));
+        };
+        if *precision != 8 {
+            return Err(JpegError::Unsupported("sample precision other than 8 bits"));
+        }
+        let height = ((*h1 as usize) << 8) | *h0 as usize;
+        let width = ((*w1 as usize) << 8) | *w0 as usize;
+        if width == 0 || height == 0 {
+            return Err(JpegError::Unsupported("height defined by the scan"));
+        }
+        if width * height > MAX_PIXELS {
+            return Err(JpegError::Unsupported("image too large"));
+        }
+        if !matches!(num_components, 1 | 3) {
+            return Err(JpegError::Unsupported("component count other than 1 or 3"));
+        }
+        if rest.len() != *num_components as usize * 3 {
+            return Err(JpegError::Invalid("frame header"));
+        }
+
+        let mut components: Vec<Component> = rest
+            .chunks_exact(3)
+            .map(|c| Component {
+                id: c[0],
+                h: (c[1] >> 4) as usize,
+                v: (c[1] & 0xf) as usize,
+                quant_table: c[2] as usize,
+                stride: 0,
+                samples: Vec::new(),
+                dc_pred: 0,
+            })
+            .collect();
+        if components
+            .iter()
+            .any(|c| !(1..=4).contains(&c.h) || !(1..=4).contains(&c.v) || c.quant_table > 3)
+        {
+            return Err(JpegError::Invalid("frame header"));
+        }
+
+        let max_h = components.iter().map(|c| c.h).max().unwrap();
+        let max_v = components.iter().map(|c| c.v).max().unwrap();
+        let mcus_x = width.div_ceil(8 * max_h);
+        let mcus_y = height.div_ceil(8 * max_v);
+        for c in &mut components {
+            c.stride = mcus_x * c.h * 8;
+            c.samples = vec![0; c.stride * mcus_y * c.v * 8];
+        }
+
+        self.frame = Some(Frame {
+            width,
+            height,
+            components,
+            max_h,
+            max_v,
+            mcus_x,
+            mcus_y,
+        });
+        Ok(())
+    }
+
+    fn read_scan(&mut self) -> Result<(), JpegError> {
+        let segment = self.segment()?.to_vec();
+        let mut frame = self
+            .frame
+            .take()
+            .ok_or(JpegError::Invalid("scan before frame header"))?;
+
+        let num_components = *segment.first().ok_or(JpegError::UnexpectedEof)? as usize;
+        if num_components == 0 || segment.len() != 1 + num_components * 2 + 3 {
+            return Err(JpegError::Invalid("scan header"));
+        }
+
+        // (component index, dc table, ac table)
+        let mut scan = Vec::new();
+        for selector in segment[1..1 + num_components * 2].chunks_exact(2) {
+            let index = frame
+                .components
+                .iter()
+                .position(|c| c.id == selector[0])
+                .ok_or(JpegError::Invalid("scan component"))?;
+            let dc = (selector[1] >> 4) as usize;
+            let ac = (selector[1] & 0xf) as usize;
+            if dc > 3 || ac > 3 {
+                return Err(JpegError::Invalid("scan header"));
+            }
+            scan.push((index, dc, ac));
+        }
+        for c in &mut frame.components {
+            c.dc_pred = 0;
+        }
+
+        let mut reader = BitReader {
+            data: self.data,
+            pos: self.pos,
+            bits: 0,
+            num_bits: 0,
+        };
+
+        // A single component scan isn't interleaved, its blocks are in plain raster order and
+        // only cover the component's own size
+        let (units_x, units_y) = if let [(index, _, _)] = scan[..] {
+            let c = &frame.components[index];
+            (
+                (frame.width * c.h).div_ceil(frame.max_h * 8),
+                (frame.height * c.v).div_ceil(frame.max_v * 8),
+            )
+        } else {
+            (frame.mcus_x, frame.mcus_y)
+        };
+
+        let mut coefs = [0i32; 64];
+        for unit in 0..units_x * units_y {
+            if self.restart_interval > 0 && unit > 0 && unit % self.restart_interval == 0 {
+                reader.restart()?;
+                for c in &mut frame.components {
+                    c.dc_pred = 0;
+                }
+            }
+
+            let (unit_x, unit_y) = (unit % units_x, unit / units_x);
+            for &(index, dc, ac) in &scan {
+                let c = &frame.components[index];
+                let blocks = if scan.len() == 1 {
+                    vec![(unit_x, unit_y)]
+                } else {
+                    (0..c.v)
+                        .flat_map(|y| (0..c.h).map(move |x| (unit_x * c.h + x, unit_y * c.v + y)))
+                        .collect()
+                };
+
+                for (block_x, block_y) in blocks {
+                    let c = &mut frame.components[index];
+                    decode_block(
+                        &mut reader,
+                        &self.dc_tables[dc],
+                        &self.ac_tables[ac],
+                        &self.quant_tables[c.quant_table],
+                        &mut c.dc_pred,
+                        &mut coefs,
+                    )?;
+
+                    let offset = block_y * 8 * c.stride + block_x * 8;
+                    idct(&self.idct_table, &coefs, &mut c.samples[offset..], c.stride);
+                }
+            }
+        }
+
+        self.pos = reader.skip_to_marker();
+        self.frame = Some(frame);
+        Ok(())
+    }
+
+    fn to_rgb(&self) -> Result<JpegImage, JpegError> {
+        let frame = self
+            .frame
+            .as_ref()
+            .ok_or(JpegError::Invalid("missing frame header"))?;
+
+        let sample = |c: &Component, x: usize, y: usize| {
+            // Nearest neighbour upsampling of subsampled channels
+            let cx = x * c.h / frame.max_h;
+            let cy = y * c.v / frame.max_v;
+            c.samples[cy * c.stride + cx] as f32
+        };
+
+        let ycbcr = self.adobe_transform != Some(0);
+        let mut rgb = Vec::with_capacity(frame.width * frame.height * 3);
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                match &frame.components[..] {
+                    [gray] => {
+                        let v = sample(gray, x, y) as u8;
+                        rgb.extend([v; 3]);
+                    }
+                    [a, b, c] if ycbcr => {
+                        let (luma, cb, cr) = (sample(a, x, y), sample(b, x, y), sample(c, x, y));
+                        let cb = cb - 128.0;
+                        let cr = cr - 128.0;
+                        rgb.extend([
+                            clamp_u8(luma + 1.402 * cr),
+                            clamp_u8(luma - 0.344136 * cb - 0.714136 * cr),
+                            clamp_u8(luma + 1.772 * cb),
+                        ]);
+                    }
+                    [a, b, c] => {
+                        rgb.extend([a, b, c].map(|comp| sample(comp, x, y) as u8));
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        Ok(JpegImage {
+            width: frame.width as u32,
+            height: frame.height as u32,
+            rgb,
+        })
+    }
+}
+
+fn clamp_u8(v: f32) -> u8 {
+    v.round().clamp(0.0, 255.0) as u8
+}
+
+fn decode_block(
+    reader: &mut BitReader,
+    dc_table: &HuffmanTable,
+    ac_table: &HuffmanTable,
+    quant_table: &[u16; 64],
+    dc_pred: &mut i32,
+    coefs: &mut [i32; 64],
+) -> Result<(), JpegError> {
+    *coefs = [0; 64];
+
+    let len = dc_table.decode(reader)?;
+    if len > 11 {
+        return Err(JpegError::Invalid("dc coefficient"));
+    }
+    *dc_pred = dc_pred
+        .checked_add(reader.receive_extend(len)?)
+        .ok_or(JpegError::Invalid("dc coefficient"))?;
+    coefs[0] = dc_pred
+        .checked_mul(quant_table[0] as i32)
+        .ok_or(JpegError::Invalid("dc coefficient"))?;
+
+    let mut k = 1;
+    while k < 64 {
+        let rs = ac_table.decode(reader)?;
+        let run = (rs >> 4) as usize;
+        let len = rs & 0xf;
+        if len == 0 {
+            // End of block, or a run of 16 zeros
+            if run != 15 {
+                break;
+            }
+            k += 16;
+            continue;
+        }
+
+        k += run;
+        if k > 63 {
+            return Err(JpegError::Invalid("ac coefficient run"));
+        }
+        coefs[ZIGZAG[k]] = reader.receive_extend(len)? * quant_table[k] as i32;
+        k += 1;
+    }
+
+    Ok(())
+}
+
+/// cos((2x + 1)uπ/16) with the DC scale folded in, indexed [x][u]
+fn idct_table() -> [[f32; 8]; 8] {
+    let mut table = [[0.0; 8]; 8];
+    for (x, row) in table.iter_mut().enumerate() {
+        for (u, v) in row.iter_mut().enumerate() {
+            let scale = if u == 0 {
+                std::f32::consts::FRAC_1_SQRT_2
+            } else {
+                1.0
+            };
+            *v = scale * ((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI / 16.0).cos();
+        }
+    }
+    table
+}
+
+/// Writes the level shifted 8x8 block into out, rows stride apart
+fn idct(table: &[[f32; 8]; 8], coefs: &[i32; 64], out: &mut [u8], stride: usize) {
+    // Rows first, then columns
+    let mut tmp = [0.0f32; 64];
+    for v in 0..8 {
+        for x in 0..8 {
+            let mut sum = 0.0;
+            for u in 0..8 {
+                sum += table[x][u] * coefs[v * 8 + u] as f32;
+            }
+            tmp[v * 8 + x] = sum;
+        }
+    }
+
+    for y in 0..8 {
+        for x in 0..8 {
+            let mut sum = 0.0;
+            for v in 0..8 {
+                sum += table[y][v] * tmp[v * 8 + x];
+            }
+            out[y * stride + x] = clamp_u8(sum / 4.0 + 128.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode_test_jpg() {
+        let image = decode(include_bytes!("../test.jpg")).unwrap();
+        assert_eq!(image.width, 1280);
+        assert_eq!(image.height, 974);
+        assert_eq!(image.rgb.len(), 1280 * 974 * 3);
+
+        let pixel = |x: usize, y: usize| {
+            let i = (y * 1280 + x) * 3;
+            [image.rgb[i], image.rgb[i + 1], image.rgb[i + 2]]
+        };
+        // Color bars and the checks underneath
+        for ((x, y), expected) in [
+            ((240, 400), [255, 255, 0]),
+            ((80, 530), [255, 0, 0]),
+            ((80, 700), [0, 255, 0]),
+            ((80, 870), [0, 0, 255]),
+            ((1200, 870), [255, 255, 255]),
+        ] {
+            let actual = pixel(x, y);
+            for (a, e) in actual.iter().zip(expected) {
+                assert!(
+                    a.abs_diff(e) < 8,
+                    "{actual:?} at {x},{y}, expected {expected:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(decode(b"\x89PNG").err(), Some(JpegError::NotJpeg));
+        assert_eq!(
+            decode(&[0xff, 0xd8, 0xff, 0xc2, 0x00]).err(),
+            Some(JpegError::Unsupported("progressive"))
+        );
+        assert_eq!(
+            decode(&[0xff, 0xd8, 0xff, 0xdb, 0x00]).err(),
+            Some(JpegError::UnexpectedEof)
+        );
+    }
+
+    /// Baseline frame header for a width x height grayscale image using quant table 0
+    fn gray_frame(width: u16, height: u16) -> Vec<u8> {
+        let mut data = vec![0xff, 0xc0, 0x00, 0x0b, 0x08];
+        data.extend(height.to_be_bytes());
+        data.extend(width.to_be_bytes());
+        data.extend([0x01, 0x01, 0x11, 0x00]);
+        data
+    }
+
+    #[test]
+    fn test_too_large() {
+        let mut data = vec![0xff, 0xd8];
+        data.extend(gray_frame(u16::MAX, u16::MAX));
+        assert_eq!(
+            decode(&data).err(),
+            Some(JpegError::Unsupported("image too large"))
+        );
+    }
+
+    #[test]
+    fn test_dc_overflow() {
+        let mut data = vec![0xff, 0xd8];
+        // 16 bit quant table 0, every entry 65535
+        data.extend([0xff, 0xdb, 0x00, 0x83, 0x10]);
+        data.extend([0xff; 128]);
+        // DC table 0 only codes 11 bit differences and AC table 0 only end of block, both as
+        // the single one bit code 0
+        for class in [0x00, 0x10] {
+            data.extend([0xff, 0xc4, 0x00, 0x14, class, 0x01]);
+            data.extend([0x00; 15]);
+            data.push(if class == 0 { 11 } else { 0 });
+        }
+        data.extend(gray_frame(8 * 32, 8));
+        data.extend([0xff, 0xda, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3f, 0x00]);
+
+        // Every block adds 2047 to the DC prediction, 17 blocks in it no longer fits an i32 once
+        // quantized
+        let bits: Vec<bool> = (0..32)
+            .flat_map(|_| [false].into_iter().chain([true; 11]).chain([false]))
+            .collect();
+        for byte in bits.chunks(8) {
+            let byte = byte
+                .iter()
+                .enumerate()
+                .fold(0u8, |acc, (i, bit)| acc | ((*bit as u8) << (7 - i)));
+            data.push(byte);
+            if byte == 0xff {
+                data.push(0x00);
+            }
+        }
+        data.extend([0xff, 0xd9]);
+
+        assert_eq!(
+            decode(&data).err(),
+            Some(JpegError::Invalid("dc coefficient"))
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum JsonParseError {
+    #[error("unexpected end of input")]
+    UnexpectedEof,
+    #[error("unexpected character {1:?} at byte {0}")]
+    UnexpectedChar(usize, char),
+    #[error("invalid number at byte {0}")]
+    InvalidNumber(usize),
+    #[error("invalid escape at byte {0}")]
+    InvalidEscape(usize),
+    #[error("trailing data at byte {0}")]
+    TrailingData(usize),
+}
+
+/// Just enough JSON for config files. Object keys keep their file order
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            JsonValue::Number(v) => Some(*v as f32),
+            _ => None,
+        }
+    }
+
+    /// Whole numbers only, 1.5 is not an integer
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            JsonValue::Number(v)
+                if v.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(v) =>
+            {
+                Some(*v as i32)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// [x, y, z] style arrays of numbers
+    pub fn as_f32_array<const N: usize>(&self) -> Option<[f32; N]> {
+        let values = self.as_array()?;
+        if values.len() != N {
+            return None;
+        }
+
+        let mut ret = [0.0f32; N];
+        for (out, v) in ret.iter_mut().zip(values) {
+            *out = v.as_f32()?;
+        }
+        Some(ret)
+    }
+}
+
+pub fn parse(s: &str) -> Result<JsonValue, JsonParseError> {
+    let mut parser = Parser { s, pos: 0 };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.pos != s.len() {
+        return Err(JsonParseError::TrailingData(parser.pos));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Result<char, JsonParseError> {
+        let c = self.peek().ok_or(JsonParseError::UnexpectedEof)?;
+        self.pos += c.len_utf8();
+        Ok(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonParseError> {
+        let pos = self.pos;
+        match self.next()? {
+            c if c == expected => Ok(()),
+            c => Err(JsonParseError::UnexpectedChar(pos, c)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn parse_literal(
+        &mut self,
+        literal: &str,
+        value: JsonValue,
+    ) -> Result<JsonValue, JsonParseError> {
+        for expected in literal.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, JsonParseError> {
+        self.skip_whitespace();
+        match self.peek().ok_or(JsonParseError::UnexpectedEof)? {
+            '{' => self.parse_object(),
+            '[' => self.parse_array(),
+            '"' => Ok(JsonValue::String(self.parse_string()?)),
+            't' => self.parse_literal("true", JsonValue::Bool(true)),
+            'f' => self.parse_literal("false", JsonValue::Bool(false)),
+            'n' => self.parse_literal("null", JsonValue::Null),
+            '-' | '0'..='9' => self.parse_number(),
+            c => Err(JsonParseError::UnexpectedChar(self.pos, c)),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, JsonParseError> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            fields.push((key, value));
+
+            self.skip_whitespace();
+            let pos = self.pos;
+            match self.next()? {
+                ',' => continue,
+                '}' => break,
+                c => return Err(JsonParseError::UnexpectedChar(pos, c)),
+            }
+        }
+
+        Ok(JsonValue::Object(fields))
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, JsonParseError> {
+        self.expect('[')?;
+        let mut values = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(values));
+        }
+
+        loop {
+            values.push(self.parse_value()?);
+
+            self.skip_whitespace();
+            let pos = self.pos;
+            match self.next()? {
+                ',' => continue,
+                ']' => break,
+                c => return Err(JsonParseError::UnexpectedChar(pos, c)),
+            }
+        }
+
+        Ok(JsonValue::Array(values))
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonParseError> {
+        self.expect('"')?;
+        let mut ret = String::new();
+
+        loop {
+            let pos = self.pos;
+            match self.next()? {
+                '"' => break,
+                '\\' => {
+                    let c = match self.next()? {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => self.parse_unicode_escape(pos)?,
+                        _ => return Err(JsonParseError::InvalidEscape(pos)),
+                    };
+                    ret.push(c);
+                }
+                c => ret.push(c),
+            }
+        }
+
+        Ok(ret)
+    }
+
+    fn parse_hex4(&mut self, escape_pos: usize) -> Result<u32, JsonParseError> {
+        let end = self.pos + 4;
+        let digits = self
+            .s
+            .get(self.pos..end)
+            .ok_or(JsonParseError::InvalidEscape(escape_pos))?;
+        let v = u32::from_str_radix(digits, 16)
+            .map_err(|_| JsonParseError::InvalidEscape(escape_pos))?;
+        self.pos = end;
+        Ok(v)
+    }
+
+    fn parse_unicode_escape(&mut self, escape_pos: usize) -> Result<char, JsonParseError> {
+        let mut code = self.parse_hex4(escape_pos)?;
+
+        // Characters outside the BMP come as a surrogate pair of escapes
+        if (0xd800..0xdc00).contains(&code) {
+            self.expect('\\')?;
+            self.expect('u')?;
+            let low = self.parse_hex4(escape_pos)?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(JsonParseError::InvalidEscape(escape_pos));
+            }
+            code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+        }
+
+        char::from_u32(code).ok_or(JsonParseError::InvalidEscape(escape_pos))
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, JsonParseError> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9') {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        self.s[start..self.pos]
+            .parse()
+            .map(JsonValue::Number)
+            .map_err(|_| JsonParseError::InvalidNumber(start))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested() {
+        let v = parse(r#" { "a": [1, -2.5e1, true, null], "b": {"c": "d\n\u00e9\ud83d\ude00"} } "#)
+            .unwrap();
+
+        assert_eq!(
+            v.get("a").unwrap(),
+            &JsonValue::Array(vec![
+                JsonValue::Number(1.0),
+                JsonValue::Number(-25.0),
+                JsonValue::Bool(true),
+                JsonValue::Null,
+            ])
+        );
+        assert_eq!(
+            v.get("b")
+                .and_then(|b| b.get("c"))
+                .and_then(JsonValue::as_str),
+            Some("d\né😀")
+        );
+        assert_eq!(v.get("missing"), None);
+    }
+
+    #[test]
+    fn test_f32_array() {
+        let v = parse("[1, 2, 3]").unwrap();
+        assert_eq!(v.as_f32_array::<3>(), Some([1.0, 2.0, 3.0]));
+        assert_eq!(v.as_f32_array::<2>(), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("[1, 2"), Err(JsonParseError::UnexpectedEof));
+        assert_eq!(parse("[1 2]"), Err(JsonParseError::UnexpectedChar(3, '2')));
+        assert_eq!(parse("{} x"), Err(JsonParseError::TrailingData(3)));
+        assert_eq!(parse("-"), Err(JsonParseError::InvalidNumber(0)));
+        assert_eq!(parse(r#""\q""#), Err(JsonParseError::InvalidEscape(1)));
+    }
+}
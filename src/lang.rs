@@ -0,0 +1,82 @@
+/// Fixed text of the default layout
+#[derive(Debug, PartialEq)]
+pub struct Labels {
+    pub todays_topic: &'static str,
+    pub stream_starting_at: &'static str,
+    pub current_time: &'static str,
+    pub till_stream_starts: &'static str,
+    pub till_back: &'static str,
+    pub live: &'static str,
+}
+
+pub const ENGLISH: Labels = Labels {
+    todays_topic: "Today's topic:",
+    stream_starting_at: "Stream starting at",
+    current_time: "Current time:",
+    till_stream_starts: "'till stream starts",
+    till_back: "'till we're back",
+    live: "We're live!",
+};
+
+const LANGUAGES: &[(&str, Labels)] = &[
+    ("en", ENGLISH),
+    (
+        "de",
+        Labels {
+            todays_topic: "Heutiges Thema:",
+            stream_starting_at: "Stream beginnt um",
+            current_time: "Aktuelle Zeit:",
+            till_stream_starts: "bis der Stream beginnt",
+            till_back: "bis wir zurück sind",
+            live: "Wir sind live!",
+        },
+    ),
+    (
+        "es",
+        Labels {
+            todays_topic: "Tema de hoy:",
+            stream_starting_at: "El stream empieza a las",
+            current_time: "Hora actual:",
+            till_stream_starts: "para que empiece el stream",
+            till_back: "para que volvamos",
+            live: "¡Estamos en directo!",
+        },
+    ),
+    (
+        "fr",
+        Labels {
+            todays_topic: "Sujet du jour :",
+            stream_starting_at: "Le stream commence à",
+            current_time: "Heure actuelle :",
+            till_stream_starts: "avant le début du stream",
+            till_back: "avant notre retour",
+            live: "Nous sommes en direct !",
+        },
+    ),
+];
+
+pub fn codes() -> impl Iterator<Item = &'static str> {
+    LANGUAGES.iter().map(|(code, _)| *code)
+}
+
+/// Accepts locale style codes like de_DE.UTF-8 by looking at the language part only
+pub fn labels(code: &str) -> Option<&'static Labels> {
+    let code = code.split(['_', '-', '.']).next().unwrap_or(code);
+    LANGUAGES
+        .iter()
+        .find(|(lang, _)| lang.eq_ignore_ascii_case(code))
+        .map(|(_, labels)| labels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_labels_lookup() {
+        assert_eq!(labels("en"), Some(&ENGLISH));
+        assert_eq!(labels("de_DE.UTF-8"), labels("de"));
+        assert_eq!(labels("FR").unwrap().current_time, "Heure actuelle :");
+        assert_eq!(labels("xx"), None);
+    }
+}
@@ -0,0 +1,108 @@
+use glow::{HasContext, NativeBuffer, NativeProgram, NativeVertexArray};
+
+use crate::{
+    gl_util,
+    mat::{Transform, Vec3},
+    GlError,
+};
+
+type UniformLocation = <glow::Context as HasContext>::UniformLocation;
+
+/// Draws world space line segments in a flat color, for debug overlays. Depth tested like the
+/// meshes, so lines behind geometry are hidden
+pub struct LineRenderer<'a> {
+    program: NativeProgram,
+    vao: NativeVertexArray,
+    vbo: NativeBuffer,
+    view_loc: Option<UniformLocation>,
+    color_loc: Option<UniformLocation>,
+    gl: &'a glow::Context,
+}
+
+impl<'a> LineRenderer<'a> {
+    pub fn new(gl: &'a glow::Context) -> Result<LineRenderer<'a>, GlError> {
+        unsafe {
+            let program = gl_util::compile_program(
+                gl,
+                include_str!("glsl/line_vertex.glsl"),
+                include_str!("glsl/color_fragment.glsl"),
+            )?;
+
+            let vao = gl.create_vertex_array().map_err(GlError)?;
+            gl.bind_vertex_array(Some(vao));
+
+            let vbo = gl.create_buffer().map_err(GlError)?;
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+
+            let pos_loc = gl
+                .get_attrib_location(program, "in_pos")
+                .expect("Invalid line shader");
+            const STRIDE: i32 = (3 * std::mem::size_of::<f32>()) as i32;
+            gl.vertex_attrib_pointer_f32(pos_loc, 3, glow::FLOAT, false, STRIDE, 0);
+            gl.enable_vertex_attrib_array(pos_loc);
+
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.bind_vertex_array(None);
+
+            let view_loc = gl.get_uniform_location(program, "view");
+            let color_loc = gl.get_uniform_location(program, "color");
+
+            Ok(LineRenderer {
+                program,
+                vao,
+                vbo,
+                view_loc,
+                color_loc,
+                gl,
+            })
+        }
+    }
+
+    pub fn render(&self, view: &Transform, lines: &[[Vec3; 2]], color: [f32; 4]) {
+        if lines.is_empty() {
+            return;
+        }
+
+        let verts: Vec<f32> = lines
+            .iter()
+            .flatten()
+            .flat_map(|p| [p.x(), p.y(), p.z()])
+            .collect();
+
+        let gl = self.gl;
+        unsafe {
+            gl.use_program(Some(self.program));
+            gl.bind_vertex_array(Some(self.vao));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+
+            let bytes = std::slice::from_raw_parts(
+                verts.as_ptr() as *const u8,
+                std::mem::size_of_val(verts.as_slice()),
+            );
+            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, bytes, glow::STREAM_DRAW);
+
+            gl.uniform_matrix_4_f32_slice(
+                self.view_loc.as_ref(),
+                true,
+                std::slice::from_raw_parts(view.arr[0].as_ptr(), 16),
+            );
+            gl.uniform_4_f32_slice(self.color_loc.as_ref(), &color);
+
+            gl.draw_arrays(glow::LINES, 0, (lines.len() * 2) as i32);
+
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.bind_vertex_array(None);
+            gl.use_program(None);
+        }
+    }
+}
+
+impl Drop for LineRenderer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_program(self.program);
+            self.gl.delete_buffer(self.vbo);
+            self.gl.delete_vertex_array(self.vao);
+        }
+    }
+}
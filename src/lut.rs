@@ -0,0 +1,149 @@
+//! Color lookup tables for grading the final image
+
+use std::{io::Read, path::Path};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LutError {
+    #[error("failed to read lut file")]
+    Read(#[source] std::io::Error),
+    #[error("failed to decode lut png")]
+    Decode(#[from] png::DecodingError),
+    #[error("{0}x{1} is neither a hald clut nor a strip lut")]
+    InvalidSize(u32, u32),
+}
+
+/// An RGB cube with size entries per channel, stored as a strip of blue slices size * size
+/// pixels wide and size pixels tall. Red runs along x within a slice and green down the rows
+pub struct Lut {
+    pub size: u32,
+    pub data: Vec<u8>,
+}
+
+impl Lut {
+    pub fn load(path: &Path) -> Result<Lut, LutError> {
+        let f = std::fs::File::open(path).map_err(LutError::Read)?;
+        Lut::from_png(std::io::BufReader::new(f))
+    }
+
+    /// Accepts Hald CLUTs (square, level^3 pixels a side) and strips already in our layout
+    pub fn from_png<R: Read>(r: R) -> Result<Lut, LutError> {
+        let mut decoder = png::Decoder::new(r);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+
+        let mut img_data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut img_data)?;
+        img_data.truncate(info.buffer_size());
+
+        let rgb = match info.color_type {
+            png::ColorType::Rgb => img_data,
+            png::ColorType::Rgba => img_data
+                .chunks_exact(4)
+                .flat_map(|px| [px[0], px[1], px[2]])
+                .collect(),
+            png::ColorType::Grayscale => img_data.iter().flat_map(|v| [*v; 3]).collect(),
+            png::ColorType::GrayscaleAlpha => {
+                img_data.chunks_exact(2).flat_map(|px| [px[0]; 3]).collect()
+            }
+            // normalize_to_color8 expands palettes
+            png::ColorType::Indexed => unreachable!(),
+        };
+
+        Lut::from_rgb(info.width, info.height, rgb)
+    }
+
+    fn from_rgb(width: u32, height: u32, data: Vec<u8>) -> Result<Lut, LutError> {
+        // Squared and cubed sizes are compared as u64, a png's dimensions can be anything a u32
+        // holds
+        if width as u64 == height as u64 * height as u64 {
+            return Ok(Lut { size: height, data });
+        }
+
+        let level = (width as f32).cbrt().round() as u32;
+        if width == height && (level as u64).pow(3) == width as u64 {
+            let size = level * level;
+            return Ok(Lut {
+                size,
+                data: hald_to_strip(size, &data),
+            });
+        }
+
+        Err(LutError::InvalidSize(width, height))
+    }
+}
+
+/// Hald CLUTs list every entry in order with red changing fastest, then green, then blue
+fn hald_to_strip(size: u32, data: &[u8]) -> Vec<u8> {
+    let size = size as usize;
+    let mut ret = vec![0; data.len()];
+    for (i, px) in data.chunks_exact(3).enumerate() {
+        let r = i % size;
+        let g = (i / size) % size;
+        let b = i / (size * size);
+        let out = (g * size * size + b * size + r) * 3;
+        ret[out..out + 3].copy_from_slice(px);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn identity_value(v: usize, size: usize) -> u8 {
+        (v * 255 / (size - 1)) as u8
+    }
+
+    #[test]
+    fn test_hald_to_strip() {
+        // Level 2 hald, 8x8 pixels holding a 4x4x4 identity cube
+        let size = 4;
+        let hald: Vec<u8> = (0..size * size * size)
+            .flat_map(|i| {
+                [
+                    identity_value(i % size, size),
+                    identity_value(i / size % size, size),
+                    identity_value(i / (size * size), size),
+                ]
+            })
+            .collect();
+
+        let lut = Lut::from_rgb(8, 8, hald).unwrap();
+        assert_eq!(lut.size, 4);
+
+        for g in 0..size {
+            for b in 0..size {
+                for r in 0..size {
+                    let i = (g * size * size + b * size + r) * 3;
+                    assert_eq!(
+                        &lut.data[i..i + 3],
+                        &[
+                            identity_value(r, size),
+                            identity_value(g, size),
+                            identity_value(b, size)
+                        ]
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lut_sizes() {
+        assert_eq!(Lut::from_rgb(16, 4, vec![0; 16 * 4 * 3]).unwrap().size, 4);
+        assert!(matches!(
+            Lut::from_rgb(10, 10, vec![0; 10 * 10 * 3]),
+            Err(LutError::InvalidSize(10, 10))
+        ));
+        assert!(matches!(
+            Lut::from_rgb(u32::MAX, 70000, Vec::new()),
+            Err(LutError::InvalidSize(u32::MAX, 70000))
+        ));
+        assert!(matches!(
+            Lut::from_rgb(u32::MAX, u32::MAX, Vec::new()),
+            Err(LutError::InvalidSize(u32::MAX, u32::MAX))
+        ));
+    }
+}
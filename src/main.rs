@@ -1,23 +1,39 @@
 #![allow(clippy::needless_range_loop)]
 
 use crate::{
-    animation::{Animation, AnimationReq},
+    animation::{Animation, AnimationReq, CaretMove},
+    audio::AudioCapture,
+    camera_path::CameraPath,
+    countdown::Countdown,
     cursor_renderer::CursorRenderer,
+    ease::Easing,
+    frustum::Frustum,
     glyph_cache::GlyphCache,
-    glyph_renderer::GlyphRenderer,
+    glyph_renderer::{
+        GlyphRenderer, TextAlign, TextBounds, TextDirection, TextOverflow, TextRegion, TextStyle,
+    },
+    line_renderer::LineRenderer,
     mat::Transform,
-    mesh_renderer::MeshRenderer,
+    mesh_renderer::{MeshRenderer, MAX_SHADOW_SAMPLES},
+    particle_renderer::ParticleRenderer,
+    postprocess::{PostprocessChain, PostprocessPass},
+    rng::Rng,
+    scene::{ObjectRole, Scene},
 };
 
 use glfw::{fail_on_errors, Context};
-use glow::{HasContext, NativeTexture};
+use glow::{HasContext, NativeFramebuffer, NativeTexture};
 
-use chrono::NaiveTime;
+use chrono::{NaiveTime, Timelike};
 
+use lang::Labels;
 use mat::Vec3;
 use mesh_renderer::{GpuMesh, UploadMeshError};
 use obj_parser::ObjParseError;
-use screen_tex_postprocess::ScreenTexPostprocessor;
+use screen_tex_postprocess::{
+    ScreenTexPostprocessor, DEFAULT_CURVATURE, DEFAULT_SCANLINE_FREQUENCY,
+    DEFAULT_SCANLINE_INTENSITY,
+};
 use thiserror::Error;
 
 use std::{
@@ -26,15 +42,38 @@ use std::{
 };
 
 mod animation;
+mod audio;
+mod camera_path;
+mod countdown;
 mod cursor_renderer;
 mod ease;
+mod frustum;
+#[cfg(all(test, feature = "gl-tests"))]
+mod gl_test_util;
 mod gl_util;
 mod glyph_cache;
 mod glyph_renderer;
+mod icon;
+mod jpeg;
+mod json;
+mod lang;
+mod line_renderer;
+mod lut;
 mod mat;
 mod mesh_renderer;
 mod obj_parser;
+mod particle_renderer;
+#[cfg(test)]
+mod plain_text_backend;
+mod postprocess;
+mod recorder;
+mod rng;
+mod scene;
 mod screen_tex_postprocess;
+#[cfg(test)]
+mod test_util;
+mod texture;
+mod timestep;
 
 #[derive(Error, Debug)]
 #[error("{0}")]
@@ -44,15 +83,283 @@ const WINDOW_WIDTH: u32 = 1920 / 2;
 const WINDOW_HEIGHT: u32 = 1080 / 2;
 const WINDOW_ASPECT: f32 = WINDOW_WIDTH as f32 / WINDOW_HEIGHT as f32;
 
+/// The part of the square text texture that lands on screen, the rest is cut off by the aspect
+fn visible_text_region() -> TextRegion {
+    TextRegion {
+        y_min: 0.5 - 0.5 / WINDOW_ASPECT,
+        y_max: 0.5 + 0.5 / WINDOW_ASPECT,
+        ..TextRegion::FULL
+    }
+}
+
+/// Pixel rows of a text texture size texels square that fill a width x height framebuffer without
+/// stretching, centered like visible_text_region. A window taller than it is wide gets them all
+fn visible_text_rows(size: i32, (width, height): (i32, i32)) -> (i32, i32) {
+    let half = (0.5 * height as f32 / width as f32).min(0.5);
+    let row = |y: f32| (y * size as f32).round() as i32;
+    (row(0.5 - half), row(0.5 + half))
+}
+
+// Size of the monitor screen texture at a content scale of 1
+const SCREEN_TEX_SIZE: i32 = 1024;
+const MAX_SCREEN_TEX_SIZE: i32 = 4096;
+const SHADOW_MAP_SIZE: i32 = 4096;
+
+// Where the first line of text starts on the monitor, in glyph renderer units
+const TEXT_ORIGIN: (f32, f32) = (0.05, 0.7);
+// Share of a line glyphs reach above their baseline, the rest is room for descenders
+const GLYPH_ASCENT: f32 = 0.75;
+// Space between the end of a scrolling topic and its next repetition
+const MARQUEE_GAP: f32 = 0.1;
+
+const BACKGROUND_COLOR: [f32; 3] = [29.0 / 255.0, 31.0 / 255.0, 33.0 / 255.0];
+// Cycled through with g, in order
+const BACKGROUND_PRESETS: &[(&str, [f32; 3])] = &[
+    ("dark gray", BACKGROUND_COLOR),
+    ("black", [0.0, 0.0, 0.0]),
+    ("deep blue", [8.0 / 255.0, 16.0 / 255.0, 48.0 / 255.0]),
+    ("chroma key green", [0.0, 1.0, 0.0]),
+];
+const DEBUG_BOUNDS_COLOR: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+
+const DEFAULT_WINDOW_TITLE: &str = "Stream starting...";
+// Distance between neighbouring monitors along the desk, in model space
+const MONITOR_SPACING: f32 = 0.5;
+// Plain h264 that most players can open
+const DEFAULT_FFMPEG_ARGS: &str = "-c:v libx264 -pix_fmt yuv420p";
+// How long to wait before checking again when a frame had nothing new to show
+const IDLE_SLEEP: Duration = Duration::from_millis(10);
+// How often --stats prints
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
 struct Args {
-    start_time: NaiveTime,
+    program_name: String,
+    start_at: StartAt,
     topic: String,
+    topic_scroll: bool,
+    topic_scroll_speed: f32,
+    subtitle: Option<String>,
+    // In the same units as TEXT_ORIGIN
+    subtitle_pos: (f32, f32),
+    subtitle_scale: f32,
+    subtitle_color: [f32; 3],
+    big_clock: bool,
+    // Center of the clock, in the same units as TEXT_ORIGIN
+    big_clock_pos: (f32, f32),
+    big_clock_scale: f32,
+    big_clock_blink: bool,
+    text_panel: bool,
+    text_panel_color: [f32; 3],
+    text_panel_alpha: f32,
+    // In the same units as TEXT_ORIGIN
+    text_panel_padding: f32,
+    labels: &'static Labels,
+    title: String,
+    icon: Option<std::path::PathBuf>,
+    title_countdown: bool,
+    monitors: usize,
+    particles: usize,
+    particle_speed: f32,
+    particle_color: [f32; 3],
+    audio_bars: bool,
+    audio_device: Option<String>,
+    no_animation: bool,
+    rtl: bool,
+    font: Option<std::path::PathBuf>,
+    font_index: usize,
+    preload_glyphs: String,
+    easing: Easing,
+    caret_glide: f32,
+    // Where the cursor stays instead of following the text, in the same units as TEXT_ORIGIN
+    cursor_pos: Option<(f32, f32)>,
+    // Space between the text and the cursor following it, in cursor widths
+    cursor_gap: f32,
+    text_softness: Option<f32>,
+    smooth_quads: bool,
+    text_overflow: TextOverflow,
+    // Fraction of the visible screen, see anchored_text_origin
+    text_anchor: Option<(f32, f32)>,
+    text_align: TextAlign,
+    camera_speed: f32,
+    // Radians
+    camera_yaw: f32,
+    camera_tilt: f32,
+    // Seconds before start to ease the camera to rest over
+    camera_settle: Option<f32>,
+    fov: Option<f32>,
+    near: Option<f32>,
+    far: Option<f32>,
+    intro_fade: f32,
+    hardware_pcf: bool,
+    shadows: bool,
+    // Draw only the text over the background, no scene
+    text_only: bool,
+    transparent: bool,
+    shadow_samples: u32,
+    light_size: f32,
+    debug_bounds: bool,
+    bg_color: [f32; 3],
+    stats: bool,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    shader_dir: Option<std::path::PathBuf>,
+    list_monitors: bool,
+    pixel_perfect: bool,
+    fixed_timestep: Option<u32>,
+    seed: Option<u64>,
+    camera_path: Option<std::path::PathBuf>,
+    scene: Option<std::path::PathBuf>,
+    live_scene: Option<std::path::PathBuf>,
+    verbose_obj: bool,
+    live_transition: f32,
+    lut: Option<std::path::PathBuf>,
+    chromatic_aberration: bool,
+    chromatic_aberration_intensity: f32,
+    noise: bool,
+    noise_intensity: f32,
+    scanline_intensity: f32,
+    scanline_frequency: f32,
+    screen_curvature: f32,
+    vignette: bool,
+    vignette_radius: f32,
+    vignette_softness: f32,
+    record: Option<std::path::PathBuf>,
+    record_fps: u32,
+    frame_pipe: Option<std::path::PathBuf>,
+    ffmpeg_args: Vec<String>,
+}
+
+#[derive(Error, Debug)]
+enum ParseColorError {
+    #[error("expected a color in the form #rrggbb")]
+    InvalidLength,
+    #[error("invalid hex digit")]
+    InvalidHex(#[from] std::num::ParseIntError),
+}
+
+/// The preset after current, or the first one if current isn't a preset
+fn next_background_preset(current: [f32; 3]) -> (&'static str, [f32; 3]) {
+    let next = BACKGROUND_PRESETS
+        .iter()
+        .position(|(_, color)| *color == current)
+        .map_or(0, |i| (i + 1) % BACKGROUND_PRESETS.len());
+    BACKGROUND_PRESETS[next]
+}
+
+/// Parse a #rrggbb hex color into [0, 1] floats
+fn parse_color(s: &str) -> Result<[f32; 3], ParseColorError> {
+    let s = s.strip_prefix('#').unwrap_or(s);
+    if s.len() != 6 || !s.is_ascii() {
+        return Err(ParseColorError::InvalidLength);
+    }
+
+    let mut ret = [0.0f32; 3];
+    for i in 0..3 {
+        ret[i] = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16)? as f32 / 255.0;
+    }
+    Ok(ret)
+}
+
+#[derive(Error, Debug)]
+enum ParsePositionError {
+    #[error("expected a position in the form x,y")]
+    MissingComma,
+    #[error("invalid coordinate")]
+    InvalidCoordinate(#[from] std::num::ParseFloatError),
+}
+
+fn parse_position(s: &str) -> Result<(f32, f32), ParsePositionError> {
+    let (x, y) = s.split_once(',').ok_or(ParsePositionError::MissingComma)?;
+    Ok((x.trim().parse()?, y.trim().parse()?))
 }
 
 impl Args {
     fn parse<It: Iterator<Item = String>>(mut args: It) -> Args {
         let mut start_time = None;
+        let mut countdown = None;
         let mut topic = None;
+        let mut topic_scroll = false;
+        let mut topic_scroll_speed = 0.1;
+        let mut subtitle = None;
+        let mut subtitle_pos = (TEXT_ORIGIN.0, 0.3);
+        let mut subtitle_scale = 0.5;
+        let mut subtitle_color = [0.6, 0.6, 0.6];
+        let mut big_clock = false;
+        let mut big_clock_pos = (0.5, 0.5);
+        let mut big_clock_scale = 3.0;
+        let mut big_clock_blink = false;
+        let mut text_panel = false;
+        let mut text_panel_color = [0.0, 0.0, 0.0];
+        let mut text_panel_alpha = 0.6;
+        let mut text_panel_padding = 0.02;
+        let mut labels = &lang::ENGLISH;
+        let mut title = None;
+        let mut title_countdown = false;
+        let mut icon = None;
+        let mut monitors = None;
+        let mut particles = 0;
+        let mut particle_speed = 0.1;
+        let mut particle_color = [0.8, 0.8, 0.8];
+        let mut audio_bars = false;
+        let mut audio_device = None;
+        let mut no_animation = false;
+        let mut rtl = false;
+        let mut font = None;
+        let mut font_index = 0;
+        let mut preload_glyphs = glyph_cache::PRINTABLE_ASCII.to_string();
+        let mut easing = Easing::default();
+        let mut caret_glide = 0.0;
+        let mut cursor_pos = None;
+        let mut cursor_gap = 0.0;
+        let mut text_softness = None;
+        let mut smooth_quads = false;
+        let mut text_overflow = TextOverflow::default();
+        let mut text_anchor = None;
+        let mut text_align = TextAlign::default();
+        let mut camera_speed = 1.0;
+        let mut camera_yaw = 0.0f32;
+        let mut camera_tilt = 0.0f32;
+        let mut camera_settle = None;
+        let mut fov = None;
+        let mut near = None;
+        let mut far = None;
+        let mut intro_fade = 0.0;
+        let mut hardware_pcf = true;
+        let mut shadows = true;
+        let mut text_only = false;
+        let mut transparent = false;
+        let mut shadow_samples = 1;
+        let mut light_size = 3.0;
+        let mut debug_bounds = false;
+        let mut bg_color = BACKGROUND_COLOR;
+        let mut stats = false;
+        let mut flip_horizontal = false;
+        let mut flip_vertical = false;
+        let mut shader_dir = None;
+        let mut list_monitors = false;
+        let mut pixel_perfect = false;
+        let mut fixed_timestep = None;
+        let mut seed = None;
+        let mut camera_path = None;
+        let mut scene = None;
+        let mut live_scene = None;
+        let mut verbose_obj = false;
+        let mut live_transition = 2.0;
+        let mut lut = None;
+        let mut chromatic_aberration = false;
+        let mut chromatic_aberration_intensity = 0.01;
+        let mut noise = false;
+        let mut noise_intensity = 0.1;
+        let mut scanline_intensity = DEFAULT_SCANLINE_INTENSITY;
+        let mut scanline_frequency = DEFAULT_SCANLINE_FREQUENCY;
+        let mut screen_curvature = DEFAULT_CURVATURE;
+        let mut vignette = false;
+        let mut vignette_radius = 0.75;
+        let mut vignette_softness = 0.45;
+        let mut record = None;
+        let mut record_fps = 30;
+        let mut frame_pipe = None;
+        let mut ffmpeg_args = None;
         let process_name = args.next().unwrap_or_else(|| "prog".to_string());
 
         while let Some(arg) = args.next() {
@@ -60,22 +367,401 @@ impl Args {
                 "--start-time" => {
                     start_time = args.next().map(|v| v.parse());
                 }
+                "--countdown" => {
+                    countdown = Some(Self::parse_value(
+                        &process_name,
+                        &arg,
+                        args.next(),
+                        countdown::parse_duration,
+                    ));
+                }
                 "--topic" => {
                     topic = args.next();
                 }
+                "--topic-scroll" => {
+                    topic_scroll = true;
+                }
+                "--topic-scroll-speed" => {
+                    topic_scroll_speed =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--subtitle" => {
+                    subtitle = args.next();
+                }
+                "--subtitle-pos" => {
+                    subtitle_pos =
+                        Self::parse_value(&process_name, &arg, args.next(), parse_position);
+                }
+                "--subtitle-scale" => {
+                    subtitle_scale =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if subtitle_scale <= 0.0 {
+                        println!("--subtitle-scale must be above 0");
+                        Self::help(&process_name);
+                    }
+                }
+                "--subtitle-color" => {
+                    subtitle_color =
+                        Self::parse_value(&process_name, &arg, args.next(), parse_color);
+                }
+                "--big-clock" => {
+                    big_clock = true;
+                }
+                "--big-clock-pos" => {
+                    big_clock_pos =
+                        Self::parse_value(&process_name, &arg, args.next(), parse_position);
+                }
+                "--big-clock-scale" => {
+                    big_clock_scale =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if big_clock_scale <= 0.0 {
+                        println!("--big-clock-scale must be above 0");
+                        Self::help(&process_name);
+                    }
+                }
+                "--big-clock-blink" => {
+                    big_clock_blink = true;
+                }
+                "--text-panel" => {
+                    text_panel = true;
+                }
+                "--text-panel-color" => {
+                    text_panel_color =
+                        Self::parse_value(&process_name, &arg, args.next(), parse_color);
+                }
+                "--text-panel-alpha" => {
+                    text_panel_alpha =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if !(0.0..=1.0).contains(&text_panel_alpha) {
+                        println!("--text-panel-alpha must be between 0 and 1");
+                        Self::help(&process_name);
+                    }
+                }
+                "--text-panel-padding" => {
+                    text_panel_padding =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if text_panel_padding < 0.0 {
+                        println!("--text-panel-padding cannot be negative");
+                        Self::help(&process_name);
+                    }
+                }
+                "--lang" => {
+                    let Some(code) = args.next() else {
+                        println!("No value provided for {arg}");
+                        Self::help(&process_name);
+                    };
+                    labels = lang::labels(&code).unwrap_or_else(|| {
+                        println!("No labels for language {code}, using English");
+                        &lang::ENGLISH
+                    });
+                }
+                "--title" => {
+                    title = args.next();
+                }
+                "--title-countdown" => {
+                    title_countdown = true;
+                }
+                "--icon" => {
+                    icon = args.next().map(Into::into);
+                }
+                "--monitors" => {
+                    monitors = args.next().map(|v| v.parse::<usize>());
+                }
+                "--particles" => {
+                    particles = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--particle-speed" => {
+                    particle_speed =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--particle-color" => {
+                    particle_color =
+                        Self::parse_value(&process_name, &arg, args.next(), parse_color);
+                }
+                "--audio-bars" => {
+                    audio_bars = true;
+                }
+                "--audio-device" => {
+                    audio_device = args.next();
+                }
+                "--no-animation" => {
+                    no_animation = true;
+                }
+                "--rtl" => {
+                    rtl = true;
+                }
+                "--font" => {
+                    font = args.next().map(Into::into);
+                }
+                "--font-index" => {
+                    font_index = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--preload-glyphs" => {
+                    preload_glyphs = args.next().unwrap_or_default();
+                }
+                "--easing" => {
+                    easing = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--cursor-pos" => {
+                    cursor_pos = Some(Self::parse_value(
+                        &process_name,
+                        &arg,
+                        args.next(),
+                        parse_position,
+                    ));
+                }
+                "--cursor-gap" => {
+                    cursor_gap = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--text-softness" => {
+                    let v: f32 = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if v <= 0.0 {
+                        println!("--text-softness must be above 0");
+                        Self::help(&process_name);
+                    }
+                    text_softness = Some(v);
+                }
+                "--caret-glide" => {
+                    caret_glide = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if caret_glide < 0.0 {
+                        println!("--caret-glide cannot be negative");
+                        Self::help(&process_name);
+                    }
+                }
+                "--smooth-quads" => {
+                    smooth_quads = true;
+                }
+                "--text-overflow" => {
+                    text_overflow = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--text-anchor" => {
+                    text_anchor = Some(Self::parse_value(
+                        &process_name,
+                        &arg,
+                        args.next(),
+                        parse_position,
+                    ));
+                }
+                "--text-align" => {
+                    text_align = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--no-camera-spin" => {
+                    camera_speed = 0.0;
+                }
+                "--camera-speed" => {
+                    camera_speed = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if camera_speed < 0.0 {
+                        println!("--camera-speed cannot be negative");
+                        Self::help(&process_name);
+                    }
+                }
+                "--camera-yaw" => {
+                    let degrees: f32 =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    camera_yaw = degrees.to_radians();
+                }
+                "--camera-tilt" => {
+                    let degrees: f32 =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    camera_tilt = degrees.to_radians();
+                }
+                "--camera-settle" => {
+                    let seconds: f32 =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if seconds <= 0.0 {
+                        println!("--camera-settle must be above 0");
+                        Self::help(&process_name);
+                    }
+                    camera_settle = Some(seconds);
+                }
+                "--fov" => {
+                    let v: f32 = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if !(v > 0.0 && v < 180.0) {
+                        println!("--fov must be between 0 and 180 degrees");
+                        Self::help(&process_name);
+                    }
+                    fov = Some(v);
+                }
+                "--near" => {
+                    let v: f32 = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if v <= 0.0 {
+                        println!("--near must be greater than 0");
+                        Self::help(&process_name);
+                    }
+                    near = Some(v);
+                }
+                "--far" => {
+                    far = Some(Self::parse_value(
+                        &process_name,
+                        &arg,
+                        args.next(),
+                        str::parse,
+                    ));
+                }
+                "--intro-fade" => {
+                    intro_fade = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if intro_fade < 0.0 {
+                        println!("--intro-fade cannot be negative");
+                        Self::help(&process_name);
+                    }
+                }
+                "--no-hardware-pcf" => {
+                    hardware_pcf = false;
+                }
+                "--no-shadows" => {
+                    shadows = false;
+                }
+                "--shadow-samples" => {
+                    shadow_samples =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if !(1..=MAX_SHADOW_SAMPLES).contains(&shadow_samples) {
+                        println!("--shadow-samples must be between 1 and {MAX_SHADOW_SAMPLES}");
+                        Self::help(&process_name);
+                    }
+                }
+                "--light-size" => {
+                    light_size = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if light_size < 0.0 {
+                        println!("--light-size cannot be negative");
+                        Self::help(&process_name);
+                    }
+                }
+                "--debug-bounds" => {
+                    debug_bounds = true;
+                }
+                "--text-only" => {
+                    text_only = true;
+                }
+                "--transparent" => {
+                    transparent = true;
+                }
+                "--bg-color" => {
+                    bg_color = Self::parse_value(&process_name, &arg, args.next(), parse_color);
+                }
+                "--stats" => {
+                    stats = true;
+                }
+                "--flip-horizontal" => {
+                    flip_horizontal = true;
+                }
+                "--flip-vertical" => {
+                    flip_vertical = true;
+                }
+                "--shader-dir" => {
+                    shader_dir = args.next().map(Into::into);
+                }
+                "--list-monitors" => {
+                    list_monitors = true;
+                }
+                "--pixel-perfect" => {
+                    pixel_perfect = true;
+                }
+                "--fixed-timestep" => {
+                    let rate: u32 = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if rate == 0 {
+                        println!("--fixed-timestep must be greater than 0");
+                        Self::help(&process_name);
+                    }
+                    fixed_timestep = Some(rate);
+                }
+                "--seed" => {
+                    seed = Some(Self::parse_value(
+                        &process_name,
+                        &arg,
+                        args.next(),
+                        str::parse,
+                    ));
+                }
+                "--camera-path" => {
+                    camera_path = args.next().map(Into::into);
+                }
+                "--scene" => {
+                    scene = args.next().map(Into::into);
+                }
+                "--live-scene" => {
+                    live_scene = args.next().map(Into::into);
+                }
+                "--verbose-obj" => {
+                    verbose_obj = true;
+                }
+                "--live-transition" => {
+                    live_transition =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                    if live_transition < 0.0 {
+                        println!("--live-transition must not be negative");
+                        Self::help(&process_name);
+                    }
+                }
+                "--lut" => {
+                    lut = args.next().map(Into::into);
+                }
+                "--chromatic-aberration" => {
+                    chromatic_aberration = true;
+                }
+                "--chromatic-aberration-intensity" => {
+                    chromatic_aberration_intensity =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--noise" => {
+                    noise = true;
+                }
+                "--noise-intensity" => {
+                    noise_intensity =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--scanline-intensity" => {
+                    scanline_intensity =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--scanline-frequency" => {
+                    scanline_frequency =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--screen-curvature" => {
+                    screen_curvature =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--vignette" => {
+                    vignette = true;
+                }
+                "--vignette-radius" => {
+                    vignette_radius =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--vignette-softness" => {
+                    vignette_softness =
+                        Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--record" => {
+                    record = args.next().map(Into::into);
+                }
+                "--record-fps" => {
+                    record_fps = Self::parse_value(&process_name, &arg, args.next(), str::parse);
+                }
+                "--frame-pipe" => {
+                    frame_pipe = args.next().map(Into::into);
+                }
+                "--ffmpeg-args" => {
+                    ffmpeg_args = args.next();
+                }
                 _ => {
                     Self::help(&process_name);
                 }
             }
         }
 
-        let start_time = match start_time {
-            Some(Ok(start_time)) => start_time,
-            Some(Err(e)) => {
+        let start_at = match (start_time, countdown) {
+            (Some(Ok(_)), Some(_)) => {
+                println!("--start-time and --countdown can't be used together");
+                Self::help(&process_name);
+            }
+            (Some(Ok(start_time)), None) => StartAt::Time(start_time),
+            (Some(Err(e)), _) => {
                 println!("Failed to parse start time: {e}");
                 Self::help(&process_name);
             }
-            None => {
+            (None, Some(countdown)) => StartAt::Countdown(countdown),
+            (None, None) => {
                 println!("Start time not provided");
                 Self::help(&process_name);
             }
@@ -89,10 +775,170 @@ impl Args {
             }
         };
 
-        Args { start_time, topic }
+        let title = title.unwrap_or_else(|| DEFAULT_WINDOW_TITLE.to_string());
+
+        let monitors = match monitors {
+            Some(Ok(0)) => {
+                println!("At least one monitor is required");
+                Self::help(&process_name);
+            }
+            Some(Ok(v)) => v,
+            Some(Err(e)) => {
+                println!("Failed to parse monitor count: {e}");
+                Self::help(&process_name);
+            }
+            None => 1,
+        };
+
+        if !(0.0..=1.0).contains(&scanline_intensity) {
+            println!("--scanline-intensity must be between 0 and 1");
+            Self::help(&process_name);
+        }
+
+        if scanline_frequency <= 0.0 {
+            println!("--scanline-frequency must be greater than 0");
+            Self::help(&process_name);
+        }
+
+        if topic_scroll && rtl {
+            println!("--topic-scroll only supports left to right text");
+            Self::help(&process_name);
+        }
+
+        if !(0.0..=1.0).contains(&screen_curvature) {
+            println!("--screen-curvature must be between 0 and 1");
+            Self::help(&process_name);
+        }
+
+        if record_fps == 0 {
+            println!("--record-fps must be greater than 0");
+            Self::help(&process_name);
+        }
+
+        let ffmpeg_args = ffmpeg_args
+            .as_deref()
+            .unwrap_or(DEFAULT_FFMPEG_ARGS)
+            .split_whitespace()
+            .map(ToString::to_string)
+            .collect();
+
+        let program_name = std::path::Path::new(&process_name)
+            .file_name()
+            .map(|v| v.to_string_lossy().into_owned())
+            .unwrap_or_else(|| process_name.clone());
+
+        Args {
+            program_name,
+            start_at,
+            topic,
+            topic_scroll,
+            topic_scroll_speed,
+            subtitle,
+            subtitle_pos,
+            subtitle_scale,
+            subtitle_color,
+            big_clock,
+            big_clock_pos,
+            big_clock_scale,
+            big_clock_blink,
+            text_panel,
+            text_panel_color,
+            text_panel_alpha,
+            text_panel_padding,
+            labels,
+            title,
+            icon,
+            title_countdown,
+            monitors,
+            particles,
+            particle_speed,
+            particle_color,
+            audio_bars,
+            audio_device,
+            no_animation,
+            rtl,
+            font,
+            font_index,
+            preload_glyphs,
+            easing,
+            caret_glide,
+            cursor_pos,
+            cursor_gap,
+            text_softness,
+            smooth_quads,
+            text_overflow,
+            text_anchor,
+            text_align,
+            camera_speed,
+            camera_yaw,
+            camera_tilt,
+            camera_settle,
+            fov,
+            near,
+            far,
+            intro_fade,
+            hardware_pcf,
+            shadows,
+            text_only,
+            transparent,
+            shadow_samples,
+            light_size,
+            debug_bounds,
+            bg_color,
+            stats,
+            flip_horizontal,
+            flip_vertical,
+            shader_dir,
+            list_monitors,
+            pixel_perfect,
+            fixed_timestep,
+            seed,
+            camera_path,
+            scene,
+            live_scene,
+            verbose_obj,
+            live_transition,
+            lut,
+            chromatic_aberration,
+            chromatic_aberration_intensity,
+            noise,
+            noise_intensity,
+            scanline_intensity,
+            scanline_frequency,
+            screen_curvature,
+            vignette,
+            vignette_radius,
+            vignette_softness,
+            record,
+            record_fps,
+            frame_pipe,
+            ffmpeg_args,
+        }
+    }
+
+    fn parse_value<T, E: std::fmt::Display>(
+        process_name: &str,
+        arg: &str,
+        value: Option<String>,
+        parse: impl FnOnce(&str) -> Result<T, E>,
+    ) -> T {
+        let Some(value) = value else {
+            println!("No value provided for {arg}");
+            Self::help(process_name);
+        };
+
+        match parse(&value) {
+            Ok(v) => v,
+            Err(e) => {
+                println!("Failed to parse {arg}: {e}");
+                Self::help(process_name);
+            }
+        }
     }
 
     fn help(process_name: &str) -> ! {
+        let easings = Easing::names();
+        let langs = lang::codes().collect::<Vec<_>>().join(", ");
         println!(
             "\
                  A pre-stream screen...\n\
@@ -102,60 +948,346 @@ impl Args {
                  \n\
                  Arguments:\n\
                  --start-time: when stream starts\n\
+                 --countdown: count down from a duration like 5m or 1h30m instead of to --start-time, c pauses and resumes it\n\
                  --topic: what are we working on today\n\
+                 --topic-scroll: scroll the topic along a single line instead of wrapping it\n\
+                 --topic-scroll-speed: how fast the topic scrolls, in screen widths per second (default: 0.1)\n\
+                 --subtitle: extra static text, like social handles, drawn below the countdown\n\
+                 --subtitle-pos: where the subtitle starts as x,y, in the units the countdown text starts at 0.05,0.7 in (default: 0.05,0.3)\n\
+                 --subtitle-scale: size of the subtitle relative to the countdown text (default: 0.5)\n\
+                 --subtitle-color: subtitle color as #rrggbb (default: #999999)\n\
+                 --big-clock: draw the remaining time as a large HH:MM:SS as well as the terminal text\n\
+                 --big-clock-pos: center of the big clock as x,y, in the same units as --subtitle-pos (default: 0.5,0.5)\n\
+                 --big-clock-scale: size of the big clock relative to the terminal text (default: 3)\n\
+                 --big-clock-blink: blink the big clock's colons every half second\n\
+                 --text-panel: draw a translucent box behind the countdown text so it reads over the scene\n\
+                 --text-panel-color: panel color as #rrggbb (default: #000000)\n\
+                 --text-panel-alpha: panel opacity, 0 to 1 (default: 0.6)\n\
+                 --text-panel-padding: space between the text and the panel edges (default: 0.02)\n\
+                 --lang: language of the fixed text, one of {langs} (default: en)\n\
+                 --title: window title (default: \"{DEFAULT_WINDOW_TITLE}\")\n\
+                 --title-countdown: append the remaining time to the window title\n\
+                 --icon: png to use as the window icon (default: none)\n\
+                 --monitors: how many monitors to place on the desk (default: 1)\n\
+                 --particles: number of background particles (default: 0)\n\
+                 --particle-speed: how fast particles drift up the screen (default: 0.1)\n\
+                 --particle-color: particle color as #rrggbb (default: #cccccc)\n\
+                 --audio-bars: show equalizer bars driven by captured audio\n\
+                 --audio-device: name of the input device to capture for --audio-bars (default: system default)\n\
+                 --no-animation: show text immediately instead of typing it out\n\
+                 --rtl: lay text out right to left, without bidi or shaping\n\
+                 --font: ttf/otf/ttc file to render text with (default: bundled Hack)\n\
+                 --font-index: face to use from a font collection (default: 0)\n\
+                 --preload-glyphs: characters to render at startup instead of on first use (default: printable ascii)\n\
+                 --easing: how typing speeds up and slows down, one of {easings} (default: in-sine)\n\
+                 --cursor-pos: keep the cursor at x,y instead of after the text, in the same units as --subtitle-pos (default: follows the text)\n\
+                 --text-softness: width of the fade at glyph edges, about 0.02 is crisp and 0.3 soft (default: 0.23)\n\
+                 --cursor-gap: space between the end of the text and the cursor in cursor widths, negative overlaps the last glyph (default: 0)\n\
+                 --caret-glide: seconds the cursor takes to move to the end of the text, eased like typing, 0 jumps (default: 0)\n\
+                 --smooth-quads: antialias the edges of the cursor and other flat quads\n\
+                 --text-overflow: what happens to text past the bottom of the screen, one of clip, scroll, shrink (default: clip)\n\
+                 --text-anchor: place the text at x,y as fractions of the screen, 0,0 is the top left corner and 1,1 the bottom right (default: fixed near the top left)\n\
+                 --text-align: which point of the text sits on --text-anchor, one of top-left, top, top-right, left, center, right, bottom-left, bottom, bottom-right (default: top-left)\n\
+                 --no-camera-spin: keep the camera still, allows skipping renders while idle, same as --camera-speed 0\n\
+                 --camera-speed: how fast the camera moves along its path, 0 holds it at the start (default: 1)\n\
+                 --camera-yaw: degrees to swing the camera around the scene's vertical axis (default: 0)\n\
+                 --camera-tilt: degrees to pitch the camera up, negative looks down (default: 0)\n\
+                 --camera-settle: seconds before start to ease the camera to rest where its path begins (default: off)\n\
+                 --fov: vertical field of view in degrees (default: from the scene, 50)\n\
+                 --near: near clip plane distance, above 0 (default: from the scene, 0.1)\n\
+                 --far: far clip plane distance, beyond --near (default: from the scene, 10)\n\
+                 --intro-fade: seconds to fade in from the background color at startup, 0 disables (default: 0)\n\
+                 --no-hardware-pcf: compare shadow depths by hand instead of with filtered shadow samplers\n\
+                 --no-shadows: skip the shadow map and light everything evenly, for slow GPUs\n\
+                 --shadow-samples: shadow map taps averaged per pixel for soft shadows, 1 to {MAX_SHADOW_SAMPLES}, each one adds a texture read to every pixel of the scene (default: 1, hard edges)\n\
+                 --light-size: how far soft shadow taps spread, in shadow map texels (default: 3)\n\
+                 --text-only: draw just the text and cursor over the background color, skipping the 3d scene\n\
+                 --transparent: clear to a see through window instead of the background color, for overlays when the compositor supports it\n\
+                 --bg-color: background color as #rrggbb, g cycles through presets while running (default: #1d1f21)\n\
+                 --debug-bounds: outline the bounding box of every mesh, b toggles it while running\n\
+                 --stats: print meshes, glyphs and draw calls of the last frame and how long it took to render, once a second\n\
+                 --flip-horizontal: mirror the whole window left to right, text included, for captures that mirror it back\n\
+                 --flip-vertical: mirror the whole window top to bottom\n\
+                 --shader-dir: read 3d_fragment.glsl, sdf_fragment.glsl and screen_fragment.glsl from here instead of the built in copies, r reloads them while running\n\
+                 --list-monitors: print the connected monitors with their positions and current video modes, then exit\n\
+                 --pixel-perfect: snap the text origin to whole screen texels for crisper static text\n\
+                 --fixed-timestep: update the scene this many times per second regardless of frame rate, for reproducible recordings (default: once per frame)\n\
+                 --seed: seed for the particle layout and drift and the film grain, the same seed repeats a run exactly (default: from the clock)\n\
+                 --camera-path: file of \"time px py pz tx ty tz [easing]\" camera keyframes, easing is one of the --easing names and shapes the move to the next keyframe (default: slow orbit)\n\
+                 --scene: json file describing the objects, light and camera (default: desk with monitors)\n\
+                 --live-scene: scene whose objects replace the current ones once the stream starts\n\
+                 --verbose-obj: print vertex, triangle, uv and normal counts and the bounds of every model as it loads\n\
+                 --live-transition: seconds to fade from the countdown to the live banner (default: 2)\n\
+                 --lut: hald clut or strip png to color grade the monitor through\n\
+                 --chromatic-aberration: split color channels towards the edges of the monitor\n\
+                 --chromatic-aberration-intensity: how far channels split (default: 0.01)\n\
+                 --noise: animated static over the monitor\n\
+                 --noise-intensity: strength of the static (default: 0.1)\n\
+                 --scanline-intensity: brightness of the scanlines, 0 to 1 (default: {DEFAULT_SCANLINE_INTENSITY})\n\
+                 --scanline-frequency: scanlines per screen height, above 0 (default: {DEFAULT_SCANLINE_FREQUENCY})\n\
+                 --screen-curvature: CRT bulge of the screen content, 0 (flat) to 1 (default: {DEFAULT_CURVATURE})\n\
+                 --vignette: darken the edges of the monitor\n\
+                 --vignette-radius: distance from the center where darkening starts (default: 0.75)\n\
+                 --vignette-softness: how gradually the vignette fades to black (default: 0.45)\n\
+                 --record: encode every frame to the given file with ffmpeg\n\
+                 --record-fps: frame rate of the recording and --frame-pipe (default: 30)\n\
+                 --frame-pipe: write raw frames to this path, usually a fifo from mkfifo, for other programs to read. A 16 byte header of \"RGBA\" then width, height and fps as little endian u32s comes first, then each frame as width * height * 4 bytes of RGBA, top row first\n\
+                 --ffmpeg-args: output options passed to ffmpeg (default: \"{DEFAULT_FFMPEG_ARGS}\")\n\
+                 \n\
+                 Keys:\n\
+                 p: pause and resume all animation\n\
+                 b: toggle mesh bounding boxes\n\
+                 r: reload shaders from --shader-dir\n\
+                 c: pause and resume --countdown\n\
+                 g: cycle the background through dark gray, black, deep blue and chroma key green\n\
                  "
         );
         std::process::exit(1);
     }
 }
 
-fn stream_starting_string(start_time: NaiveTime, now: NaiveTime, topic: &str) -> String {
-    let remaining = start_time - now;
-    let program = std::env::args().next().unwrap();
+// Line of stream_starting_string that shows the topic
+const TOPIC_LINE: usize = 2;
+
+/// Time left before start. A start time up to 12 hours behind now has already passed and gives
+/// zero, anything further back is taken to be tomorrow's
+fn remaining_until(start: NaiveTime, now: NaiveTime) -> chrono::Duration {
+    let remaining = start - now;
+    if remaining >= chrono::Duration::zero() {
+        remaining
+    } else if remaining < -chrono::Duration::hours(12) {
+        remaining + chrono::Duration::days(1)
+    } else {
+        chrono::Duration::zero()
+    }
+}
+
+/// How far the camera has eased to rest, 0 until remaining drops below the settle window and 1
+/// once it runs out
+fn camera_settle_progress(remaining: chrono::Duration, settle_secs: f32) -> f32 {
+    let remaining_secs = remaining.num_milliseconds() as f32 / 1000.0;
+    let t = (1.0 - remaining_secs / settle_secs).clamp(0.0, 1.0);
+    Easing::InOutSine.apply(t)
+}
+
+/// What the screen counts down to, from --start-time or --countdown
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StartAt {
+    Time(NaiveTime),
+    Countdown(Duration),
+}
+
+/// StartAt while running, a countdown starts with the app and can be paused
+enum StartClock {
+    Time(NaiveTime),
+    Countdown(Countdown),
+}
+
+impl StartClock {
+    fn new(start_at: StartAt, now: Instant) -> StartClock {
+        match start_at {
+            StartAt::Time(start_time) => StartClock::Time(start_time),
+            StartAt::Countdown(duration) => StartClock::Countdown(Countdown::new(duration, now)),
+        }
+    }
+}
+
+/// Time left before start, wall_time matters for --start-time and now for --countdown
+fn time_remaining(start: &StartClock, wall_time: NaiveTime, now: Instant) -> chrono::Duration {
+    match start {
+        StartClock::Time(start_time) => remaining_until(*start_time, wall_time),
+        StartClock::Countdown(countdown) => {
+            chrono::Duration::from_std(countdown.remaining(now)).unwrap_or(chrono::Duration::zero())
+        }
+    }
+}
+
+fn format_remaining(remaining: chrono::Duration) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        remaining.num_hours(),
+        remaining.num_minutes() % 60,
+        remaining.num_seconds() % 60,
+    )
+}
+
+/// Text for --big-clock. Hidden colons keep their space rather than being left out so the digits
+/// don't shift when they blink
+fn big_clock_string(remaining: chrono::Duration, colons_visible: bool) -> String {
+    let s = format_remaining(remaining);
+    if colons_visible {
+        s
+    } else {
+        s.replace(':', "\x1b[8m:\x1b[28m")
+    }
+}
+
+fn stream_starting_string(
+    program: &str,
+    start_time: NaiveTime,
+    now: NaiveTime,
+    topic: &str,
+    labels: &Labels,
+) -> String {
+    let remaining = remaining_until(start_time, now);
     format!(
         "\
         $ ./{}\n\
         \n\
-        Today's topic: {}\n\
-        Stream starting at {}\n\
-            Current time: {}\n\
-            {:02}:{:02}:{:02} 'till stream starts",
+        {} {}\n\
+        {} {}\n\
+            {} {}\n\
+            {} {}",
         program,
+        labels.todays_topic,
         topic,
+        labels.stream_starting_at,
         start_time.format("%H:%M:%S"),
+        labels.current_time,
         now.format("%H:%M:%S"),
-        remaining.num_hours(),
-        remaining.num_minutes() % 60,
-        remaining.num_seconds() % 60,
+        format_remaining(remaining),
+        labels.till_stream_starts,
     )
 }
 
-fn reset_animation(
-    start_time: NaiveTime,
+/// Same layout as stream_starting_string for --countdown, which has no start time to show
+fn countdown_string(
+    program: &str,
+    remaining: chrono::Duration,
+    now: NaiveTime,
     topic: &str,
+    labels: &Labels,
+) -> String {
+    format!(
+        "\
+        $ ./{}\n\
+        \n\
+        {} {}\n\
+        {} {}\n\
+        {} {}",
+        program,
+        labels.todays_topic,
+        topic,
+        labels.current_time,
+        now.format("%H:%M:%S"),
+        format_remaining(remaining),
+        labels.till_back,
+    )
+}
+
+fn window_title(title: &str, remaining: chrono::Duration) -> String {
+    format!("{} - Starting in {}", title, format_remaining(remaining))
+}
+
+fn live_string(program: &str, labels: &Labels) -> String {
+    format!("$ ./{program}\n\n{}", labels.live)
+}
+
+/// What the screen should say at now, the text animations type towards
+fn target_string(args: &Args, now: NaiveTime, remaining: chrono::Duration, live: bool) -> String {
+    // The scrolling topic is drawn separately, keep its line free
+    let topic = if args.topic_scroll { "" } else { &args.topic };
+    match args.start_at {
+        _ if live => live_string(&args.program_name, args.labels),
+        StartAt::Time(start_time) => {
+            stream_starting_string(&args.program_name, start_time, now, topic, args.labels)
+        }
+        StartAt::Countdown(_) => {
+            countdown_string(&args.program_name, remaining, now, topic, args.labels)
+        }
+    }
+}
+
+fn reset_animation(
+    args: &Args,
     current: String,
+    target: &str,
 ) -> (Animation, VecDeque<AnimationReq>) {
-    let new_s = stream_starting_string(start_time, chrono::Local::now().time(), topic);
-    let reqs = animation::construct_animation_requests(&current, &new_s);
+    if args.no_animation {
+        return (Animation::None(target.to_string()), VecDeque::new());
+    }
+    let reqs = animation::construct_animation_requests(&current, target, args.easing);
     (Animation::None(current), reqs)
 }
 
-fn init_gl(window: &mut glfw::PWindow) -> glow::Context {
-    unsafe {
-        let gl = glow::Context::from_loader_function(|s| window.get_proc_address(s) as *const _);
+/// Monitors are placed alternating on either side of the central one, i.e. 0, +1, -1, +2, -2...
+fn monitor_offset(i: usize) -> f32 {
+    let step = i.div_ceil(2) as f32;
+    let side = if i.is_multiple_of(2) { -1.0 } else { 1.0 };
+    step * side * MONITOR_SPACING
+}
+
+/// Where the first baseline of a block of lines of text width wide starts, placed so the align
+/// point of the block lands on anchor.
+///
+/// anchor is a fraction of the visible part of the screen texture, 0,0 is the top left corner
+/// and 1,1 the bottom right whatever its aspect ratio. The result is in render_str units like
+/// TEXT_ORIGIN, where x runs 0 to 1 left to right and y grows upwards, and is the left end of
+/// the line even for RTL text, which mirrors it like TEXT_ORIGIN
+fn anchored_text_origin(
+    (anchor_x, anchor_y): (f32, f32),
+    align: TextAlign,
+    width: f32,
+    lines: usize,
+    line_height: f32,
+) -> (f32, f32) {
+    let region = visible_text_region();
+    let x = region.x_min + anchor_x * (region.x_max - region.x_min);
+    let y = region.y_max - anchor_y * (region.y_max - region.y_min);
+
+    let (align_x, align_y) = align.fractions();
+    let height = lines as f32 * line_height;
+    let left = x - align_x * width;
+    let top = y + align_y * height;
+    (left, top - line_height * GLYPH_ASCENT)
+}
+
+/// Left edge of a cursor width wide that sits gap cursor widths past end_x, the end of the text in
+/// reading order
+fn cursor_left(end_x: f32, width: f32, gap: f32, direction: TextDirection) -> f32 {
+    match direction {
+        TextDirection::Ltr => end_x + gap * width,
+        TextDirection::Rtl => end_x - width - gap * width,
+    }
+}
 
-        let r = 29.0f32 / 255.0f32;
-        let g = 31.0f32 / 255.0f32;
-        let b = 33.0f32 / 255.0f32;
-        gl.clear_color(r, g, b, 1.0);
+/// Box behind laid out text as x, y, w, h. It reaches above the first baseline and below the
+/// last so ascenders and descenders stay inside
+fn text_panel_rect(bounds: TextBounds, padding: f32) -> (f32, f32, f32, f32) {
+    let line_height = bounds.line_height;
+    let top = bounds.top_baseline + line_height * GLYPH_ASCENT;
+    let bottom = bounds.bottom_baseline - line_height * (1.0 - GLYPH_ASCENT);
+    (
+        bounds.x_min - padding,
+        bottom - padding,
+        bounds.x_max - bounds.x_min + padding * 2.0,
+        top - bottom + padding * 2.0,
+    )
+}
+
+/// Moves a text space position onto the nearest texel corner of a square target. Snapping keeps
+/// glyph edges sharp when the text sits still, but anything gliding across the screen would step
+/// a texel at a time instead of moving smoothly
+fn snap_to_pixel(pos: (f32, f32), tex_size: i32, aspect: f32) -> (f32, f32) {
+    let size = tex_size as f32;
+    let x = (pos.0 * size).round() / size;
+    // Same mapping as vertex.glsl, text y is scaled by the aspect ratio around the center
+    let pixel_y = (((pos.1 * 2.0 - 1.0) * aspect + 1.0) / 2.0 * size).round();
+    let y = ((pixel_y / size * 2.0 - 1.0) / aspect + 1.0) / 2.0;
+    (x, y)
+}
 
-        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
-        gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
-        gl.enable(glow::DEPTH_TEST);
-        gl.depth_func(glow::LESS);
-        gl.enable(glow::BLEND);
+const AUDIO_BARS: usize = 32;
+// How much of the window height a full scale bar takes
+const AUDIO_BAR_MAX_HEIGHT: f32 = 0.2;
+// Fraction of the bar height lost per second, so bars fall smoothly instead of flickering
+const AUDIO_BAR_DECAY: f32 = 2.0;
 
-        gl
+fn init_gl(window: &mut glfw::PWindow) -> glow::Context {
+    unsafe {
+        // Everything else is set at the start of each pass, see gl_util::begin_pass, and around
+        // each upload, see gl_util::with_packed_rows
+        glow::Context::from_loader_function(|s| window.get_proc_address(s) as *const _)
     }
 }
 
@@ -164,21 +1296,226 @@ struct App<'a> {
     gl: &'a glow::Context,
     glyph_renderer: GlyphRenderer<'a>,
     cursor_renderer: CursorRenderer<'a>,
+    line_renderer: LineRenderer<'a>,
+    debug_bounds: bool,
+    background: [f32; 3],
     screen_tex_postprocessor: ScreenTexPostprocessor<'a>,
+    postprocess_chain: PostprocessChain<'a>,
     mesh_renderer: &'a MeshRenderer<'a>,
+    particle_renderer: Option<ParticleRenderer<'a>>,
+    audio_capture: Option<AudioCapture>,
+    audio_bars: Vec<f32>,
     current_animation: Animation,
     animation_queue: VecDeque<AnimationReq>,
     cursor_visible: bool,
     cursor_flip_time: Instant,
     cursor_blink_duration: Duration,
+    caret: CaretMove,
+    start: StartClock,
+    // Where the current animations end up
+    target_text: String,
+    // Text shown in the last rendered frame, used to tell if the next one would look any different
+    rendered_text: String,
+    // Empty without --big-clock
+    big_clock_text: String,
+    // Wall clock second the target text was last built for, it can't change more often
+    target_second: u32,
+    dirty: bool,
     last_update: Instant,
     time: f32,
     light_dir: Vec3,
+    light_color: [f32; 3],
+    ambient: [f32; 3],
+    // Vertical, in degrees
+    fov: f32,
+    near: f32,
+    far: f32,
+    projection: Transform,
+    framebuffer_size: (i32, i32),
+    // Drawn into first with --flip-horizontal or --flip-vertical, rebuilt when the window resizes
+    flip_target: Option<FlipTarget<'a>>,
+    screen_tex_size: i32,
     view_matrix: Transform,
-    monitor: GpuMesh<'a>,
-    screen: GpuMesh<'a>,
-    table: GpuMesh<'a>,
-    walls: GpuMesh<'a>,
+    camera_path: CameraPath,
+    objects: Vec<SceneMesh<'a>>,
+    // Swapped in for objects halfway through the live transition
+    live_objects: Option<Vec<SceneMesh<'a>>>,
+    stream_state: StreamState,
+    stats: FrameStats,
+}
+
+/// What the last call to App::render drew. Only meshes and glyphs count towards draw calls
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct FrameStats {
+    meshes: usize,
+    glyphs: usize,
+    draw_calls: usize,
+    frame_time: Duration,
+}
+
+impl std::fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "meshes {}, glyphs {}, draw calls {}, frame {:.2}ms",
+            self.meshes,
+            self.glyphs,
+            self.draw_calls,
+            self.frame_time.as_secs_f64() * 1000.0
+        )
+    }
+}
+
+/// Off screen color and depth the size of the window
+struct FlipTarget<'a> {
+    gl: &'a glow::Context,
+    color: NativeTexture,
+    depth: NativeTexture,
+    fb: NativeFramebuffer,
+    size: (i32, i32),
+}
+
+impl<'a> FlipTarget<'a> {
+    fn new(gl: &'a glow::Context, (width, height): (i32, i32)) -> Result<FlipTarget<'a>, GlError> {
+        let (color, depth, fb) = unsafe { gl_util::setup_color_depth_target(gl, width, height)? };
+        Ok(FlipTarget {
+            gl,
+            color,
+            depth,
+            fb,
+            size: (width, height),
+        })
+    }
+}
+
+impl Drop for FlipTarget<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_framebuffer(self.fb);
+            self.gl.delete_texture(self.color);
+            self.gl.delete_texture(self.depth);
+        }
+    }
+}
+
+struct SceneMesh<'a> {
+    mesh: GpuMesh<'a>,
+    transform: Transform,
+    role: ObjectRole,
+    alpha_cutoff: f32,
+    color: [f32; 3],
+    emissive: [f32; 3],
+    draw_order: i32,
+    transparent: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DrawKey {
+    draw_order: i32,
+    transparent: bool,
+    /// Distance from the camera, anything that grows with it works
+    depth: f32,
+}
+
+/// Indices of keys in the order to draw them. Opaque objects go before transparent ones and
+/// lower draw_order before higher. Ties are broken by depth, opaque objects nearest first so the
+/// depth test skips what they hide, transparent ones furthest first so they blend over what's
+/// behind them
+fn draw_order(keys: &[DrawKey]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&keys[a], &keys[b]);
+        let depth = if a.transparent {
+            b.depth.total_cmp(&a.depth)
+        } else {
+            a.depth.total_cmp(&b.depth)
+        };
+        (a.transparent, a.draw_order)
+            .cmp(&(b.transparent, b.draw_order))
+            .then(depth)
+    });
+    order
+}
+
+fn load_scene_objects<'a>(
+    gl: &glow::Context,
+    mesh_renderer: &'a MeshRenderer<'a>,
+    scene: &Scene,
+    verbose: bool,
+) -> Result<Vec<SceneMesh<'a>>, MainError> {
+    let mut objects = Vec::new();
+    for object in &scene.objects {
+        let obj_data = scene.read_asset(&object.obj)?;
+        let mesh = obj_parser::Mesh::from_obj_file(obj_data.as_ref())
+            .map_err(|e| MainError::LoadMesh(object.obj.clone(), e))?;
+        if verbose {
+            println!("{}: {}", object.obj, mesh.summary());
+        }
+        let tex = match &object.texture {
+            Some(name) => {
+                let tex_data = scene.read_asset(name)?;
+                let tex = texture::load_texture(gl, tex_data.as_ref())
+                    .map_err(|e| MainError::LoadTexture(name.clone(), e))?;
+                Some(tex)
+            }
+            None => None,
+        };
+        let mesh = mesh_renderer
+            .upload_mesh(&mesh, tex)
+            .map_err(|e| MainError::UploadMesh(object.obj.clone(), e))?;
+
+        objects.push(SceneMesh {
+            mesh,
+            transform: scene.object_transform(object),
+            role: object.role,
+            alpha_cutoff: object.alpha_cutoff.unwrap_or(0.0),
+            color: object.color,
+            emissive: object.emissive,
+            draw_order: object.draw_order,
+            transparent: object.transparent,
+        });
+    }
+    Ok(objects)
+}
+
+/// GpuMesh cleans up its own buffers but not the texture it was given, each object loaded by
+/// load_scene_objects has its own
+fn delete_scene_objects(gl: &glow::Context, objects: Vec<SceneMesh>) {
+    for object in objects {
+        if let Some(tex) = object.mesh.tex {
+            unsafe {
+                gl.delete_texture(tex);
+            }
+        }
+    }
+}
+
+/// Goes live once when the start time is reached and never goes back
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StreamState {
+    PreStream,
+    /// since is App::time when the start time was reached, swapped is set once the live content
+    /// replaced the countdown
+    Live {
+        since: f32,
+        swapped: bool,
+    },
+}
+
+impl StreamState {
+    fn is_live(&self) -> bool {
+        matches!(self, StreamState::Live { .. })
+    }
+}
+
+/// How much the screen is covered elapsed seconds into a transition of the given length. It
+/// fades to black over the first half and back over the second, the content swaps at the peak
+fn live_transition_cover(elapsed: f32, duration: f32) -> f32 {
+    if duration <= 0.0 {
+        return 0.0;
+    }
+    let t = (elapsed / duration).clamp(0.0, 1.0);
+    1.0 - (t * 2.0 - 1.0).abs()
 }
 
 impl App<'_> {
@@ -188,72 +1525,308 @@ impl App<'_> {
         glyph_cache: &'a mut GlyphCache,
         mesh_renderer: &'a MeshRenderer<'a>,
     ) -> Result<App<'a>, MainError> {
-        let glyph_renderer =
+        glyph_cache.preload(gl, &args.preload_glyphs)?;
+        let mut glyph_renderer =
             GlyphRenderer::new(gl, glyph_cache).map_err(MainError::CreateGlyphRenderer)?;
+        if let Some(softness) = args.text_softness {
+            glyph_renderer.set_edge_softness(softness);
+        }
         let cursor_renderer = CursorRenderer::new(gl).map_err(MainError::CreateCursorRenderer)?;
-
+        cursor_renderer.set_smooth_edges(args.smooth_quads);
+        let line_renderer = LineRenderer::new(gl).map_err(MainError::CreateLineRenderer)?;
+
+        let start_instant = Instant::now();
+        let start = StartClock::new(args.start_at, start_instant);
+        let wall_time = chrono::Local::now().time();
+        let remaining = time_remaining(&start, wall_time, start_instant);
+        let target_text = target_string(args, wall_time, remaining, false);
         let (current_animation, animation_queue) =
-            reset_animation(args.start_time, &args.topic, "".to_string());
+            reset_animation(args, "".to_string(), &target_text);
         let cursor_visible = false;
 
         let cursor_blink_duration: Duration = Duration::from_secs_f32(0.5);
+        let caret_start = if args.rtl {
+            (1.0 - TEXT_ORIGIN.0, TEXT_ORIGIN.1)
+        } else {
+            TEXT_ORIGIN
+        };
+        let caret = CaretMove::new(
+            caret_start,
+            Duration::from_secs_f32(args.caret_glide),
+            args.easing,
+            Instant::now(),
+        );
         let cursor_flip_time = Instant::now() + cursor_blink_duration;
 
-        let screen_tex_postprocessor = ScreenTexPostprocessor::new(gl)
+        let mut screen_tex_postprocessor = ScreenTexPostprocessor::new(gl)
             .map_err(MainError::CreateScreenTexPostProcessorError)?;
-        let monitor = obj_parser::Mesh::from_obj_file(include_bytes!("../monitor.obj").as_slice())
-            .map_err(MainError::LoadMonitor)?;
-        let screen = obj_parser::Mesh::from_obj_file(include_bytes!("../screen.obj").as_slice())
-            .map_err(MainError::LoadScreen)?;
-        let table = obj_parser::Mesh::from_obj_file(include_bytes!("../table.obj").as_slice())
-            .map_err(MainError::LoadTable)?;
-        let walls = obj_parser::Mesh::from_obj_file(include_bytes!("../walls.obj").as_slice())
-            .map_err(MainError::LoadWalls)?;
-
-        let monitor_tex =
-            load_texture_from_png(gl, include_bytes!("../monitor_texture.png").as_slice());
-        let screen_tex =
-            load_texture_from_png(gl, include_bytes!("../screen_textuire.png").as_slice());
-        let table_tex =
-            load_texture_from_png(gl, include_bytes!("../table_texture.png").as_slice());
-        let walls_tex = load_texture_from_png(gl, include_bytes!("../wall_texture.png").as_slice());
-
-        let monitor = mesh_renderer
-            .upload_mesh(&monitor, monitor_tex)
-            .map_err(MainError::UploadMonitor)?;
-        let screen = mesh_renderer
-            .upload_mesh(&screen, screen_tex)
-            .map_err(MainError::UploadScreen)?;
-        let table = mesh_renderer
-            .upload_mesh(&table, table_tex)
-            .map_err(MainError::UploadTable)?;
-        let walls = mesh_renderer
-            .upload_mesh(&walls, walls_tex)
-            .map_err(MainError::UploadWalls)?;
+        screen_tex_postprocessor.set_scanline_intensity(args.scanline_intensity);
+        screen_tex_postprocessor.set_scanline_frequency(args.scanline_frequency);
+        screen_tex_postprocessor.set_curvature(args.screen_curvature);
+        let mut postprocess_chain =
+            PostprocessChain::new(gl).map_err(MainError::CreatePostprocessChain)?;
+        if args.chromatic_aberration {
+            postprocess_chain.push(
+                PostprocessPass::chromatic_aberration(gl, args.chromatic_aberration_intensity)
+                    .map_err(MainError::CreatePostprocessChain)?,
+            );
+        }
+        // Every randomized effect gets its own generator, forked whether it's enabled or not so
+        // toggling one effect doesn't change the others for the same seed
+        let mut rng = args.seed.map(Rng::new).unwrap_or_else(Rng::from_time);
+        let noise_rng = rng.fork();
+        let particle_rng = rng.fork();
+
+        if args.noise {
+            postprocess_chain.push(
+                PostprocessPass::noise(gl, args.noise_intensity, noise_rng)
+                    .map_err(MainError::CreatePostprocessChain)?,
+            );
+        }
+        if args.vignette {
+            postprocess_chain.push(
+                PostprocessPass::vignette(gl, args.vignette_radius, args.vignette_softness)
+                    .map_err(MainError::CreatePostprocessChain)?,
+            );
+        }
+        if let Some(path) = &args.lut {
+            let lut = lut::Lut::load(path)?;
+            postprocess_chain
+                .push(PostprocessPass::lut(gl, &lut).map_err(MainError::CreatePostprocessChain)?);
+        }
+
+        let particle_renderer = if args.particles > 0 {
+            let renderer = ParticleRenderer::new(
+                gl,
+                args.particles,
+                args.particle_speed,
+                args.particle_color,
+                particle_rng,
+            )
+            .map_err(MainError::CreateParticleRenderer)?;
+            Some(renderer)
+        } else {
+            None
+        };
+
+        let audio_capture = if args.audio_bars {
+            match AudioCapture::new(args.audio_device.as_deref()) {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    println!("Audio capture unavailable, disabling audio bars: {e}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let mut scene = match &args.scene {
+            Some(path) => Scene::load(path)?,
+            None => Scene::default_scene(),
+        };
+
+        // An explicit --camera-path wins over whatever the scene asks for
+        let camera_path = match (&args.camera_path, scene.camera.path.take()) {
+            (Some(path), _) => {
+                let f = std::fs::File::open(path).map_err(MainError::OpenCameraPath)?;
+                CameraPath::from_reader(std::io::BufReader::new(f))?
+            }
+            (None, Some(path)) => path,
+            (None, None) => CameraPath::default_orbit(),
+        };
+
+        // Flags win over the scene file
+        let fov = args.fov.unwrap_or(scene.camera.fov);
+        let near = args.near.unwrap_or(scene.camera.near);
+        let far = args.far.unwrap_or(scene.camera.far);
+        if near <= 0.0 || far <= near {
+            return Err(MainError::InvalidClipPlanes { near, far });
+        }
+        let perspective = Transform::perspective(fov.to_radians(), near, far);
+        let projection = Transform::scale(1.0 / WINDOW_ASPECT, 1.0, 1.0) * perspective;
+
+        let objects = load_scene_objects(gl, mesh_renderer, &scene, args.verbose_obj)?;
+        let live_objects = match &args.live_scene {
+            Some(path) => Some(load_scene_objects(
+                gl,
+                mesh_renderer,
+                &Scene::load(path)?,
+                args.verbose_obj,
+            )?),
+            None => None,
+        };
 
         Ok(App {
             args,
             gl,
             glyph_renderer,
             cursor_renderer,
+            line_renderer,
+            debug_bounds: args.debug_bounds,
+            background: args.bg_color,
             mesh_renderer,
+            particle_renderer,
+            audio_capture,
+            audio_bars: vec![0.0; AUDIO_BARS],
             screen_tex_postprocessor,
+            postprocess_chain,
             current_animation,
             animation_queue,
             cursor_visible,
             cursor_flip_time,
             cursor_blink_duration,
+            caret,
+            start,
+            target_text,
+            rendered_text: String::new(),
+            big_clock_text: String::new(),
+            target_second: wall_time.num_seconds_from_midnight(),
+            dirty: true,
             time: 0.0,
             last_update: Instant::now(),
-            light_dir: [0.0f32, 0.0f32, 0.0f32].into(),
+            light_dir: scene.light.direction,
+            light_color: scene.light.color,
+            ambient: scene.light.ambient,
+            fov,
+            near,
+            far,
+            projection,
+            framebuffer_size: (WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32),
+            flip_target: None,
+            screen_tex_size: SCREEN_TEX_SIZE,
             view_matrix: Transform::identity(),
-            monitor,
-            screen,
-            table,
-            walls,
+            camera_path,
+            objects,
+            live_objects,
+            stream_state: StreamState::PreStream,
+            stats: FrameStats::default(),
         })
     }
 
+    /// On HiDPI displays this is larger than the window size
+    fn set_framebuffer_size(&mut self, width: i32, height: i32) {
+        // Minimized windows report 0x0, keep the last usable size
+        if width <= 0 || height <= 0 {
+            return;
+        }
+
+        self.framebuffer_size = (width, height);
+        let aspect = width as f32 / height as f32;
+        self.projection = Transform::scale(1.0 / aspect, 1.0, 1.0)
+            * Transform::perspective(self.fov.to_radians(), self.near, self.far);
+        self.dirty = true;
+    }
+
+    /// Render the screen text at a higher resolution when the display has more pixels per point
+    fn set_content_scale(&mut self, scale: f32) {
+        self.screen_tex_size =
+            ((SCREEN_TEX_SIZE as f32 * scale).round() as i32).clamp(1, MAX_SCREEN_TEX_SIZE);
+        self.dirty = true;
+    }
+
+    /// Rebuilds shaders from --shader-dir. A shader that fails to read or compile is reported
+    /// and the one already in use stays
+    fn reload_shaders(&mut self) {
+        let args = self.args;
+        let Some(dir) = &args.shader_dir else {
+            return;
+        };
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).map_err(|e| e.to_string());
+        let results = [
+            (
+                "3d_fragment.glsl",
+                read("3d_fragment.glsl").and_then(|s| {
+                    self.mesh_renderer
+                        .reload_fragment_shader(&s)
+                        .map_err(|e| e.to_string())
+                }),
+            ),
+            (
+                "sdf_fragment.glsl",
+                read("sdf_fragment.glsl").and_then(|s| {
+                    self.glyph_renderer
+                        .reload_fragment_shader(&s)
+                        .map_err(|e| e.to_string())
+                }),
+            ),
+            (
+                "screen_fragment.glsl",
+                read("screen_fragment.glsl").and_then(|s| {
+                    self.screen_tex_postprocessor
+                        .reload_fragment_shader(&s)
+                        .map_err(|e| e.to_string())
+                }),
+            ),
+        ];
+
+        for (name, result) in results {
+            match result {
+                Ok(()) => println!("Loaded {name}"),
+                Err(e) => println!("Keeping the previous {name}: {e}"),
+            }
+        }
+        self.dirty = true;
+    }
+
+    fn remaining(&self, wall_time: NaiveTime, now: Instant) -> chrono::Duration {
+        time_remaining(&self.start, wall_time, now)
+    }
+
+    fn toggle_countdown_pause(&mut self, now: Instant) {
+        if let StartClock::Countdown(countdown) = &mut self.start {
+            countdown.toggle_pause(now);
+            self.dirty = true;
+        }
+    }
+
+    fn toggle_debug_bounds(&mut self) {
+        self.debug_bounds = !self.debug_bounds;
+        self.dirty = true;
+    }
+
+    fn cycle_background(&mut self) {
+        let (name, color) = next_background_preset(self.background);
+        println!("Background: {name}");
+        self.background = color;
+        self.dirty = true;
+    }
+
+    fn update_live_transition(&mut self) {
+        let StreamState::Live { since, swapped } = &mut self.stream_state else {
+            return;
+        };
+
+        let elapsed = self.time - *since;
+        if elapsed < self.args.live_transition {
+            self.dirty = true;
+        }
+
+        if *swapped || elapsed < self.args.live_transition / 2.0 {
+            return;
+        }
+        *swapped = true;
+
+        // Replaced outright rather than typed, the screen is dark at this point
+        self.target_text = live_string(&self.args.program_name, self.args.labels);
+        self.current_animation = Animation::None(self.target_text.clone());
+        self.animation_queue.clear();
+        if let Some(live_objects) = self.live_objects.take() {
+            let replaced = std::mem::replace(&mut self.objects, live_objects);
+            delete_scene_objects(self.gl, replaced);
+        }
+        self.dirty = true;
+    }
+
+    fn live_transition_cover(&self) -> f32 {
+        match self.stream_state {
+            StreamState::PreStream => 0.0,
+            StreamState::Live { since, .. } => {
+                live_transition_cover(self.time - since, self.args.live_transition)
+            }
+        }
+    }
+
     fn light_transform(&self) -> Transform {
         Transform::scale(0.75, 0.5, 1.0 / 10.0)
             * Transform::look_at(
@@ -270,92 +1843,296 @@ impl App<'_> {
 
     fn update(&mut self, now: Instant) {
         let time_since_last = (now - self.last_update).as_secs_f32();
-        if self.current_animation.finished(now) {
+
+        let wall_time = chrono::Local::now().time();
+        let second = wall_time.num_seconds_from_midnight();
+
+        if self.stream_state == StreamState::PreStream && self.remaining(wall_time, now).is_zero() {
+            self.stream_state = StreamState::Live {
+                since: self.time,
+                swapped: false,
+            };
+        }
+        self.update_live_transition();
+
+        // The text can only change when the clock ticks over, hold it steady until then
+        if second != self.target_second {
+            self.current_animation.release();
+        }
+
+        if self.current_animation.finished(now)
+            && self.animation_queue.is_empty()
+            && second != self.target_second
+        {
+            // Don't return early here, the rest of the scene still needs to move
             let animation =
                 std::mem::replace(&mut self.current_animation, Animation::None("".to_string()));
-            let s = animation.into_finished_string();
+            self.target_text = target_string(
+                self.args,
+                wall_time,
+                self.remaining(wall_time, now),
+                self.stream_state.is_live(),
+            );
+            let (animation, queue) = reset_animation(
+                self.args,
+                animation.into_finished_string(),
+                &self.target_text,
+            );
+            self.current_animation = animation;
+            self.animation_queue = queue;
+            self.target_second = second;
+        }
 
-            self.current_animation = match self.animation_queue.pop_front() {
-                Some(req) => animation::apply_animation_req(req, s, now),
-                None => {
-                    (self.current_animation, self.animation_queue) =
-                        reset_animation(self.args.start_time, &self.args.topic, s);
-                    return;
-                }
+        animation::step(&mut self.current_animation, &mut self.animation_queue, now);
+        if self.current_animation.as_str() != self.rendered_text {
+            self.dirty = true;
+        }
+
+        if self.args.big_clock {
+            let colons_visible = !self.args.big_clock_blink || wall_time.nanosecond() < 500_000_000;
+            let text = big_clock_string(self.remaining(wall_time, now), colons_visible);
+            if text != self.big_clock_text {
+                self.big_clock_text = text;
+                self.dirty = true;
             }
         }
 
-        self.current_animation.update(now);
+        // The caret follows the end of the text, gliding there with --caret-glide
+        if self.args.cursor_pos.is_none() {
+            let (x, y) = self.text_start();
+            let style = self.text_style();
+            let pen = self.glyph_renderer.pen_after(
+                self.current_animation.as_str(),
+                x,
+                y,
+                visible_text_region(),
+                style,
+            );
+            self.caret.move_to(pen, now);
+        }
+        if !self.caret.finished(now) {
+            self.dirty = true;
+        }
+
+        if self.cursor_flip_time < now {
+            self.cursor_flip_time += self.cursor_blink_duration;
+            self.cursor_visible = !self.cursor_visible;
+            self.dirty = true;
+        }
+
+        // Anything moving on its own means every frame is different
+        let scene_moving = self.args.camera_speed > 0.0 || self.particle_renderer.is_some();
+        if (scene_moving && !self.args.text_only)
+            || self.audio_capture.is_some()
+            || self.args.noise
+            || self.args.topic_scroll
+            || self.intro_fade_alpha() > 0.0
+        {
+            self.dirty = true;
+        }
 
         self.time += time_since_last;
-        let z_offs = f32::sin(self.time / 8.0) * 0.05;
-        let y_offs = f32::cos(self.time / 8.0) * 0.05;
-        self.view_matrix = Transform::scale(1.0 / WINDOW_ASPECT, 1.0, 1.0)
-            * Transform::perspective(50.0f32.to_radians(), 0.1, 10.0)
-            * Transform::look_at(
-                [0.6, 0.20 + y_offs, -0.05 + z_offs].into(),
-                [0.16, 0.045, 0.0].into(),
-                [0.0, 1.0, 0.0].into(),
-            )
-            .inverted();
-        self.light_dir = [-0.3, -1.0, -0.6].into();
-        self.mesh_renderer.set_light_color(&[0.8, 0.8, 0.5]);
+        if let Some(particle_renderer) = &mut self.particle_renderer {
+            particle_renderer.update(time_since_last);
+        }
+        if let Some(audio_capture) = &self.audio_capture {
+            let spectrum = audio_capture.spectrum(AUDIO_BARS);
+            for (bar, level) in self.audio_bars.iter_mut().zip(spectrum) {
+                *bar = f32::max(level, *bar - AUDIO_BAR_DECAY * time_since_last);
+            }
+        }
+        let frame =
+            |camera| camera_path::framed(&camera, self.args.camera_yaw, self.args.camera_tilt);
+        let mut camera = frame(self.camera_path.sample(self.time * self.args.camera_speed));
+        if let Some(settle_secs) = self.args.camera_settle {
+            let t = camera_settle_progress(self.remaining(wall_time, now), settle_secs);
+            if t > 0.0 {
+                camera = camera_path::blend(&camera, &frame(self.camera_path.sample(0.0)), t);
+            }
+        }
+        self.view_matrix = &self.projection * &camera.inverted();
         self.last_update = now;
     }
 
-    fn render_objects(&self) {
-        let monitor_transform = Transform::from_translation(0.0, 0.04, 0.0)
-            * Transform::scale(1.5, 1.5 * 4.0 / 5.0, 1.5);
-        self.mesh_renderer
-            .render(&self.table, &Transform::identity());
-        self.mesh_renderer.render(&self.monitor, &monitor_transform);
-        self.mesh_renderer.render(&self.screen, &monitor_transform);
-        self.mesh_renderer
-            .render(&self.walls, &Transform::scale(1.0, 1.0, -1.0));
+    /// screen_tex replaces the texture of the central screen, None leaves it idle
+    fn render_objects(&self, camera: &Transform, screen_tex: Option<NativeTexture>) {
+        self.mesh_renderer.set_camera_transform(camera);
+        let frustum = Frustum::from_transform(camera);
+        let visible = |mesh: &GpuMesh, transform: &Transform| {
+            frustum.intersects_sphere(&mesh.bounds.transformed(transform))
+        };
+
+        let keys: Vec<DrawKey> = self
+            .objects
+            .iter()
+            .map(|object| DrawKey {
+                draw_order: object.draw_order,
+                transparent: object.transparent,
+                depth: camera
+                    .project_point(object.mesh.bounds.transformed(&object.transform).center)
+                    .z(),
+            })
+            .collect();
+
+        for object in draw_order(&keys).into_iter().map(|i| &self.objects[i]) {
+            self.mesh_renderer.set_alpha_cutoff(object.alpha_cutoff);
+            self.mesh_renderer.set_untextured_color(&object.color);
+            self.mesh_renderer.set_emissive(&object.emissive);
+            if object.role == ObjectRole::Static {
+                if visible(&object.mesh, &object.transform) {
+                    self.mesh_renderer.render(&object.mesh, &object.transform);
+                }
+                continue;
+            }
+
+            let monitor_transform =
+                |i| Transform::from_translation(0.0, 0.0, monitor_offset(i)) * &object.transform;
+
+            // Only the central screen shows the countdown, the rest keep their idle texture
+            let central = monitor_transform(0);
+            if visible(&object.mesh, &central) {
+                match (object.role, screen_tex) {
+                    (ObjectRole::Screen, Some(tex)) => {
+                        self.mesh_renderer
+                            .render_with_texture(&object.mesh, Some(tex), &central)
+                    }
+                    _ => self.mesh_renderer.render(&object.mesh, &central),
+                }
+            }
+
+            let others: Vec<Transform> = (1..self.args.monitors)
+                .map(monitor_transform)
+                .filter(|transform| visible(&object.mesh, transform))
+                .collect();
+            self.mesh_renderer.render_instanced(&object.mesh, &others);
+        }
+    }
+
+    /// World space boxes of every mesh copy, culled or not
+    fn render_debug_bounds(&self) {
+        if !self.debug_bounds {
+            return;
+        }
+
+        let mut lines = Vec::new();
+        for object in &self.objects {
+            let transforms: Vec<Transform> = match object.role {
+                ObjectRole::Static => vec![object.transform.clone()],
+                ObjectRole::Monitor | ObjectRole::Screen => (0..self.args.monitors.max(1))
+                    .map(|i| {
+                        Transform::from_translation(0.0, 0.0, monitor_offset(i)) * &object.transform
+                    })
+                    .collect(),
+            };
+            for transform in &transforms {
+                lines.extend(object.mesh.bounding_box.transformed(transform).edges());
+            }
+        }
+
+        self.line_renderer
+            .render(&self.view_matrix, &lines, DEBUG_BOUNDS_COLOR);
     }
 
     fn render_light_depth(&self) -> NativeTexture {
         unsafe {
-            let (tex, fb) = gl_util::setup_depth_texture_render(self.gl, 4096, 4096).unwrap();
+            let (tex, fb) =
+                gl_util::setup_depth_texture_render(self.gl, SHADOW_MAP_SIZE, SHADOW_MAP_SIZE)
+                    .unwrap();
+            gl_util::begin_pass(self.gl, SHADOW_MAP_SIZE, SHADOW_MAP_SIZE);
+            if self.mesh_renderer.hardware_pcf() {
+                gl_util::enable_depth_compare(self.gl, tex);
+            }
 
             self.gl.clear(glow::DEPTH_BUFFER_BIT);
-            self.mesh_renderer
-                .set_camera_transform(&self.light_transform());
-            self.render_objects();
+            self.render_objects(&self.light_transform(), None);
 
             self.gl.delete_framebuffer(fb);
             tex
         }
     }
 
-    fn render_text_to_texture(&mut self, now: Instant) -> NativeTexture {
+    fn render_text_to_texture(&mut self) -> NativeTexture {
         unsafe {
-            let (tex, fb) = gl_util::setup_color_texture_render(self.gl, 1024, 1024).unwrap();
-            self.gl.clear_color(0.0, 0.0, 0.0, 1.0);
+            let size = self.screen_tex_size;
+            let (tex, fb) = gl_util::setup_color_texture_render(self.gl, size, size).unwrap();
+            gl_util::begin_pass(self.gl, size, size);
+            // With --text-only the texture is the whole frame rather than something lit on screen
+            let [r, g, b, a] = if self.args.text_only {
+                self.window_clear_color()
+            } else {
+                [0.0, 0.0, 0.0, 1.0]
+            };
+            self.gl.clear_color(r, g, b, a);
             self.gl
                 .clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
 
-            let s = self.current_animation.as_str();
+            let style = self.text_style();
+            let direction = style.direction;
+            let (start_x, start_y) = self.text_start();
+
+            if self.args.text_panel {
+                self.render_text_panel(start_x, start_y, style);
+            }
 
-            let mut cursor_pos_x = 0.05;
-            let mut cursor_pos_y = 0.7;
-            let cursor_update =
-                self.glyph_renderer
-                    .render_str(s, cursor_pos_x, cursor_pos_y, WINDOW_ASPECT);
+            let s = self.current_animation.as_str();
+            self.glyph_renderer.render_str(
+                s,
+                start_x,
+                start_y,
+                visible_text_region(),
+                WINDOW_ASPECT,
+                style,
+            );
+
+            let (cursor_pos_x, cursor_pos_y) = match self.args.cursor_pos {
+                // Mirrored for RTL like the subtitle
+                Some((x, y)) => match direction {
+                    TextDirection::Ltr => (x, y),
+                    TextDirection::Rtl => (1.0 - x, y),
+                },
+                // The caret follows the end of the text, see update
+                None => self.caret.position(self.last_update),
+            };
+
+            if self.args.topic_scroll {
+                self.render_topic_marquee(size);
+            }
 
-            cursor_pos_x += cursor_update.0;
-            cursor_pos_y += cursor_update.1;
+            if let Some(subtitle) = &self.args.subtitle {
+                let (x, y) = self.args.subtitle_pos;
+                let x = match direction {
+                    TextDirection::Ltr => x,
+                    TextDirection::Rtl => 1.0 - x,
+                };
+                let style = TextStyle {
+                    scale: self.args.subtitle_scale,
+                    color: self.args.subtitle_color,
+                    ..style
+                };
+                self.glyph_renderer.render_str(
+                    subtitle,
+                    x,
+                    y,
+                    visible_text_region(),
+                    WINDOW_ASPECT,
+                    style,
+                );
+            }
 
-            if self.cursor_flip_time < now {
-                self.cursor_flip_time += self.cursor_blink_duration;
-                self.cursor_visible = !self.cursor_visible;
+            if self.args.big_clock {
+                self.render_big_clock();
             }
 
             if self.cursor_visible {
                 let cursor_height = self.glyph_renderer.line_height() * 0.6;
                 let cursor_width = cursor_height / 2.0;
+                // The gap is from the text, a fixed --cursor-pos has none to keep
+                let gap = match self.args.cursor_pos {
+                    Some(_) => 0.0,
+                    None => self.args.cursor_gap,
+                };
                 self.cursor_renderer.render(
-                    cursor_pos_x,
+                    cursor_left(cursor_pos_x, cursor_width, gap, direction),
                     cursor_pos_y,
                     cursor_width,
                     cursor_height,
@@ -363,38 +2140,339 @@ impl App<'_> {
                 );
             }
 
-            let (tex2, fb2) = gl_util::setup_color_texture_render(self.gl, 1024, 1024).unwrap();
+            let cover = self.live_transition_cover();
+            if cover > 0.0 {
+                self.cursor_renderer.render_colored(
+                    0.0,
+                    0.0,
+                    1.0,
+                    1.0,
+                    1.0,
+                    [0.0, 0.0, 0.0, cover],
+                );
+            }
+
+            let (tex2, fb2) = gl_util::setup_color_texture_render(self.gl, size, size).unwrap();
             self.screen_tex_postprocessor
                 .render(tex, self.time, WINDOW_ASPECT);
 
             self.gl.delete_framebuffer(fb);
             self.gl.delete_framebuffer(fb2);
             self.gl.delete_texture(tex);
-            tex2
+
+            self.postprocess_chain
+                .render(tex2, size, size, self.time, WINDOW_ASPECT)
+                .unwrap()
+        }
+    }
+
+    /// Draws the topic scrolling after its label, once the label has been typed out
+    fn render_topic_marquee(&mut self, tex_size: i32) {
+        let label = self.args.labels.todays_topic;
+        let label_typed = self
+            .current_animation
+            .as_str()
+            .lines()
+            .nth(TOPIC_LINE)
+            .is_some_and(|line| line.starts_with(label));
+        if !label_typed {
+            return;
+        }
+
+        let topic_width = self.glyph_renderer.measure_str(&self.args.topic);
+        if topic_width <= 0.0 {
+            return;
+        }
+
+        let line_height = self.glyph_renderer.line_height();
+        let origin = self.text_origin();
+        let start_x = origin.0 + self.glyph_renderer.measure_str(&format!("{label} "));
+        let baseline = origin.1 - line_height * TOPIC_LINE as f32;
+
+        // Clip to the rest of the line, converting through the same mapping as glyph vertices
+        let to_pixels = |v: f32| ((v + 1.0) / 2.0 * tex_size as f32) as i32;
+        let left = to_pixels(start_x * 2.0 - 1.0);
+        let bottom = to_pixels(((baseline - line_height * 0.5) * 2.0 - 1.0) * WINDOW_ASPECT);
+        let top = to_pixels(((baseline + line_height * 0.5) * 2.0 - 1.0) * WINDOW_ASPECT);
+
+        let period = topic_width + MARQUEE_GAP;
+        let offset = (self.time * self.args.topic_scroll_speed) % period;
+        let style = TextStyle {
+            no_wrap: true,
+            ..TextStyle::default()
+        };
+
+        unsafe {
+            self.gl.enable(glow::SCISSOR_TEST);
+            self.gl.scissor(left, bottom, tex_size - left, top - bottom);
+
+            let mut x = start_x - offset;
+            while x < 1.0 {
+                self.glyph_renderer.render_str(
+                    &self.args.topic,
+                    x,
+                    baseline,
+                    TextRegion::FULL,
+                    WINDOW_ASPECT,
+                    style,
+                );
+                x += period;
+            }
+
+            self.gl.disable(glow::SCISSOR_TEST);
         }
     }
 
-    fn render(&mut self, now: Instant) {
+    fn text_style(&self) -> TextStyle {
+        let direction = if self.args.rtl {
+            TextDirection::Rtl
+        } else {
+            TextDirection::Ltr
+        };
+        TextStyle {
+            direction,
+            overflow: self.args.text_overflow,
+            ..TextStyle::default()
+        }
+    }
+
+    /// The pen position render_str starts from, text_origin mirrored for RTL
+    fn text_start(&mut self) -> (f32, f32) {
+        let (x, y) = self.text_origin();
+        if self.args.rtl {
+            (1.0 - x, y)
+        } else {
+            (x, y)
+        }
+    }
+
+    /// Where the first line of text starts, in the screen texture's text space. With
+    /// --text-anchor this depends on the size of the text being typed towards, so the text stays
+    /// put while it types
+    fn text_origin(&mut self) -> (f32, f32) {
+        let origin = match self.args.text_anchor {
+            Some(anchor) => {
+                let s = &self.target_text;
+                anchored_text_origin(
+                    anchor,
+                    self.args.text_align,
+                    self.glyph_renderer.measure_str(s),
+                    s.matches('\n').count() + 1,
+                    self.glyph_renderer.line_height(),
+                )
+            }
+            None => TEXT_ORIGIN,
+        };
+
+        if self.args.pixel_perfect {
+            snap_to_pixel(origin, self.screen_tex_size, WINDOW_ASPECT)
+        } else {
+            origin
+        }
+    }
+
+    /// Sized to the text as it is this frame, so it grows while typing
+    fn render_text_panel(&mut self, x: f32, y: f32, style: TextStyle) {
+        let s = self.current_animation.as_str();
+        let Some(bounds) = self
+            .glyph_renderer
+            .text_bounds(s, x, y, visible_text_region(), style)
+        else {
+            return;
+        };
+
+        let (x, y, w, h) = text_panel_rect(bounds, self.args.text_panel_padding);
+        let [r, g, b] = self.args.text_panel_color;
+        self.cursor_renderer.render_colored(
+            x,
+            y,
+            w,
+            h,
+            WINDOW_ASPECT,
+            [r, g, b, self.args.text_panel_alpha],
+        );
+    }
+
+    /// Centered on --big-clock-pos. Glyphs reach GLYPH_ASCENT of a line above the baseline and
+    /// the rest below, so the baseline sits a quarter line under the center
+    fn render_big_clock(&mut self) {
+        let scale = self.args.big_clock_scale;
+        let width = self.glyph_renderer.measure_str(&self.big_clock_text) * scale;
+        let line_height = self.glyph_renderer.line_height() * scale;
+        let (x, y) = self.args.big_clock_pos;
+        let style = TextStyle {
+            no_wrap: true,
+            scale,
+            ..TextStyle::default()
+        };
+        self.glyph_renderer.render_str(
+            &self.big_clock_text,
+            x - width / 2.0,
+            y - line_height * (GLYPH_ASCENT - 0.5),
+            visible_text_region(),
+            WINDOW_ASPECT,
+            style,
+        );
+    }
+
+    fn render_audio_bars(&self) {
+        if self.audio_capture.is_none() {
+            return;
+        }
+
         unsafe {
-            let tex = self.render_light_depth();
-            let screen_tex = self.render_text_to_texture(now);
+            // Screen space overlay, ignore whatever the 3d scene left in the depth buffer
+            self.gl.disable(glow::DEPTH_TEST);
+            let slot_width = 1.0 / AUDIO_BARS as f32;
+            for (i, level) in self.audio_bars.iter().enumerate() {
+                self.cursor_renderer.render(
+                    i as f32 * slot_width,
+                    0.0,
+                    slot_width * 0.8,
+                    level * AUDIO_BAR_MAX_HEIGHT,
+                    1.0,
+                );
+            }
+            self.gl.enable(glow::DEPTH_TEST);
+        }
+    }
 
+    /// Opacity of the cover drawn over the scene while fading in
+    fn intro_fade_alpha(&self) -> f32 {
+        if self.args.intro_fade <= 0.0 {
+            return 0.0;
+        }
+        (1.0 - self.time / self.args.intro_fade).clamp(0.0, 1.0)
+    }
+
+    fn render_intro_fade(&self) {
+        let alpha = self.intro_fade_alpha();
+        if alpha <= 0.0 {
+            return;
+        }
+
+        let [r, g, b] = self.background;
+        unsafe {
+            self.gl.disable(glow::DEPTH_TEST);
+            self.cursor_renderer
+                .render_colored(0.0, 0.0, 1.0, 1.0, 1.0, [r, g, b, alpha]);
+            self.gl.enable(glow::DEPTH_TEST);
+        }
+    }
+
+    /// Whether anything visible changed since the last call to render
+    fn needs_render(&self) -> bool {
+        self.dirty
+    }
+
+    /// Counters from the last rendered frame
+    fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    /// The background, or nothing at all with --transparent
+    fn window_clear_color(&self) -> [f32; 4] {
+        if self.args.transparent {
+            [0.0; 4]
+        } else {
+            let [r, g, b] = self.background;
+            [r, g, b, 1.0]
+        }
+    }
+
+    /// Particles and the lit meshes, with screen_tex on the monitor. Everything --text-only skips
+    fn render_scene(&self, light_tex: Option<NativeTexture>, screen_tex: NativeTexture) {
+        if let Some(particle_renderer) = &self.particle_renderer {
+            particle_renderer.render();
+        }
+
+        self.mesh_renderer
+            .set_view_to_light_transform(&self.view_pos_to_light_pos());
+        self.mesh_renderer.set_light_dir(&self.light_dir);
+        self.mesh_renderer.set_light_color(&self.light_color);
+        self.mesh_renderer.set_ambient(&self.ambient);
+        if let Some(tex) = light_tex {
+            self.mesh_renderer.set_light_texture(tex);
+        }
+        self.render_objects(&self.view_matrix, Some(screen_tex));
+        self.render_debug_bounds();
+    }
+
+    fn render(&mut self) {
+        let start = Instant::now();
+        self.mesh_renderer.reset_counters();
+        self.glyph_renderer.reset_counters();
+        self.dirty = false;
+        self.rendered_text.clear();
+        self.rendered_text.push_str(self.current_animation.as_str());
+
+        unsafe {
+            let light_tex =
+                (self.args.shadows && !self.args.text_only).then(|| self.render_light_depth());
+            let screen_tex = self.render_text_to_texture();
+
+            // Flipping draws off screen first, then mirrors the blit to the window
+            let (width, height) = self.framebuffer_size;
+            let flip_fb = if self.args.flip_horizontal || self.args.flip_vertical {
+                if self.flip_target.as_ref().map(|t| t.size) != Some((width, height)) {
+                    // Free the old one before making its replacement
+                    self.flip_target = None;
+                    self.flip_target = Some(FlipTarget::new(self.gl, (width, height)).unwrap());
+                }
+                self.flip_target.as_ref().map(|t| t.fb)
+            } else {
+                None
+            };
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, flip_fb);
+
+            // The text pass leaves its own clear color behind, so set ours every frame
+            gl_util::begin_pass(self.gl, width, height);
+            let [r, g, b, a] = self.window_clear_color();
+            self.gl.clear_color(r, g, b, a);
             self.gl
                 .clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
-            self.gl
-                .viewport(0, 0, WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32);
 
-            self.mesh_renderer.set_camera_transform(&self.view_matrix);
-            self.mesh_renderer
-                .set_view_to_light_transform(&self.view_pos_to_light_pos());
-            self.mesh_renderer.set_light_dir(&self.light_dir);
-            self.mesh_renderer.set_light_texture(tex);
-            self.screen.tex = screen_tex;
-            self.render_objects();
+            if self.args.text_only {
+                self.gl.disable(glow::DEPTH_TEST);
+                let size = self.screen_tex_size;
+                gl_util::blit_texture_rows(
+                    self.gl,
+                    screen_tex,
+                    size,
+                    visible_text_rows(size, (width, height)),
+                    flip_fb,
+                    (width, height),
+                )
+                .unwrap();
+            } else {
+                self.render_scene(light_tex, screen_tex);
+            }
+            self.render_audio_bars();
+            self.render_intro_fade();
+
+            if let Some(fb) = flip_fb {
+                gl_util::blit_flipped(
+                    self.gl,
+                    fb,
+                    None,
+                    (width, height),
+                    self.args.flip_horizontal,
+                    self.args.flip_vertical,
+                );
+            }
 
-            self.gl.delete_texture(tex);
+            if let Some(tex) = light_tex {
+                self.gl.delete_texture(tex);
+            }
             self.gl.delete_texture(screen_tex);
         }
+
+        self.stats = FrameStats {
+            meshes: self.mesh_renderer.meshes_drawn(),
+            glyphs: self.glyph_renderer.glyphs_drawn(),
+            draw_calls: self.mesh_renderer.draw_calls() + self.glyph_renderer.draw_calls(),
+            frame_time: start.elapsed(),
+        };
     }
 }
 
@@ -410,75 +2488,53 @@ enum MainError {
     CreateGlyphRenderer(GlError),
     #[error("failed to create cursor renderer")]
     CreateCursorRenderer(GlError),
+    #[error("failed to create line renderer")]
+    CreateLineRenderer(GlError),
     #[error("failed to create screen text postprocessor")]
     CreateScreenTexPostProcessorError(GlError),
+    #[error("failed to create postprocess chain")]
+    CreatePostprocessChain(GlError),
     #[error("failed to create mesh renderer")]
     CreateMeshRenderer(GlError),
-    #[error("failed to load table obj")]
-    LoadTable(ObjParseError),
-    #[error("failed to load walls obj")]
-    LoadWalls(ObjParseError),
-    #[error("failed to load monitor obj")]
-    LoadMonitor(ObjParseError),
-    #[error("failed to load screen obj")]
-    LoadScreen(ObjParseError),
-    #[error("failed to upload table to gpu")]
-    UploadTable(UploadMeshError),
-    #[error("failed to upload walls to gpu")]
-    UploadWalls(UploadMeshError),
-    #[error("failed to upload monitor to gpu")]
-    UploadMonitor(UploadMeshError),
-    #[error("failed to upload screen to gpu")]
-    UploadScreen(UploadMeshError),
+    #[error("failed to create particle renderer")]
+    CreateParticleRenderer(GlError),
+    #[error("clip planes need 0 < near < far, got near {near} and far {far}")]
+    InvalidClipPlanes { near: f32, far: f32 },
+    #[error("failed to load scene")]
+    LoadScene(#[from] scene::SceneError),
+    #[error("failed to load {0}")]
+    LoadMesh(String, ObjParseError),
+    #[error("failed to load {0}")]
+    LoadTexture(String, texture::LoadTextureError),
+    #[error("failed to upload {0} to gpu")]
+    UploadMesh(String, UploadMeshError),
+    #[error("failed to open camera path")]
+    OpenCameraPath(std::io::Error),
+    #[error("failed to load camera path")]
+    LoadCameraPath(#[from] camera_path::CameraPathParseError),
+    #[error("failed to load window icon")]
+    LoadIcon(#[from] icon::IconError),
+    #[error("failed to load color lut")]
+    LoadLut(#[from] lut::LutError),
+    #[error("failed to start recording")]
+    StartRecording(#[from] recorder::RecorderError),
     #[error("failed to get character")]
     GetCharacter(#[from] glyph_cache::GetCharacterError),
 }
 
-fn load_texture_from_png<R: std::io::Read>(gl: &glow::Context, f: R) -> NativeTexture {
-    let mut png_reader = png::Decoder::new(f).read_info().unwrap();
-
-    let mut img_data = vec![0; png_reader.output_buffer_size()];
-    let img_info = png_reader.next_frame(&mut img_data).unwrap();
-
-    unsafe {
-        let tex = gl_util::create_tex_default_params(gl).unwrap();
-
-        let color_format = match img_info.color_type {
-            png::ColorType::Grayscale => glow::RED,
-            png::ColorType::Rgb => glow::RGB,
-            png::ColorType::Indexed => {
-                panic!("Indexed colors unsupported");
-            }
-            png::ColorType::GrayscaleAlpha => {
-                panic!("Grayscale alpha unsupported");
-            }
-            png::ColorType::Rgba => glow::RGBA,
-        };
-
-        let bit_depth = match img_info.bit_depth {
-            png::BitDepth::One | png::BitDepth::Two | png::BitDepth::Four => {
-                panic!("Unsupported bit depth: {:?}", img_info.bit_depth);
-            }
-            png::BitDepth::Eight => glow::UNSIGNED_BYTE,
-            png::BitDepth::Sixteen => glow::UNSIGNED_SHORT,
-        };
-
-        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
-        gl.tex_image_2d(
-            glow::TEXTURE_2D,
-            0,
-            glow::RGBA as i32,
-            img_info.width.try_into().unwrap(),
-            img_info.height.try_into().unwrap(),
-            0,
-            color_format,
-            bit_depth,
-            Some(&img_data),
-        );
-        gl.bind_texture(glow::TEXTURE_2D, None);
-
-        tex
-    }
+/// One line of --list-monitors output
+fn monitor_description(
+    index: usize,
+    name: Option<&str>,
+    pos: (i32, i32),
+    mode: Option<glfw::VidMode>,
+) -> String {
+    let name = name.unwrap_or("unnamed");
+    let mode = match mode {
+        Some(mode) => format!("{}x{} @ {}Hz", mode.width, mode.height, mode.refresh_rate),
+        None => "no video mode".to_string(),
+    };
+    format!("{index}: {name} at {},{}, {mode}", pos.0, pos.1)
 }
 
 fn main() -> Result<(), MainError> {
@@ -486,35 +2542,525 @@ fn main() -> Result<(), MainError> {
 
     let mut glfw = glfw::init(fail_on_errors!())?;
 
+    if args.list_monitors {
+        glfw.with_connected_monitors(|_, monitors| {
+            if monitors.is_empty() {
+                println!("No monitors connected");
+            }
+            for (i, monitor) in monitors.iter().enumerate() {
+                println!(
+                    "{}",
+                    monitor_description(
+                        i,
+                        monitor.get_name().as_deref(),
+                        monitor.get_pos(),
+                        monitor.get_video_mode()
+                    )
+                );
+            }
+        });
+        return Ok(());
+    }
+
+    glfw.window_hint(glfw::WindowHint::TransparentFramebuffer(args.transparent));
     let (mut window, events) = glfw
         .create_window(
             WINDOW_WIDTH,
             WINDOW_HEIGHT,
-            "Stream starting...",
+            &args.title,
             glfw::WindowMode::Windowed,
         )
         .ok_or(MainError::CreateGlfwWindow)?;
 
+    if let Some(path) = &args.icon {
+        window.set_icon_from_pixels(vec![icon::load(path)?]);
+    }
+
     window.make_current();
     window.set_key_polling(true);
+    window.set_framebuffer_size_polling(true);
+    window.set_content_scale_polling(true);
 
     const PIXEL_SIZE: u32 = 256;
-    let mut glyph_cache = GlyphCache::new(PIXEL_SIZE)?;
+    let mut glyph_cache = GlyphCache::new(PIXEL_SIZE, args.font.as_deref(), args.font_index)?;
     let gl = init_gl(&mut window);
 
-    let mesh_renderer = MeshRenderer::new(&gl).map_err(MainError::CreateMeshRenderer)?;
+    let mesh_renderer = MeshRenderer::new(&gl, args.hardware_pcf, args.shadows)
+        .map_err(MainError::CreateMeshRenderer)?;
+    mesh_renderer.set_soft_shadows(args.shadow_samples, args.light_size);
     let mut app = App::new(&gl, &args, &mut glyph_cache, &mesh_renderer)?;
+    app.reload_shaders();
+    let (framebuffer_width, framebuffer_height) = window.get_framebuffer_size();
+    app.set_framebuffer_size(framebuffer_width, framebuffer_height);
+    app.set_content_scale(window.get_content_scale().0);
+
+    // The video keeps the size the window started with
+    let record_size = (framebuffer_width as u32, framebuffer_height as u32);
+    let mut recorders = Vec::new();
+    if let Some(path) = &args.record {
+        recorders.push(recorder::Recorder::new(
+            path,
+            record_size.0,
+            record_size.1,
+            args.record_fps,
+            &args.ffmpeg_args,
+        )?);
+    }
+    if let Some(path) = &args.frame_pipe {
+        recorders.push(recorder::Recorder::to_pipe(
+            path,
+            record_size.0,
+            record_size.1,
+            args.record_fps,
+        ));
+    }
+
+    let mut last_title_second = None;
+    let mut last_stats_print = Instant::now();
+    // Steps start from the app's own clock so the first one is a whole step long
+    let mut fixed_timestep = args.fixed_timestep.map(|rate| {
+        timestep::FixedTimestep::new(Duration::from_secs_f64(1.0 / rate as f64), app.last_update)
+    });
+
+    let mut clock = timestep::PausableClock::default();
 
     while !window.should_close() {
-        let now = Instant::now();
-        app.update(now);
-        app.render(now);
+        let real_now = Instant::now();
+        let now = clock.now(real_now);
+
+        if args.title_countdown {
+            let wall_time = chrono::Local::now().time();
+            let second = wall_time.num_seconds_from_midnight();
+            if last_title_second != Some(second) {
+                window.set_title(&window_title(&args.title, app.remaining(wall_time, now)));
+                last_title_second = Some(second);
+            }
+        }
+
+        // Paused frames still render when something like a resize asks for it
+        match &mut fixed_timestep {
+            _ if clock.is_paused() => (),
+            Some(fixed_timestep) => {
+                for step in fixed_timestep.steps(now) {
+                    app.update(step);
+                }
+            }
+            None => app.update(now),
+        }
+        // The recording needs a fresh back buffer to read from every frame
+        if app.needs_render() || !recorders.is_empty() {
+            app.render();
+
+            // Read back at most once a frame, however many recorders want it
+            let mut pixels = None;
+            for recorder in &mut recorders {
+                // The video keeps running through pauses
+                let frames_due = recorder.frames_due(real_now);
+                if frames_due > 0 {
+                    let pixels = pixels.get_or_insert_with(|| unsafe {
+                        gl_util::read_framebuffer_rgba(&gl, record_size.0, record_size.1)
+                    });
+                    recorder.push_frame(pixels.clone(), frames_due);
+                }
+            }
+
+            window.swap_buffers();
 
-        window.swap_buffers();
+            if args.stats && real_now.duration_since(last_stats_print) >= STATS_INTERVAL {
+                println!("{}", app.stats());
+                last_stats_print = real_now;
+            }
+        } else {
+            std::thread::sleep(IDLE_SLEEP);
+        }
 
         glfw.poll_events();
-        for _ in glfw::flush_messages(&events) {}
+        for (_, event) in glfw::flush_messages(&events) {
+            match event {
+                glfw::WindowEvent::FramebufferSize(width, height) => {
+                    app.set_framebuffer_size(width, height)
+                }
+                glfw::WindowEvent::ContentScale(scale, _) => app.set_content_scale(scale),
+                glfw::WindowEvent::Key(glfw::Key::B, _, glfw::Action::Press, _) => {
+                    app.toggle_debug_bounds()
+                }
+                glfw::WindowEvent::Key(glfw::Key::P, _, glfw::Action::Press, _) => {
+                    clock.toggle_pause(Instant::now())
+                }
+                glfw::WindowEvent::Key(glfw::Key::R, _, glfw::Action::Press, _) => {
+                    app.reload_shaders()
+                }
+                glfw::WindowEvent::Key(glfw::Key::C, _, glfw::Action::Press, _) => {
+                    app.toggle_countdown_pause(now)
+                }
+                glfw::WindowEvent::Key(glfw::Key::G, _, glfw::Action::Press, _) => {
+                    app.cycle_background()
+                }
+                _ => (),
+            }
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frame_stats_display() {
+        let stats = FrameStats {
+            meshes: 12,
+            glyphs: 130,
+            draw_calls: 150,
+            frame_time: Duration::from_micros(1234),
+        };
+        assert_eq!(
+            stats.to_string(),
+            "meshes 12, glyphs 130, draw calls 150, frame 1.23ms"
+        );
+    }
+
+    #[test]
+    fn test_monitor_description() {
+        let mode = glfw::VidMode {
+            width: 2560,
+            height: 1440,
+            red_bits: 8,
+            green_bits: 8,
+            blue_bits: 8,
+            refresh_rate: 144,
+        };
+        assert_eq!(
+            monitor_description(1, Some("DP-1"), (1920, 0), Some(mode)),
+            "1: DP-1 at 1920,0, 2560x1440 @ 144Hz"
+        );
+        assert_eq!(
+            monitor_description(0, None, (0, 0), None),
+            "0: unnamed at 0,0, no video mode"
+        );
+    }
+
+    #[test]
+    fn test_camera_settle_progress() {
+        let secs = chrono::Duration::seconds;
+        assert_eq!(camera_settle_progress(secs(60), 10.0), 0.0);
+        assert_eq!(camera_settle_progress(secs(10), 10.0), 0.0);
+        assert!((camera_settle_progress(secs(5), 10.0) - 0.5).abs() < 0.001);
+        assert_eq!(camera_settle_progress(secs(0), 10.0), 1.0);
+
+        // Eases out of the spin rather than jumping into the blend
+        assert!(camera_settle_progress(secs(9), 10.0) < 0.1);
+    }
+
+    #[test]
+    fn test_remaining_until() {
+        let time = |h, m, s| NaiveTime::from_hms_opt(h, m, s).unwrap();
+
+        assert_eq!(
+            remaining_until(time(14, 30, 0), time(12, 15, 20)),
+            chrono::Duration::seconds(2 * 3600 + 14 * 60 + 40)
+        );
+        // Starting just after midnight
+        assert_eq!(
+            remaining_until(time(0, 10, 0), time(23, 50, 0)),
+            chrono::Duration::minutes(20)
+        );
+        // Already started
+        assert_eq!(
+            remaining_until(time(14, 0, 0), time(14, 5, 0)),
+            chrono::Duration::zero()
+        );
+        assert_eq!(
+            remaining_until(time(14, 0, 0), time(14, 0, 0)),
+            chrono::Duration::zero()
+        );
+    }
+
+    #[test]
+    fn test_time_remaining() {
+        let wall_time = NaiveTime::from_hms_opt(14, 0, 0).unwrap();
+        let start = Instant::now();
+
+        let at_time = StartClock::new(
+            StartAt::Time(NaiveTime::from_hms_opt(14, 1, 0).unwrap()),
+            start,
+        );
+        assert_eq!(
+            time_remaining(&at_time, wall_time, start + Duration::from_secs(30)),
+            chrono::Duration::minutes(1)
+        );
+
+        // Counts from when it was made, and stops at zero
+        let countdown = StartClock::new(StartAt::Countdown(Duration::from_secs(90)), start);
+        assert_eq!(
+            time_remaining(&countdown, wall_time, start + Duration::from_secs(30)),
+            chrono::Duration::minutes(1)
+        );
+        assert_eq!(
+            time_remaining(&countdown, wall_time, start + Duration::from_secs(300)),
+            chrono::Duration::zero()
+        );
+    }
+
+    #[test]
+    fn test_live_transition_cover() {
+        assert_eq!(live_transition_cover(0.0, 2.0), 0.0);
+        assert_eq!(live_transition_cover(0.5, 2.0), 0.5);
+        assert_eq!(live_transition_cover(1.0, 2.0), 1.0);
+        assert_eq!(live_transition_cover(1.5, 2.0), 0.5);
+        assert_eq!(live_transition_cover(5.0, 2.0), 0.0);
+        assert_eq!(live_transition_cover(0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_stream_starting_string() {
+        let start_time = NaiveTime::from_hms_opt(14, 30, 0).unwrap();
+        let now = NaiveTime::from_hms_opt(12, 15, 20).unwrap();
+
+        assert_eq!(
+            stream_starting_string(
+                "stream-start-screen",
+                start_time,
+                now,
+                "rust",
+                &lang::ENGLISH
+            ),
+            "\
+            $ ./stream-start-screen\n\
+            \n\
+            Today's topic: rust\n\
+            Stream starting at 14:30:00\n\
+            Current time: 12:15:20\n\
+            02:14:40 'till stream starts"
+        );
+    }
+
+    #[test]
+    fn test_stream_starting_string_localized() {
+        let start_time = NaiveTime::from_hms_opt(14, 30, 0).unwrap();
+        let now = NaiveTime::from_hms_opt(12, 15, 20).unwrap();
+
+        assert_eq!(
+            stream_starting_string("prog", start_time, now, "rust", lang::labels("de").unwrap()),
+            "\
+            $ ./prog\n\
+            \n\
+            Heutiges Thema: rust\n\
+            Stream beginnt um 14:30:00\n\
+            Aktuelle Zeit: 12:15:20\n\
+            02:14:40 bis der Stream beginnt"
+        );
+    }
+
+    #[test]
+    fn test_countdown_string() {
+        let now = NaiveTime::from_hms_opt(12, 15, 20).unwrap();
+
+        assert_eq!(
+            countdown_string(
+                "prog",
+                chrono::Duration::seconds(299),
+                now,
+                "rust",
+                &lang::ENGLISH
+            ),
+            "\
+            $ ./prog\n\
+            \n\
+            Today's topic: rust\n\
+            Current time: 12:15:20\n\
+            00:04:59 'till we're back"
+        );
+    }
+
+    #[test]
+    fn test_window_title() {
+        assert_eq!(
+            window_title("stream", chrono::Duration::seconds(299)),
+            "stream - Starting in 00:04:59"
+        );
+        assert_eq!(
+            window_title("stream", chrono::Duration::hours(3)),
+            "stream - Starting in 03:00:00"
+        );
+    }
+
+    #[test]
+    fn test_big_clock_string() {
+        let remaining = chrono::Duration::seconds(3 * 3600 + 25 * 60 + 7);
+        assert_eq!(big_clock_string(remaining, true), "03:25:07");
+
+        let hidden = big_clock_string(remaining, false);
+        assert_eq!(hidden, "03\x1b[8m:\x1b[28m25\x1b[8m:\x1b[28m07");
+        // Same characters, only the colons are hidden
+        let chars: Vec<(char, bool)> = glyph_renderer::SgrChars::new(&hidden)
+            .map(|(c, attrs)| (c, attrs.hidden))
+            .collect();
+        let expected: Vec<(char, bool)> = "03:25:07".chars().map(|c| (c, c == ':')).collect();
+        assert_eq!(chars, expected);
+    }
+
+    #[test]
+    fn test_draw_order() {
+        let key = |draw_order, transparent, depth| DrawKey {
+            draw_order,
+            transparent,
+            depth,
+        };
+        let keys = [
+            key(0, true, 1.0),
+            key(0, false, 5.0),
+            key(0, true, 3.0),
+            key(-1, false, 9.0),
+            key(0, false, 2.0),
+            key(1, false, 0.0),
+        ];
+        assert_eq!(draw_order(&keys), [3, 4, 1, 5, 2, 0]);
+    }
+
+    #[test]
+    fn test_snap_to_pixel() {
+        let aspect = 16.0 / 9.0;
+        let (x, y) = snap_to_pixel((0.05, 0.7), 1024, aspect);
+        assert!((x * 1024.0 - (x * 1024.0).round()).abs() < 0.001);
+        let pixel_y = ((y * 2.0 - 1.0) * aspect + 1.0) / 2.0 * 1024.0;
+        assert!((pixel_y - pixel_y.round()).abs() < 0.001);
+
+        // Never more than half a texel away
+        assert!((x - 0.05).abs() <= 0.5 / 1024.0);
+        assert!((y - 0.7).abs() <= 0.5 / 1024.0 / aspect + 0.0001);
+    }
+
+    #[test]
+    fn test_anchored_text_origin() {
+        let region = visible_text_region();
+        let lh = 0.1;
+
+        // The top left corner of the block is the top left of the visible screen
+        let (x, y) = anchored_text_origin((0.0, 0.0), TextAlign::TopLeft, 0.5, 2, lh);
+        assert!(x.abs() < 0.0001);
+        assert!((y - (region.y_max - lh * GLYPH_ASCENT)).abs() < 0.0001);
+
+        // Centered blocks split their size around the middle of the screen
+        let (x, y) = anchored_text_origin((0.5, 0.5), TextAlign::Center, 0.5, 2, lh);
+        let middle = (region.y_min + region.y_max) / 2.0;
+        assert!((x - 0.25).abs() < 0.0001);
+        assert!((y - (middle + lh - lh * GLYPH_ASCENT)).abs() < 0.0001);
+
+        // The bottom of the last line sits on the bottom edge
+        let (x, y) = anchored_text_origin((1.0, 1.0), TextAlign::BottomRight, 0.5, 2, lh);
+        assert!((x - 0.5).abs() < 0.0001);
+        let bounds = TextBounds {
+            x_min: x,
+            x_max: x + 0.5,
+            top_baseline: y,
+            bottom_baseline: y - lh,
+            line_height: lh,
+        };
+        let (_, bottom, _, _) = text_panel_rect(bounds, 0.0);
+        assert!((bottom - region.y_min).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_cursor_left() {
+        assert_eq!(cursor_left(0.5, 0.1, 0.0, TextDirection::Ltr), 0.5);
+        assert_eq!(cursor_left(0.5, 0.1, 0.0, TextDirection::Rtl), 0.4);
+
+        // Gaps and overlaps grow away from the text in either direction
+        assert!((cursor_left(0.5, 0.1, 0.5, TextDirection::Ltr) - 0.55).abs() < 0.0001);
+        assert!((cursor_left(0.5, 0.1, -1.0, TextDirection::Ltr) - 0.4).abs() < 0.0001);
+        assert!((cursor_left(0.5, 0.1, 0.5, TextDirection::Rtl) - 0.35).abs() < 0.0001);
+        assert!((cursor_left(0.5, 0.1, -1.0, TextDirection::Rtl) - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_visible_text_rows() {
+        let window = (WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32);
+        let (y0, y1) = visible_text_rows(1024, window);
+        // Centered, and as tall as the window is relative to its width
+        assert_eq!(y0 + y1, 1024);
+        assert!(((y1 - y0) as f32 - 1024.0 / WINDOW_ASPECT).abs() <= 1.0);
+        let region = visible_text_region();
+        assert_eq!(y0, (region.y_min * 1024.0).round() as i32);
+
+        // Follows the window when it is resized
+        assert_eq!(visible_text_rows(1024, (2000, 500)), (384, 640));
+        assert_eq!(visible_text_rows(1024, (500, 1000)), (0, 1024));
+    }
+
+    #[test]
+    fn test_text_panel_rect() {
+        let bounds = TextBounds {
+            x_min: 0.1,
+            x_max: 0.6,
+            top_baseline: 0.7,
+            bottom_baseline: 0.6,
+            line_height: 0.1,
+        };
+        let (x, y, w, h) = text_panel_rect(bounds, 0.02);
+        assert!((x - 0.08).abs() < 0.0001);
+        assert!((w - 0.54).abs() < 0.0001);
+        // A quarter line under the second baseline at 0.6, three quarters over the first
+        assert!((y - 0.555).abs() < 0.0001);
+        assert!((y + h - 0.795).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_parse_position() {
+        assert_eq!(parse_position("0.05,0.3").unwrap(), (0.05, 0.3));
+        assert_eq!(parse_position(" 1, -0.5").unwrap(), (1.0, -0.5));
+        assert!(matches!(
+            parse_position("0.5"),
+            Err(ParsePositionError::MissingComma)
+        ));
+        assert!(matches!(
+            parse_position("a,1"),
+            Err(ParsePositionError::InvalidCoordinate(_))
+        ));
+    }
+
+    #[test]
+    fn test_next_background_preset() {
+        assert_eq!(next_background_preset(BACKGROUND_COLOR).0, "black");
+        assert_eq!(next_background_preset([0.0, 1.0, 0.0]).0, "dark gray");
+        // A custom --bg-color starts the cycle from the top
+        assert_eq!(
+            next_background_preset([0.5, 0.5, 0.5]),
+            ("dark gray", BACKGROUND_COLOR)
+        );
+    }
+
+    #[test]
+    fn test_parse_color() {
+        let color = parse_color("#ff8000").unwrap();
+        assert_eq!(color, [1.0, 128.0 / 255.0, 0.0]);
+        assert_eq!(parse_color("000000").unwrap(), [0.0, 0.0, 0.0]);
+        assert!(matches!(
+            parse_color("#fff"),
+            Err(ParseColorError::InvalidLength)
+        ));
+        assert!(matches!(
+            parse_color("#gg0000"),
+            Err(ParseColorError::InvalidHex(_))
+        ));
+    }
+
+    #[test]
+    fn test_stream_starting_string_padding() {
+        let start_time = NaiveTime::from_hms_opt(9, 5, 7).unwrap();
+        let now = NaiveTime::from_hms_opt(9, 0, 1).unwrap();
+
+        assert_eq!(
+            stream_starting_string("prog", start_time, now, "padding", &lang::ENGLISH),
+            "\
+            $ ./prog\n\
+            \n\
+            Today's topic: padding\n\
+            Stream starting at 09:05:07\n\
+            Current time: 09:00:01\n\
+            00:05:06 'till stream starts"
+        );
+    }
+}
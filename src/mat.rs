@@ -26,6 +26,38 @@ impl Vec3 {
 
         [self.x() / l, self.y() / l, self.z() / l].into()
     }
+
+    pub fn distance(&self, other: &Vec3) -> f32 {
+        (*self - *other).length()
+    }
+
+    /// Cheaper than distance when only comparing
+    pub fn distance_squared(&self, other: &Vec3) -> f32 {
+        (*self - *other).0.iter().map(|v| v * v).sum()
+    }
+
+    /// Component-wise minimum
+    pub fn min(&self, other: &Vec3) -> Vec3 {
+        std::array::from_fn(|i| self.0[i].min(other.0[i])).into()
+    }
+
+    /// Component-wise maximum
+    pub fn max(&self, other: &Vec3) -> Vec3 {
+        std::array::from_fn(|i| self.0[i].max(other.0[i])).into()
+    }
+
+    /// Clamps each component between the matching components of lo and hi
+    pub fn clamp(&self, lo: &Vec3, hi: &Vec3) -> Vec3 {
+        self.max(lo).min(hi)
+    }
+
+    pub fn lerp(&self, other: Vec3, t: f32) -> Vec3 {
+        let mut ret = [0.0f32; 3];
+        for i in 0..3 {
+            ret[i] = self.0[i] + (other.0[i] - self.0[i]) * t;
+        }
+        ret.into()
+    }
 }
 
 impl From<[f32; 3]> for Vec3 {
@@ -54,7 +86,7 @@ pub enum Axis {
     Z,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Transform {
     pub arr: [[f32; 4]; 4],
 }
@@ -76,6 +108,14 @@ impl Transform {
         transform
     }
 
+    pub fn from_scale(s: Vec3) -> Transform {
+        Transform::scale(s.x(), s.y(), s.z())
+    }
+
+    pub fn from_uniform_scale(s: f32) -> Transform {
+        Transform::scale(s, s, s)
+    }
+
     pub fn identity() -> Transform {
         let arr = [
             [1.0, 0.0, 0.0, 0.0],
@@ -108,8 +148,8 @@ impl Transform {
             }
             Axis::Y => {
                 transform.arr[0][0] = cx;
-                transform.arr[0][2] = -sx;
-                transform.arr[2][0] = sx;
+                transform.arr[0][2] = sx;
+                transform.arr[2][0] = -sx;
                 transform.arr[2][2] = cx;
             }
             Axis::Z => {
@@ -122,6 +162,33 @@ impl Transform {
         transform
     }
 
+    pub fn from_quaternion(q: Quat) -> Transform {
+        let Quat { w, x, y, z } = q.normalized();
+        let arr = [
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+                0.0,
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+                0.0,
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+
+        Transform { arr }
+    }
+
     pub fn inverted(&self) -> Transform {
         // Stolen from
         // https://stackoverflow.com/questions/1148309/inverting-a-4x4-matrix
@@ -242,6 +309,36 @@ impl Transform {
         Transform { arr: out }
     }
 
+    /// Apply the transform to a point, assuming w = 1 and no perspective divide
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        let mut ret = [0.0f32; 3];
+        for y in 0..3 {
+            ret[y] = self.arr[y][0] * p.x()
+                + self.arr[y][1] * p.y()
+                + self.arr[y][2] * p.z()
+                + self.arr[y][3];
+        }
+        ret.into()
+    }
+
+    /// Apply the transform to a point with w = 1, including the perspective divide. Gives
+    /// normalized device coordinates for a projection
+    pub fn project_point(&self, p: Vec3) -> Vec3 {
+        let w = self.arr[3][0] * p.x()
+            + self.arr[3][1] * p.y()
+            + self.arr[3][2] * p.z()
+            + self.arr[3][3];
+        let p = self.transform_point(p);
+        [p.x() / w, p.y() / w, p.z() / w].into()
+    }
+
+    /// Largest scale factor applied to any of the x/y/z axis
+    pub fn max_axis_scale(&self) -> f32 {
+        (0..3)
+            .map(|x| Vec3::from([self.arr[0][x], self.arr[1][x], self.arr[2][x]]).length())
+            .fold(0.0, f32::max)
+    }
+
     pub fn perspective(fov: f32, near: f32, far: f32) -> Transform {
         // Perspective is applied by taking the fov in each dimension and splitting it into a right
         // angle triangle.
@@ -320,9 +417,17 @@ impl Transform {
         Transform { arr }
     }
 
+    /// Alias for look_at_lh, the convention the rest of the scene uses
     pub fn look_at(eye: Vec3, center: Vec3, up: Vec3) -> Transform {
-        let mut transform = Transform::identity();
+        Transform::look_at_lh(eye, center, up)
+    }
 
+    /// Places a camera at eye facing center, returning the camera to world transform. Invert it
+    /// to get a view matrix.
+    ///
+    /// Left handed, the camera looks down +z with +x to its right and +y up. This is what
+    /// perspective expects, it divides by +z
+    pub fn look_at_lh(eye: Vec3, center: Vec3, up: Vec3) -> Transform {
         // We want to look at the center from our eye, so that should be our new Z axis
         let new_forward = (center - eye).normalized();
         // The new sideways is the axis perpendicular to both up and forward
@@ -330,41 +435,43 @@ impl Transform {
         // Adjust our up position to be orthogonal to the other two axis
         let new_up = Vec3([0.0, 0.0, 0.0]) - cross(new_sideways, new_forward);
 
-        // Now just apply those by sticking them in the output transform. Imagine putting [1, 0,
-        // 0], [0, 1, 0], [0, 0, 1], and it should make sense
-        transform.arr[0][0] = new_sideways.x();
-        transform.arr[1][0] = new_sideways.y();
-        transform.arr[2][0] = new_sideways.z();
+        Transform::from_axes(new_sideways, new_up, new_forward, eye)
+    }
 
-        transform.arr[0][1] = new_up.x();
-        transform.arr[1][1] = new_up.y();
-        transform.arr[2][1] = new_up.z();
+    /// Like look_at_lh but right handed, the camera looks down -z with +x to its right and +y up
+    /// as in gluLookAt. perspective divides by +z, so this needs a projection that flips z
+    pub fn look_at_rh(eye: Vec3, center: Vec3, up: Vec3) -> Transform {
+        let forward = (center - eye).normalized();
+        let sideways = cross(forward, up.normalized()).normalized();
+        let new_up = cross(sideways, forward);
 
-        transform.arr[0][2] = new_forward.x();
-        transform.arr[1][2] = new_forward.y();
-        transform.arr[2][2] = new_forward.z();
+        Transform::from_axes(sideways, new_up, Vec3([0.0, 0.0, 0.0]) - forward, eye)
+    }
 
-        // And set the position as well
-        transform.arr[0][3] = eye.x();
-        transform.arr[1][3] = eye.y();
-        transform.arr[2][3] = eye.z();
+    /// Maps the unit axes onto x, y and z and the origin onto origin
+    fn from_axes(x: Vec3, y: Vec3, z: Vec3, origin: Vec3) -> Transform {
+        let mut transform = Transform::identity();
 
-        //let mut translation = Transform::identity();
-        //translation.arr[2][3] = length(center-eye);
+        // Now just apply those by sticking them in the output transform. Imagine putting [1, 0,
+        // 0], [0, 1, 0], [0, 0, 1], and it should make sense
+        for (col, axis) in [x, y, z, origin].into_iter().enumerate() {
+            transform.arr[0][col] = axis.x();
+            transform.arr[1][col] = axis.y();
+            transform.arr[2][col] = axis.z();
+        }
 
         transform
     }
 }
 
-impl std::ops::Mul<&Self> for Transform {
-    type Output = Self;
+impl std::ops::Mul<&Transform> for &Transform {
+    type Output = Transform;
 
-    fn mul(self, rhs: &Self) -> Self {
+    fn mul(self, rhs: &Transform) -> Transform {
         let mut output = Transform::new();
         for y in 0..4 {
             for x in 0..4 {
                 for i in 0..4 {
-                    // FIXME: Duplciated
                     output.arr[y][x] += self.arr[y][i] * rhs.arr[i][x]
                 }
             }
@@ -372,10 +479,165 @@ impl std::ops::Mul<&Self> for Transform {
         output
     }
 }
+impl std::ops::Mul<&Self> for Transform {
+    type Output = Self;
+    fn mul(self, rhs: &Self) -> Self {
+        &self * rhs
+    }
+}
 impl std::ops::Mul<Self> for Transform {
     type Output = Self;
     fn mul(self, rhs: Self) -> Self {
-        self.mul(&rhs)
+        &self * &rhs
+    }
+}
+
+/// a *= b is a = a * b, so b applies first
+impl std::ops::MulAssign<&Self> for Transform {
+    fn mul_assign(&mut self, rhs: &Self) {
+        *self = &*self * rhs;
+    }
+}
+impl std::ops::MulAssign<Self> for Transform {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self *= &rhs;
+    }
+}
+
+/// Rotation stored as w + xi + yj + zk, nicer than chained axis angles when blending orientations
+#[derive(Debug, Copy, Clone)]
+pub struct Quat {
+    w: f32,
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+impl Quat {
+    pub fn identity() -> Quat {
+        Quat {
+            w: 1.0,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        }
+    }
+
+    pub fn from_axis_angle(angle: f32, axis: Vec3) -> Quat {
+        let axis = axis.normalized();
+        let s = f32::sin(angle / 2.0);
+        Quat {
+            w: f32::cos(angle / 2.0),
+            x: axis.x() * s,
+            y: axis.y() * s,
+            z: axis.z() * s,
+        }
+    }
+
+    /// Rotation part of the transform, which is expected to have no scale or skew
+    pub fn from_rotation(transform: &Transform) -> Quat {
+        let m = &transform.arr;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        // Pick whichever component is largest to divide by to keep things numerically stable
+        if trace > 0.0 {
+            let s = f32::sqrt(trace + 1.0) * 2.0;
+            Quat {
+                w: 0.25 * s,
+                x: (m[2][1] - m[1][2]) / s,
+                y: (m[0][2] - m[2][0]) / s,
+                z: (m[1][0] - m[0][1]) / s,
+            }
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = f32::sqrt(1.0 + m[0][0] - m[1][1] - m[2][2]) * 2.0;
+            Quat {
+                w: (m[2][1] - m[1][2]) / s,
+                x: 0.25 * s,
+                y: (m[0][1] + m[1][0]) / s,
+                z: (m[0][2] + m[2][0]) / s,
+            }
+        } else if m[1][1] > m[2][2] {
+            let s = f32::sqrt(1.0 + m[1][1] - m[0][0] - m[2][2]) * 2.0;
+            Quat {
+                w: (m[0][2] - m[2][0]) / s,
+                x: (m[0][1] + m[1][0]) / s,
+                y: 0.25 * s,
+                z: (m[1][2] + m[2][1]) / s,
+            }
+        } else {
+            let s = f32::sqrt(1.0 + m[2][2] - m[0][0] - m[1][1]) * 2.0;
+            Quat {
+                w: (m[1][0] - m[0][1]) / s,
+                x: (m[0][2] + m[2][0]) / s,
+                y: (m[1][2] + m[2][1]) / s,
+                z: 0.25 * s,
+            }
+        }
+    }
+
+    fn dot(&self, other: &Quat) -> f32 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn normalized(&self) -> Quat {
+        let l = f32::sqrt(self.dot(self));
+        Quat {
+            w: self.w / l,
+            x: self.x / l,
+            y: self.y / l,
+            z: self.z / l,
+        }
+    }
+
+    /// Interpolate at constant angular speed from self (t = 0) to other (t = 1)
+    pub fn slerp(&self, other: &Quat, t: f32) -> Quat {
+        let mut other = *other;
+        let mut cos_theta = self.dot(&other);
+
+        // q and -q are the same rotation, go the short way around
+        if cos_theta < 0.0 {
+            other = Quat {
+                w: -other.w,
+                x: -other.x,
+                y: -other.y,
+                z: -other.z,
+            };
+            cos_theta = -cos_theta;
+        }
+
+        // sin(theta) gets too small to divide by when the two are nearly the same, a plain lerp
+        // is indistinguishable there
+        let (a, b) = if cos_theta > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = f32::acos(cos_theta);
+            let sin_theta = f32::sin(theta);
+            (
+                f32::sin((1.0 - t) * theta) / sin_theta,
+                f32::sin(t * theta) / sin_theta,
+            )
+        };
+
+        Quat {
+            w: self.w * a + other.w * b,
+            x: self.x * a + other.x * b,
+            y: self.y * a + other.y * b,
+            z: self.z * a + other.z * b,
+        }
+        .normalized()
+    }
+}
+
+impl std::ops::Mul for Quat {
+    type Output = Quat;
+
+    fn mul(self, rhs: Quat) -> Quat {
+        Quat {
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        }
     }
 }
 
@@ -392,6 +654,8 @@ pub fn cross(a: Vec3, b: Vec3) -> Vec3 {
 mod test {
     use super::*;
 
+    use crate::test_util::{assert_near, assert_transforms_eq};
+
     #[test]
     fn test_cross() {
         let expected = [-3., 6., -3.];
@@ -403,6 +667,156 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_distance() {
+        let a = Vec3::from([1.0, 1.0, 1.0]);
+        let b = Vec3::from([4.0, 5.0, 1.0]);
+        assert_eq!(a.distance(&b), 5.0);
+        assert_eq!(a.distance_squared(&b), 25.0);
+    }
+
+    #[test]
+    fn test_min_max_clamp() {
+        let a = Vec3::from([1.0, -2.0, 3.0]);
+        let b = Vec3::from([0.0, 4.0, 3.5]);
+        assert_eq!(a.min(&b).0, [0.0, -2.0, 3.0]);
+        assert_eq!(a.max(&b).0, [1.0, 4.0, 3.5]);
+
+        let lo = Vec3::from([0.0; 3]);
+        let hi = Vec3::from([2.0; 3]);
+        assert_eq!(a.clamp(&lo, &hi).0, [1.0, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn test_from_scale() {
+        assert_eq!(
+            Transform::from_scale([2.0, 3.0, 4.0].into()).arr,
+            Transform::scale(2.0, 3.0, 4.0).arr
+        );
+        assert_eq!(
+            Transform::from_uniform_scale(1.5).arr,
+            Transform::scale(1.5, 1.5, 1.5).arr
+        );
+    }
+
+    #[test]
+    fn test_project_point() {
+        let perspective = Transform::perspective(std::f32::consts::FRAC_PI_2, 0.1, 10.0);
+        let near = perspective.project_point([0.0, 0.0, 1.0].into());
+        let far = perspective.project_point([0.0, 0.0, 5.0].into());
+        // Further away goes deeper and closer to the center
+        assert!(far.z() > near.z());
+        let edge = |z: f32| perspective.project_point([1.0, 0.0, z].into()).x();
+        assert!(edge(5.0) < edge(1.0));
+
+        let p = Transform::from_translation(1.0, 2.0, 3.0).project_point([1.0, 1.0, 1.0].into());
+        assert_eq!([p.x(), p.y(), p.z()], [2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_quat_matches_axis_angle() {
+        let angle = std::f32::consts::PI / 2.0;
+        for (axis, v) in [
+            (Axis::X, [1.0, 0.0, 0.0]),
+            (Axis::Y, [0.0, 1.0, 0.0]),
+            (Axis::Z, [0.0, 0.0, 1.0]),
+        ] {
+            let q = Quat::from_axis_angle(angle, v.into());
+            assert_transforms_eq(
+                &Transform::from_quaternion(q),
+                &Transform::from_axis_angle(angle, axis),
+            );
+        }
+    }
+
+    #[test]
+    fn test_quat_mul_composes() {
+        let y: Vec3 = [0.0, 1.0, 0.0].into();
+        let q = Quat::from_axis_angle(0.3, y) * Quat::from_axis_angle(0.5, y);
+        assert_transforms_eq(
+            &Transform::from_quaternion(q),
+            &Transform::from_axis_angle(0.8, Axis::Y),
+        );
+    }
+
+    #[test]
+    fn test_quat_from_rotation_round_trip() {
+        for angle in [0.3, 2.0, 3.1] {
+            let axis: Vec3 = [0.2, -1.0, 0.5].into();
+            let expected = Transform::from_quaternion(Quat::from_axis_angle(angle, axis));
+            let q = Quat::from_rotation(&expected);
+            assert_transforms_eq(&Transform::from_quaternion(q), &expected);
+        }
+    }
+
+    #[test]
+    fn test_slerp() {
+        let a = Quat::from_axis_angle(0.0, [0.0, 1.0, 0.0].into());
+        let b = Quat::from_axis_angle(std::f32::consts::PI / 2.0, [0.0, 1.0, 0.0].into());
+
+        assert_transforms_eq(
+            &Transform::from_quaternion(a.slerp(&b, 0.0)),
+            &Transform::from_quaternion(a),
+        );
+        assert_transforms_eq(
+            &Transform::from_quaternion(a.slerp(&b, 1.0)),
+            &Transform::from_quaternion(b),
+        );
+        assert_transforms_eq(
+            &Transform::from_quaternion(a.slerp(&b, 0.5)),
+            &Transform::from_axis_angle(std::f32::consts::PI / 4.0, Axis::Y),
+        );
+    }
+
+    #[test]
+    fn test_look_at_lh_view_space() {
+        let up = [0.0, 1.0, 0.0].into();
+        let view = Transform::look_at_lh([0.0, 0.0, -5.0].into(), [0.0; 3].into(), up).inverted();
+        // In front of the camera is +z, right is +x
+        assert_near(view.transform_point([0.0; 3].into()), [0.0, 0.0, 5.0]);
+        assert_near(
+            view.transform_point([1.0, 2.0, 0.0].into()),
+            [1.0, 2.0, 5.0],
+        );
+
+        // Looking down -x from the side, world +z ends up on the camera's right
+        let view = Transform::look_at_lh([5.0, 0.0, 0.0].into(), [0.0; 3].into(), up).inverted();
+        assert_near(
+            view.transform_point([0.0, 0.0, 1.0].into()),
+            [1.0, 0.0, 5.0],
+        );
+    }
+
+    #[test]
+    fn test_look_at_rh_view_space() {
+        let up = [0.0, 1.0, 0.0].into();
+        let view = Transform::look_at_rh([0.0, 0.0, 5.0].into(), [0.0; 3].into(), up).inverted();
+        // In front of the camera is -z, right is still +x
+        assert_near(view.transform_point([0.0; 3].into()), [0.0, 0.0, -5.0]);
+        assert_near(
+            view.transform_point([1.0, 2.0, 0.0].into()),
+            [1.0, 2.0, -5.0],
+        );
+    }
+
+    #[test]
+    fn test_axis_angle_right_handed() {
+        // A quarter turn about each axis carries the next axis onto the one after, the Y case
+        // used to send z to -x
+        let quarter = std::f32::consts::FRAC_PI_2;
+        let cases = [
+            (Axis::X, [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+            (Axis::Y, [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]),
+            (Axis::Z, [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ];
+        for (axis, from, to) in cases {
+            let rotated = Transform::from_axis_angle(quarter, axis).transform_point(from.into());
+            for (a, b) in rotated.0.iter().zip(to) {
+                assert!((a - b).abs() < 0.001);
+            }
+        }
+    }
+
     #[test]
     fn test_simple_mul() {
         let a = Transform {
@@ -421,7 +835,12 @@ mod test {
                 [4.0f32, 5.0f32, 6.0f32, 7.0f32],
             ],
         };
-        let c = a * b;
+        let c = &a * &b;
+        assert_eq!(c.arr, (a.clone() * b.clone()).arr);
+
+        let mut assigned = a;
+        assigned *= &b;
+        assert_eq!(assigned.arr, c.arr);
 
         let expected: [[f32; 4]; 4] = [
             [60.0f32, 40.0f32, 50.0f32, 60.0f32],
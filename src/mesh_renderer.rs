@@ -1,7 +1,10 @@
+use std::cell::{Cell, RefCell};
+
 use glow::{HasContext, NativeBuffer, NativeProgram, NativeTexture, NativeVertexArray};
 
 use thiserror::Error;
 
+use crate::frustum::{BoundingBox, BoundingSphere};
 use crate::mat::{Transform, Vec3};
 use crate::obj_parser::{Mesh, VertData};
 use crate::{gl_util, GlError};
@@ -11,8 +14,12 @@ pub struct GpuMesh<'a> {
     vao: NativeVertexArray,
     vbo: NativeBuffer,
     ebo: NativeBuffer,
-    // NOTE: Not owned, do not free
-    pub tex: NativeTexture,
+    // NOTE: Not owned, do not free. None draws the untextured color, see
+    // MeshRenderer::set_untextured_color
+    pub tex: Option<NativeTexture>,
+    /// Model space bounds
+    pub bounds: BoundingSphere,
+    pub bounding_box: BoundingBox,
     num_elements: i32,
 }
 
@@ -36,28 +43,106 @@ pub enum UploadMeshError {
     Ebo(GlError),
 }
 
-pub struct MeshRenderer<'a> {
+const VERTEX_SOURCE: &str = include_str!("glsl/3d_vertex.glsl");
+const FRAGMENT_SOURCE: &str = include_str!("glsl/3d_fragment.glsl");
+
+/// Defines have to come after the #version line, None if there isn't one
+fn with_defines(source: &str, hardware_pcf: bool, shadows: bool) -> Option<String> {
+    let mut defines = String::new();
+    if hardware_pcf {
+        defines.push_str("#define HARDWARE_PCF\n");
+    }
+    if !shadows {
+        defines.push_str("#define NO_SHADOWS\n");
+    }
+
+    let (version, rest) = source.split_once('\n')?;
+    if !version.trim_start().starts_with("#version") {
+        return None;
+    }
+    Some(format!("{version}\n{defines}{rest}"))
+}
+
+/// Most taps 3d_fragment.glsl takes for soft shadows, the size of its POISSON_DISK
+pub const MAX_SHADOW_SAMPLES: u32 = 16;
+
+/// The program and its uniforms, swapped out together when the fragment shader is reloaded
+struct MeshShader {
     program: NativeProgram,
-    vert_loc: Option<u32>,
-    uv_loc: Option<u32>,
-    norm_loc: Option<u32>,
     model_loc: Option<<glow::Context as HasContext>::UniformLocation>,
     view_loc: Option<<glow::Context as HasContext>::UniformLocation>,
     view_to_light_loc: Option<<glow::Context as HasContext>::UniformLocation>,
     light_dir_loc: Option<<glow::Context as HasContext>::UniformLocation>,
     light_color_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    ambient_loc: Option<<glow::Context as HasContext>::UniformLocation>,
     light_tex_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    tint_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    alpha_cutoff_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    untextured_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    untextured_color_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    emissive_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    shadow_samples_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    light_size_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    instanced_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+}
+
+impl MeshShader {
+    unsafe fn new(gl: &glow::Context, program: NativeProgram) -> MeshShader {
+        let uniform = |name| gl.get_uniform_location(program, name);
+        MeshShader {
+            program,
+            model_loc: uniform("model"),
+            view_loc: uniform("view"),
+            view_to_light_loc: uniform("view_pos_to_light_pos"),
+            light_dir_loc: uniform("light_dir"),
+            light_color_loc: uniform("light_color"),
+            ambient_loc: uniform("ambient"),
+            light_tex_loc: uniform("light_tex"),
+            tint_loc: uniform("tint"),
+            alpha_cutoff_loc: uniform("alpha_cutoff"),
+            untextured_loc: uniform("untextured"),
+            untextured_color_loc: uniform("untextured_color"),
+            emissive_loc: uniform("emissive"),
+            shadow_samples_loc: uniform("shadow_samples"),
+            light_size_loc: uniform("light_size"),
+            instanced_loc: uniform("instanced"),
+        }
+    }
+}
+
+/// Lit and shadowed meshes. Relies on the depth test from gl_util::begin_pass, the light
+/// texture stays bound to unit 1 between draws
+pub struct MeshRenderer<'a> {
+    shader: RefCell<MeshShader>,
+    vert_loc: Option<u32>,
+    uv_loc: Option<u32>,
+    norm_loc: Option<u32>,
+    color_loc: Option<u32>,
+    instance_model_loc: Option<u32>,
+    instance_vbo: NativeBuffer,
+    hardware_pcf: bool,
+    shadows: bool,
+    // Uniform values that have to survive a shader reload
+    soft_shadows: Cell<(u32, f32)>,
+    tint: Cell<([f32; 3], f32)>,
+    draw_calls: Cell<usize>,
+    meshes_drawn: Cell<usize>,
     gl: &'a glow::Context,
 }
 
 impl<'a> MeshRenderer<'a> {
-    pub fn new(gl: &'a glow::Context) -> Result<MeshRenderer<'a>, GlError> {
+    /// With hardware_pcf the light texture must have depth comparison enabled, see
+    /// gl_util::enable_depth_compare. Without shadows everything is lit and no light texture
+    /// is needed
+    pub fn new(
+        gl: &'a glow::Context,
+        hardware_pcf: bool,
+        shadows: bool,
+    ) -> Result<MeshRenderer<'a>, GlError> {
         unsafe {
-            let program = gl_util::compile_program(
-                gl,
-                include_str!("glsl/3d_vertex.glsl"),
-                include_str!("glsl/3d_fragment.glsl"),
-            );
+            let fragment_source = with_defines(FRAGMENT_SOURCE, hardware_pcf, shadows)
+                .expect("shader should start with a version line");
+            let program = gl_util::compile_program(gl, VERTEX_SOURCE, &fragment_source)?;
 
             let vert_loc = gl.get_attrib_location(program, "in_vert");
 
@@ -65,35 +150,36 @@ impl<'a> MeshRenderer<'a> {
 
             let norm_loc = gl.get_attrib_location(program, "in_normal");
 
-            let model_loc = gl.get_uniform_location(program, "model");
-
-            let view_loc = gl.get_uniform_location(program, "view");
-
-            let light_dir_loc = gl.get_uniform_location(program, "light_dir");
+            let color_loc = gl.get_attrib_location(program, "in_color");
 
-            let light_color_loc = gl.get_uniform_location(program, "light_color");
+            let instance_model_loc = gl.get_attrib_location(program, "in_model");
 
-            let view_to_light_loc = gl.get_uniform_location(program, "view_pos_to_light_pos");
-
-            let light_tex_loc = gl.get_uniform_location(program, "light_tex");
+            let instance_vbo = gl.create_buffer().map_err(GlError)?;
 
             Ok(MeshRenderer {
-                program,
+                shader: RefCell::new(MeshShader::new(gl, program)),
                 vert_loc,
-                model_loc,
-                view_loc,
-                light_dir_loc,
-                light_color_loc,
-                view_to_light_loc,
-                light_tex_loc,
                 uv_loc,
                 norm_loc,
+                color_loc,
+                instance_model_loc,
+                instance_vbo,
+                hardware_pcf,
+                shadows,
+                soft_shadows: Cell::new((1, 0.0)),
+                tint: Cell::new(([1.0; 3], 1.0)),
+                draw_calls: Cell::new(0),
+                meshes_drawn: Cell::new(0),
                 gl,
             })
         }
     }
 
-    pub fn upload_mesh(&self, mesh: &Mesh, tex: NativeTexture) -> Result<GpuMesh, UploadMeshError> {
+    pub fn upload_mesh(
+        &self,
+        mesh: &Mesh,
+        tex: Option<NativeTexture>,
+    ) -> Result<GpuMesh<'a>, UploadMeshError> {
         unsafe {
             let gl = self.gl;
 
@@ -126,7 +212,7 @@ impl<'a> MeshRenderer<'a> {
             );
 
             const STRIDE: i32 = std::mem::size_of::<VertData>() as i32;
-            assert_eq!(STRIDE as usize, 9 * std::mem::size_of::<f32>());
+            assert_eq!(STRIDE as usize, 12 * std::mem::size_of::<f32>());
 
             if let Some(vert_loc) = &self.vert_loc {
                 gl.vertex_attrib_pointer_f32(
@@ -137,7 +223,7 @@ impl<'a> MeshRenderer<'a> {
                     STRIDE,
                     VertData::vert_offset(),
                 );
-                gl.enable_vertex_attrib_array(0);
+                gl.enable_vertex_attrib_array(*vert_loc);
             }
 
             if let Some(uv_loc) = &self.uv_loc {
@@ -149,7 +235,7 @@ impl<'a> MeshRenderer<'a> {
                     STRIDE,
                     VertData::uv_offset(),
                 );
-                gl.enable_vertex_attrib_array(1);
+                gl.enable_vertex_attrib_array(*uv_loc);
             }
 
             if let Some(norm_loc) = &self.norm_loc {
@@ -161,10 +247,23 @@ impl<'a> MeshRenderer<'a> {
                     STRIDE,
                     VertData::normal_offset(),
                 );
-                gl.enable_vertex_attrib_array(2);
+                gl.enable_vertex_attrib_array(*norm_loc);
+            }
+
+            if let Some(color_loc) = &self.color_loc {
+                gl.vertex_attrib_pointer_f32(
+                    *color_loc,
+                    3,
+                    glow::FLOAT,
+                    false,
+                    STRIDE,
+                    VertData::color_offset(),
+                );
+                gl.enable_vertex_attrib_array(*color_loc);
             }
 
             let num_elements = mesh.faces.len() * mesh.faces[0].len();
+            let (bounds_min, bounds_max) = mesh.bounding_box();
 
             gl.bind_vertex_array(None);
             gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
@@ -176,16 +275,22 @@ impl<'a> MeshRenderer<'a> {
                 vbo,
                 ebo,
                 tex,
+                bounds: BoundingSphere::from_bounding_box(bounds_min, bounds_max),
+                bounding_box: BoundingBox {
+                    min: bounds_min,
+                    max: bounds_max,
+                },
                 num_elements: num_elements.try_into().expect("Too many elements"),
             })
         }
     }
 
     pub fn set_camera_transform(&self, transform: &Transform) {
+        let shader = self.shader.borrow();
         unsafe {
-            self.gl.use_program(Some(self.program));
+            self.gl.use_program(Some(shader.program));
             self.gl.uniform_matrix_4_f32_slice(
-                self.view_loc.as_ref(),
+                shader.view_loc.as_ref(),
                 true,
                 std::slice::from_raw_parts(transform.arr[0].as_ptr(), 16),
             );
@@ -194,10 +299,11 @@ impl<'a> MeshRenderer<'a> {
     }
 
     pub fn set_view_to_light_transform(&self, transform: &Transform) {
+        let shader = self.shader.borrow();
         unsafe {
-            self.gl.use_program(Some(self.program));
+            self.gl.use_program(Some(shader.program));
             self.gl.uniform_matrix_4_f32_slice(
-                self.view_to_light_loc.as_ref(),
+                shader.view_to_light_loc.as_ref(),
                 true,
                 std::slice::from_raw_parts(transform.arr[0].as_ptr(), 16),
             );
@@ -205,24 +311,31 @@ impl<'a> MeshRenderer<'a> {
         }
     }
 
+    pub fn hardware_pcf(&self) -> bool {
+        self.hardware_pcf
+    }
+
     pub fn set_light_texture(&self, tex: NativeTexture) {
+        let shader = self.shader.borrow();
         unsafe {
-            self.gl.use_program(Some(self.program));
-            self.gl.uniform_1_i32(self.light_tex_loc.as_ref(), 1);
+            self.gl.use_program(Some(shader.program));
+            self.gl.uniform_1_i32(shader.light_tex_loc.as_ref(), 1);
             self.gl.active_texture(glow::TEXTURE1);
             self.gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            self.gl.active_texture(glow::TEXTURE0);
             self.gl.use_program(None);
         }
     }
 
     pub fn set_light_dir(&self, dir: &Vec3) {
+        let shader = self.shader.borrow();
         unsafe {
-            self.gl.use_program(Some(self.program));
+            self.gl.use_program(Some(shader.program));
 
             let normalized = dir.normalized();
 
             self.gl.uniform_3_f32(
-                self.light_dir_loc.as_ref(),
+                shader.light_dir_loc.as_ref(),
                 normalized.x(),
                 normalized.y(),
                 normalized.z(),
@@ -232,37 +345,316 @@ impl<'a> MeshRenderer<'a> {
     }
 
     pub fn set_light_color(&self, color: &[f32; 3]) {
+        let shader = self.shader.borrow();
+        unsafe {
+            self.gl.use_program(Some(shader.program));
+
+            self.gl.uniform_3_f32(
+                shader.light_color_loc.as_ref(),
+                color[0],
+                color[1],
+                color[2],
+            );
+            self.gl.use_program(None);
+        }
+    }
+
+    /// Light added to every surface regardless of direction or shadow, so shadowed areas keep
+    /// some detail. Black (the default) leaves them unlit
+    pub fn set_ambient(&self, color: &[f32; 3]) {
+        let shader = self.shader.borrow();
         unsafe {
-            self.gl.use_program(Some(self.program));
+            self.gl.use_program(Some(shader.program));
 
             self.gl
-                .uniform_3_f32(self.light_color_loc.as_ref(), color[0], color[1], color[2]);
+                .uniform_3_f32(shader.ambient_loc.as_ref(), color[0], color[1], color[2]);
+            self.gl.use_program(None);
+        }
+    }
+
+    /// Averages samples shadow map taps spread over light_size shadow map texels, so shadow
+    /// edges blur like under a light with some area. Every tap is a texture read for every
+    /// fragment drawn, so the shading cost grows linearly with samples. 1 (the default) is a
+    /// single hard edged tap, anything above MAX_SHADOW_SAMPLES is clamped
+    pub fn set_soft_shadows(&self, samples: u32, light_size: f32) {
+        self.soft_shadows.set((samples, light_size));
+        let shader = self.shader.borrow();
+        unsafe {
+            self.gl.use_program(Some(shader.program));
+
+            self.gl
+                .uniform_1_i32(shader.shadow_samples_loc.as_ref(), samples as i32);
+            self.gl
+                .uniform_1_f32(shader.light_size_loc.as_ref(), light_size);
+            self.gl.use_program(None);
+        }
+    }
+
+    /// Multiplies the lit color of everything rendered after this, white with an alpha of 1 (the
+    /// default) changes nothing. Blended meshes only come out right when drawn back to front,
+    /// sorting them is up to the caller
+    pub fn set_tint(&self, color: &[f32; 3], alpha: f32) {
+        self.tint.set((*color, alpha));
+        let shader = self.shader.borrow();
+        unsafe {
+            self.gl.use_program(Some(shader.program));
+
+            self.gl.uniform_4_f32(
+                shader.tint_loc.as_ref(),
+                color[0],
+                color[1],
+                color[2],
+                alpha,
+            );
+            self.gl.use_program(None);
+        }
+    }
+
+    /// Texels with alpha below cutoff are discarded for everything rendered after this, 0 (the
+    /// default) draws every texel. Cut out pixels never write depth, so unlike blending the
+    /// result doesn't depend on draw order and they drop out of the shadow map too
+    pub fn set_alpha_cutoff(&self, cutoff: f32) {
+        let shader = self.shader.borrow();
+        unsafe {
+            self.gl.use_program(Some(shader.program));
+
+            self.gl
+                .uniform_1_f32(shader.alpha_cutoff_loc.as_ref(), cutoff);
+            self.gl.use_program(None);
+        }
+    }
+
+    /// Color of meshes drawn without a texture, before lighting. White (the default) leaves just
+    /// the vertex colors
+    pub fn set_untextured_color(&self, color: &[f32; 3]) {
+        let shader = self.shader.borrow();
+        unsafe {
+            self.gl.use_program(Some(shader.program));
+
+            self.gl.uniform_3_f32(
+                shader.untextured_color_loc.as_ref(),
+                color[0],
+                color[1],
+                color[2],
+            );
+            self.gl.use_program(None);
+        }
+    }
+
+    /// Share of each mesh's own color, textured or not, that shows at full brightness on top of
+    /// the lighting for everything rendered after this. Black (the default) is unlit by itself.
+    /// The composited countdown screen is just the texture here, so whatever the screen
+    /// postprocessing draws into it, scanlines and vignette included, glows along with the text
+    pub fn set_emissive(&self, emissive: &[f32; 3]) {
+        let shader = self.shader.borrow();
+        unsafe {
+            self.gl.use_program(Some(shader.program));
+
+            self.gl.uniform_3_f32(
+                shader.emissive_loc.as_ref(),
+                emissive[0],
+                emissive[1],
+                emissive[2],
+            );
             self.gl.use_program(None);
         }
     }
 
     pub fn render(&self, mesh: &GpuMesh, transform: &Transform) {
+        self.render_with_texture(mesh, mesh.tex, transform)
+    }
+
+    /// Render a mesh with a texture other than the one it was uploaded with, None draws it in
+    /// the untextured color
+    pub fn render_with_texture(
+        &self,
+        mesh: &GpuMesh,
+        tex: Option<NativeTexture>,
+        transform: &Transform,
+    ) {
+        let shader = self.shader.borrow();
         let gl = self.gl;
 
         unsafe {
-            gl.use_program(Some(self.program));
+            gl.use_program(Some(shader.program));
             gl.bind_vertex_array(Some(mesh.vao));
 
             gl.active_texture(glow::TEXTURE0);
-            gl.bind_texture(glow::TEXTURE_2D, Some(mesh.tex));
+            gl.bind_texture(glow::TEXTURE_2D, tex);
+            gl.uniform_1_i32(shader.untextured_loc.as_ref(), tex.is_none() as i32);
 
             gl.uniform_matrix_4_f32_slice(
-                self.model_loc.as_ref(),
+                shader.model_loc.as_ref(),
                 true,
                 std::slice::from_raw_parts(transform.arr[0].as_ptr(), 16),
             );
             gl.draw_elements(glow::TRIANGLES, mesh.num_elements, glow::UNSIGNED_INT, 0);
+            self.draw_calls.set(self.draw_calls.get() + 1);
+            self.meshes_drawn.set(self.meshes_drawn.get() + 1);
+
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.bind_vertex_array(None);
+            gl.use_program(None);
+        }
+    }
+
+    /// Render the same mesh once per transform with a single instanced draw call
+    pub fn render_instanced(&self, mesh: &GpuMesh, transforms: &[Transform]) {
+        self.render_instanced_with_texture(mesh, mesh.tex, transforms)
+    }
+
+    pub fn render_instanced_with_texture(
+        &self,
+        mesh: &GpuMesh,
+        tex: Option<NativeTexture>,
+        transforms: &[Transform],
+    ) {
+        let shader = self.shader.borrow();
+        let gl = self.gl;
+
+        let Some(instance_model_loc) = self.instance_model_loc else {
+            // Shader doesn't support instancing, fall back to one draw per instance
+            for transform in transforms {
+                self.render_with_texture(mesh, tex, transform);
+            }
+            return;
+        };
+
+        if transforms.is_empty() {
+            return;
+        }
+
+        // GLSL matrix attributes are consumed one column at a time, but our transforms are stored
+        // [row][col]
+        let instance_data: Vec<[[f32; 4]; 4]> = transforms
+            .iter()
+            .map(|t| {
+                let mut columns = [[0.0f32; 4]; 4];
+                for col in 0..4 {
+                    for row in 0..4 {
+                        columns[col][row] = t.arr[row][col];
+                    }
+                }
+                columns
+            })
+            .collect();
+
+        unsafe {
+            gl.use_program(Some(shader.program));
+            gl.bind_vertex_array(Some(mesh.vao));
+
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.instance_vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                slice_arr_to_u8_slice(&instance_data),
+                glow::STREAM_DRAW,
+            );
+
+            const STRIDE: i32 = std::mem::size_of::<[[f32; 4]; 4]>() as i32;
+            const COLUMN_SIZE: i32 = std::mem::size_of::<[f32; 4]>() as i32;
+            for col in 0..4 {
+                let loc = instance_model_loc + col;
+                gl.vertex_attrib_pointer_f32(
+                    loc,
+                    4,
+                    glow::FLOAT,
+                    false,
+                    STRIDE,
+                    col as i32 * COLUMN_SIZE,
+                );
+                gl.vertex_attrib_divisor(loc, 1);
+                gl.enable_vertex_attrib_array(loc);
+            }
+
+            gl.active_texture(glow::TEXTURE0);
+            gl.bind_texture(glow::TEXTURE_2D, tex);
+            gl.uniform_1_i32(shader.untextured_loc.as_ref(), tex.is_none() as i32);
+
+            gl.uniform_1_i32(shader.instanced_loc.as_ref(), 1);
+            gl.draw_elements_instanced(
+                glow::TRIANGLES,
+                mesh.num_elements,
+                glow::UNSIGNED_INT,
+                0,
+                transforms.len().try_into().expect("Too many instances"),
+            );
+            self.draw_calls.set(self.draw_calls.get() + 1);
+            self.meshes_drawn
+                .set(self.meshes_drawn.get() + transforms.len());
+            gl.uniform_1_i32(shader.instanced_loc.as_ref(), 0);
+
+            // Leave the vao as we found it so regular draws use the model uniform again
+            for col in 0..4 {
+                gl.disable_vertex_attrib_array(instance_model_loc + col);
+            }
 
             gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
             gl.bind_vertex_array(None);
             gl.use_program(None);
         }
     }
+
+    /// Swaps in a new 3d fragment shader, the current one stays if it doesn't build. The
+    /// vertex shader stays the built in one, so uploaded meshes keep working
+    pub fn reload_fragment_shader(&self, source: &str) -> Result<(), GlError> {
+        let source = with_defines(source, self.hardware_pcf, self.shadows)
+            .ok_or_else(|| GlError("shader should start with a #version line".to_string()))?;
+
+        unsafe {
+            let program = gl_util::compile_program(self.gl, VERTEX_SOURCE, &source)?;
+
+            // Mesh vaos were set up with the first program's attribute locations
+            let attribs = [
+                ("in_vert", self.vert_loc),
+                ("in_uv", self.uv_loc),
+                ("in_normal", self.norm_loc),
+                ("in_color", self.color_loc),
+                ("in_model", self.instance_model_loc),
+            ];
+            let moved = attribs.iter().any(|&(name, loc)| {
+                let new_loc = self.gl.get_attrib_location(program, name);
+                new_loc.is_some() && new_loc != loc
+            });
+            if moved {
+                self.gl.delete_program(program);
+                return Err(GlError("vertex attribute locations changed".to_string()));
+            }
+
+            let old = self.shader.replace(MeshShader::new(self.gl, program));
+            self.gl.delete_program(old.program);
+        }
+
+        let (samples, light_size) = self.soft_shadows.get();
+        self.set_soft_shadows(samples, light_size);
+        let (color, alpha) = self.tint.get();
+        self.set_tint(&color, alpha);
+        Ok(())
+    }
+
+    /// Number of draw calls issued since the last call to [`MeshRenderer::reset_counters`]
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls.get()
+    }
+
+    /// Like draw_calls, but every instance counts
+    pub fn meshes_drawn(&self) -> usize {
+        self.meshes_drawn.get()
+    }
+
+    pub fn reset_counters(&self) {
+        self.draw_calls.set(0);
+        self.meshes_drawn.set(0);
+    }
+}
+
+impl Drop for MeshRenderer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_program(self.shader.get_mut().program);
+            self.gl.delete_buffer(self.instance_vbo);
+        }
+    }
 }
 
 unsafe fn slice_arr_to_u8_slice<T>(input: &[T]) -> &[u8] {
@@ -5,6 +5,8 @@ use std::{
 
 use thiserror::Error;
 
+use crate::mat::Vec3;
+
 #[derive(Debug, Error)]
 #[error("obj parse error")]
 pub enum ObjParseError {
@@ -18,6 +20,21 @@ pub enum ObjParseError {
     InvalidFaceNorm(std::num::ParseIntError),
     MissingTexCoord,
     NonFloatTexCoord(std::num::ParseFloatError),
+    InvalidElementVert(std::num::ParseIntError),
+    /// index is one based as written in the file, len is how many of kind the file defines
+    FaceIndexOutOfRange {
+        kind: FaceIndexKind,
+        index: u32,
+        len: usize,
+    },
+}
+
+/// Which part of a v/vt/vn face element an index refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaceIndexKind {
+    Vertex,
+    TexCoord,
+    Normal,
 }
 
 #[repr(C)]
@@ -26,6 +43,7 @@ pub struct VertData {
     pub vert: [f32; 4],
     pub uv: [f32; 2],
     pub norm: [f32; 3],
+    pub color: [f32; 3],
 }
 
 impl VertData {
@@ -34,6 +52,7 @@ impl VertData {
             vert: [0.0; 4],
             uv: [0.0; 2],
             norm: [0.0; 3],
+            color: [0.0; 3],
         }
     }
     pub const fn vert_offset() -> i32 {
@@ -59,20 +78,48 @@ impl VertData {
                 .offset_from(&obj as *const VertData as *const u8) as i32
         }
     }
+
+    pub const fn color_offset() -> i32 {
+        let obj = VertData::new();
+        unsafe {
+            (std::ptr::addr_of!(obj.color) as *const u8)
+                .offset_from(&obj as *const VertData as *const u8) as i32
+        }
+    }
+}
+
+/// How many of each element the file defined, before faces combine them into vertices
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ObjCounts {
+    pub positions: usize,
+    pub tex_coords: usize,
+    pub normals: usize,
 }
 
 #[derive(Debug)]
 pub struct Mesh {
     pub vertices: Vec<VertData>,
     pub faces: Vec<[u32; 3]>,
+    pub counts: ObjCounts,
+    /// Segments from l elements, polylines are split into their segments. Nothing draws these
+    /// yet, so segments and points with a vertex out of range are dropped instead of failing the
+    /// mesh
+    #[allow(unused)]
+    pub lines: Vec<[[f32; 3]; 2]>,
+    /// Positions from p elements
+    #[allow(unused)]
+    pub points: Vec<[f32; 3]>,
 }
 
 impl Mesh {
     pub fn from_obj_file<R: BufRead>(r: R) -> Result<Mesh, ObjParseError> {
         let mut vertices = Vec::new();
+        let mut colors = Vec::new();
         let mut faces = Vec::new();
         let mut tex_coords = Vec::new();
         let mut normals = Vec::new();
+        let mut line_elements = Vec::new();
+        let mut point_elements = Vec::new();
 
         for line in r.lines() {
             let line = line.map_err(ObjParseError::FileRead)?;
@@ -81,8 +128,9 @@ impl Mesh {
             let typ = line_it.next().ok_or(ObjParseError::MissingType)?;
             match typ {
                 "v" => {
-                    let v = parse_vertex(line_it)?;
+                    let (v, color) = parse_vertex(line_it)?;
                     vertices.push(v);
+                    colors.push(color);
                 }
                 "f" => {
                     let v = parse_face(line_it)?;
@@ -96,13 +144,79 @@ impl Mesh {
                     let v = parse_vertex_3(line_it)?;
                     normals.push(v);
                 }
+                "l" => {
+                    let v = parse_element_verts(line_it)?;
+                    line_elements.extend(v.windows(2).map(|w| [w[0], w[1]]));
+                }
+                "p" => {
+                    point_elements.extend(parse_element_verts(line_it)?);
+                }
                 t => {
                     println!("Unsupported type {t}");
                 }
             }
         }
 
-        Ok(obj_data_to_mesh(&vertices, &tex_coords, &normals, &faces))
+        let position = |i: u32| {
+            let v = i.checked_sub(1).and_then(|i| vertices.get(i as usize));
+            if v.is_none() {
+                println!("Skipping element with out of range vertex {i}");
+            }
+            v.map(|v| [v[0], v[1], v[2]])
+        };
+        let lines = line_elements
+            .iter()
+            .filter_map(|[a, b]| Some([position(*a)?, position(*b)?]))
+            .collect();
+        let points = point_elements.iter().filter_map(|i| position(*i)).collect();
+
+        Ok(Mesh {
+            lines,
+            points,
+            counts: ObjCounts {
+                positions: vertices.len(),
+                tex_coords: tex_coords.len(),
+                normals: normals.len(),
+            },
+            ..obj_data_to_mesh(&vertices, &colors, &tex_coords, &normals, &faces)?
+        })
+    }
+
+    /// One line describing what was loaded, for checking a model parsed the way it should.
+    /// Every face needs a texture coordinate and a normal, nothing is generated for them
+    pub fn summary(&self) -> String {
+        let (min, max) = self.bounding_box();
+        format!(
+            "{} vertices, {} triangles from {} positions, {} uvs and {} normals, bounds ({:.3}, {:.3}, {:.3}) to ({:.3}, {:.3}, {:.3})",
+            self.vertices.len(),
+            self.faces.len(),
+            self.counts.positions,
+            self.counts.tex_coords,
+            self.counts.normals,
+            min.x(),
+            min.y(),
+            min.z(),
+            max.x(),
+            max.y(),
+            max.z(),
+        )
+    }
+
+    /// Axis aligned (min, max) corners of all vertices in the mesh
+    pub fn bounding_box(&self) -> (Vec3, Vec3) {
+        if self.vertices.is_empty() {
+            return ([0.0; 3].into(), [0.0; 3].into());
+        }
+
+        let mut min = Vec3::from([f32::MAX; 3]);
+        let mut max = Vec3::from([f32::MIN; 3]);
+        for v in &self.vertices {
+            let p = Vec3::from([v.vert[0], v.vert[1], v.vert[2]]);
+            min = min.min(&p);
+            max = max.max(&p);
+        }
+
+        (min, max)
     }
 }
 
@@ -126,17 +240,29 @@ fn parse_vertex_3<'a, It: Iterator<Item = &'a str>>(mut it: It) -> Result<[f32;
     Ok(res)
 }
 
-fn parse_vertex<'a, It: Iterator<Item = &'a str>>(mut it: It) -> Result<[f32; 4], ObjParseError> {
-    let mut res = [0f32; 4];
-
-    parse_vertex_n(&mut it, &mut res[0..3])?;
-
-    res[3] = match it.next() {
-        Some(v) => v.parse().map_err(ObjParseError::NonFloatVertex)?,
-        None => 1.0,
+/// Parses x y [z] [w] [r g b]. A missing z is 0 for 2D exporters, vertices without a color are
+/// white so they render as before. Only the floats directly after y count, anything from the
+/// first token that isn't one is ignored
+fn parse_vertex<'a, It: Iterator<Item = &'a str>>(
+    mut it: It,
+) -> Result<([f32; 4], [f32; 3]), ObjParseError> {
+    let mut res = [0.0, 0.0, 0.0, 1.0];
+    let mut color = [1f32; 3];
+
+    parse_vertex_n(&mut it, &mut res[0..2])?;
+
+    let extra: Vec<f32> = it.map_while(|s| s.parse().ok()).collect();
+    // Four values after x y are z and a color with no w, otherwise w comes before the color
+    let (z_w, rgb) = match extra.len() {
+        4 => (&extra[..1], &extra[1..]),
+        n if n >= 5 => (&extra[..2], &extra[2..5]),
+        n => (&extra[..n.min(2)], &extra[0..0]),
     };
 
-    Ok(res)
+    res[2..2 + z_w.len()].copy_from_slice(z_w);
+    color[..rgb.len()].copy_from_slice(rgb);
+
+    Ok((res, color))
 }
 
 fn parse_tex_coord<'a, It: Iterator<Item = &'a str>>(
@@ -176,6 +302,8 @@ fn parse_face<'a, It: Iterator<Item = &'a str>>(
         },
     ];
 
+    // Index 0 wraps around to a value no file can reach, so it fails the range check in
+    // obj_data_to_mesh like any other missing element
     for i in 0..3 {
         let face = it.next().ok_or(ObjParseError::MissingFaceVert)?;
         let mut face_it = face.split('/');
@@ -185,7 +313,7 @@ fn parse_face<'a, It: Iterator<Item = &'a str>>(
         ret[i].vert = vert_id
             .parse::<u32>()
             .map_err(ObjParseError::InvalidFaceVert)?
-            - 1u32;
+            .wrapping_sub(1);
 
         let tex_id = face_it
             .next()
@@ -193,7 +321,7 @@ fn parse_face<'a, It: Iterator<Item = &'a str>>(
         ret[i].uv = tex_id
             .parse::<u32>()
             .map_err(ObjParseError::InvalidFaceUv)?
-            - 1u32;
+            .wrapping_sub(1);
 
         let norm_id = face_it
             .next()
@@ -201,12 +329,25 @@ fn parse_face<'a, It: Iterator<Item = &'a str>>(
         ret[i].norm = norm_id
             .parse::<u32>()
             .map_err(ObjParseError::InvalidFaceNorm)?
-            - 1u32;
+            .wrapping_sub(1);
     }
 
     Ok(ret)
 }
 
+/// One based vertex indices of an l or p element, any /vt part is ignored
+fn parse_element_verts<'a, It: Iterator<Item = &'a str>>(
+    it: It,
+) -> Result<Vec<u32>, ObjParseError> {
+    it.map(|elem| {
+        let vert_id = elem.split('/').next().unwrap_or(elem);
+        vert_id
+            .parse::<u32>()
+            .map_err(ObjParseError::InvalidElementVert)
+    })
+    .collect()
+}
+
 #[derive(Debug, Hash, Clone, Copy, Eq, PartialEq)]
 struct FaceIndices {
     vert: u32,
@@ -216,12 +357,30 @@ struct FaceIndices {
 
 fn obj_data_to_mesh(
     in_vertices: &[[f32; 4]],
+    in_colors: &[[f32; 3]],
     in_uvs: &[[f32; 2]],
     in_normals: &[[f32; 3]],
     in_faces: &[[FaceIndices; 3]],
-) -> Mesh {
+) -> Result<Mesh, ObjParseError> {
     type MergedIndex = u32;
 
+    let check = |kind, index: u32, len: usize| {
+        if (index as usize) < len {
+            Ok(())
+        } else {
+            Err(ObjParseError::FaceIndexOutOfRange {
+                kind,
+                index: index.wrapping_add(1),
+                len,
+            })
+        }
+    };
+    for vert in in_faces.iter().flatten() {
+        check(FaceIndexKind::Vertex, vert.vert, in_vertices.len())?;
+        check(FaceIndexKind::TexCoord, vert.uv, in_uvs.len())?;
+        check(FaceIndexKind::Normal, vert.norm, in_normals.len())?;
+    }
+
     let mut mapping: HashMap<FaceIndices, MergedIndex> = HashMap::new();
     // If we've seen this, take the index of vert_and_uv for that pair
     // If we haven't seen it, create a new vert/uv pair and push into vert_and_uv
@@ -237,6 +396,7 @@ fn obj_data_to_mesh(
                     vert: in_vertices[vert.vert as usize],
                     uv: in_uvs[vert.uv as usize],
                     norm: in_normals[vert.norm as usize],
+                    color: in_colors[vert.vert as usize],
                 });
 
                 (output_vert_and_uv.len() - 1).try_into().unwrap()
@@ -248,10 +408,13 @@ fn obj_data_to_mesh(
         output_faces.push(output_face);
     }
 
-    Mesh {
+    Ok(Mesh {
         vertices: output_vert_and_uv,
         faces: output_faces,
-    }
+        counts: ObjCounts::default(),
+        lines: Vec::new(),
+        points: Vec::new(),
+    })
 }
 
 #[cfg(test)]
@@ -261,7 +424,8 @@ mod test {
     #[test]
     fn test_valid_vertex_parse() {
         match parse_vertex("1.0 2.0 3.0".split_whitespace()) {
-            Ok(v) => {
+            Ok((v, color)) => {
+                assert_eq!(color, [1.0, 1.0, 1.0]);
                 let expected = [1.0, 2.0, 3.0, 1.0];
                 for (a, b) in v.into_iter().zip(expected) {
                     assert!((a - b).abs() < 0.0001);
@@ -271,7 +435,7 @@ mod test {
         };
 
         match parse_vertex("1.0 2.0 3.0 2.0".split_whitespace()) {
-            Ok(v) => {
+            Ok((v, _)) => {
                 let expected = [1.0, 2.0, 3.0, 2.0];
                 for (a, b) in v.into_iter().zip(expected) {
                     assert!((a - b).abs() < 0.0001);
@@ -281,6 +445,75 @@ mod test {
         };
     }
 
+    #[test]
+    fn test_vertex_color_parse() {
+        match parse_vertex("1.0 2.0 3.0 0.25 0.5 0.75".split_whitespace()) {
+            Ok((v, color)) => {
+                assert_eq!(v, [1.0, 2.0, 3.0, 1.0]);
+                assert_eq!(color, [0.25, 0.5, 0.75]);
+            }
+            Err(e) => panic!("Unexpected vertex parse failure: {e:?}"),
+        };
+
+        match parse_vertex("1.0 2.0 3.0 1.0 0.5 0.5".split_whitespace()) {
+            Ok((v, color)) => {
+                assert_eq!(v, [1.0, 2.0, 3.0, 1.0]);
+                assert_eq!(color, [1.0, 0.5, 0.5]);
+            }
+            Err(e) => panic!("Unexpected vertex parse failure: {e:?}"),
+        };
+    }
+
+    #[test]
+    fn test_vertex_parse_loose_component_count() {
+        match parse_vertex("1.0 2.0".split_whitespace()) {
+            Ok((v, color)) => {
+                assert_eq!(v, [1.0, 2.0, 0.0, 1.0]);
+                assert_eq!(color, [1.0, 1.0, 1.0]);
+            }
+            Err(e) => panic!("Unexpected vertex parse failure: {e:?}"),
+        };
+
+        // Anything after the last float is ignored
+        match parse_vertex("1.0 2.0 3.0 2.0 junk".split_whitespace()) {
+            Ok((v, color)) => {
+                assert_eq!(v, [1.0, 2.0, 3.0, 2.0]);
+                assert_eq!(color, [1.0, 1.0, 1.0]);
+            }
+            Err(e) => panic!("Unexpected vertex parse failure: {e:?}"),
+        };
+
+        match parse_vertex("1.0 2.0 3.0 junk".split_whitespace()) {
+            Ok((v, color)) => {
+                assert_eq!(v, [1.0, 2.0, 3.0, 1.0]);
+                assert_eq!(color, [1.0, 1.0, 1.0]);
+            }
+            Err(e) => panic!("Unexpected vertex parse failure: {e:?}"),
+        };
+
+        // A color cut short by garbage leaves the floats before it as z and w
+        match parse_vertex("1.0 2.0 3.0 0.25 0.5 junk 0.75".split_whitespace()) {
+            Ok((v, color)) => {
+                assert_eq!(v, [1.0, 2.0, 3.0, 0.25]);
+                assert_eq!(color, [1.0, 1.0, 1.0]);
+            }
+            Err(e) => panic!("Unexpected vertex parse failure: {e:?}"),
+        };
+
+        match parse_vertex("1.0 2.0 3.0 2.0 0.25 0.5 0.75 9 9".split_whitespace()) {
+            Ok((v, color)) => {
+                assert_eq!(v, [1.0, 2.0, 3.0, 2.0]);
+                assert_eq!(color, [0.25, 0.5, 0.75]);
+            }
+            Err(e) => panic!("Unexpected vertex parse failure: {e:?}"),
+        };
+
+        match parse_vertex("1.0".split_whitespace()) {
+            Err(ObjParseError::MissingVertex) => (),
+            v => panic!("Unexpected parse result: {v:?}"),
+        };
+    }
+
     #[test]
     fn test_vertex_parse_invalid_float() {
         match parse_vertex("asdflka jdf".split_whitespace()) {
@@ -296,6 +529,21 @@ mod test {
         };
     }
 
+    #[test]
+    fn test_bounding_box() {
+        let obj = "\
+            v -1.0 2.0 0.5\n\
+            v 3.0 -4.0 0.0\n\
+            v 0.0 0.0 -2.0\n\
+            vt 0.0 0.0\n\
+            vn 0.0 0.0 1.0\n\
+            f 1/1/1 2/1/1 3/1/1\n";
+        let mesh = Mesh::from_obj_file(obj.as_bytes()).unwrap();
+        let (min, max) = mesh.bounding_box();
+        assert_eq!([min.x(), min.y(), min.z()], [-1.0, -4.0, -2.0]);
+        assert_eq!([max.x(), max.y(), max.z()], [3.0, 2.0, 0.5]);
+    }
+
     #[test]
     fn test_face_parse_with_slashes() {
         match parse_face("1/2/3 2/3/4 3/4/5".split_whitespace()) {
@@ -323,6 +571,103 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_lines_and_points() {
+        let obj = "\
+            v 0 0 0\n\
+            v 1 0 0\n\
+            v 0 1 0\n\
+            vt 0 0\n\
+            vn 0 0 1\n\
+            f 1/1/1 2/1/1 3/1/1\n\
+            l 1 2/1 3\n\
+            p 3\n";
+        let mesh = Mesh::from_obj_file(obj.as_bytes()).unwrap();
+
+        assert_eq!(mesh.faces, [[0, 1, 2]]);
+        assert_eq!(
+            mesh.lines,
+            [
+                [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]],
+                [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+            ]
+        );
+        assert_eq!(mesh.points, [[0.0, 1.0, 0.0]]);
+
+        // Bad indices only drop their own segment or point, the faces still load
+        let obj = "\
+            v 0 0 0\n\
+            v 1 0 0\n\
+            v 0 1 0\n\
+            vt 0 0\n\
+            vn 0 0 1\n\
+            f 1/1/1 2/1/1 3/1/1\n\
+            l 1 2 4\n\
+            l 0 1\n\
+            p 5 2\n";
+        let mesh = Mesh::from_obj_file(obj.as_bytes()).unwrap();
+        assert_eq!(mesh.faces, [[0, 1, 2]]);
+        assert_eq!(mesh.lines, [[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]]]);
+        assert_eq!(mesh.points, [[1.0, 0.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_face_index_out_of_range() {
+        let header = "v 0 0 0\nv 1 0 0\nvt 0 0\nvn 0 0 1\n";
+        let parse = |face: &str| Mesh::from_obj_file(format!("{header}{face}\n").as_bytes());
+
+        assert!(matches!(
+            parse("f 1/1/1 2/1/1 3/1/1"),
+            Err(ObjParseError::FaceIndexOutOfRange {
+                kind: FaceIndexKind::Vertex,
+                index: 3,
+                len: 2
+            })
+        ));
+        assert!(matches!(
+            parse("f 1/1/1 2/2/1 1/1/1"),
+            Err(ObjParseError::FaceIndexOutOfRange {
+                kind: FaceIndexKind::TexCoord,
+                index: 2,
+                len: 1
+            })
+        ));
+        assert!(matches!(
+            parse("f 1/1/0 2/1/1 1/1/1"),
+            Err(ObjParseError::FaceIndexOutOfRange {
+                kind: FaceIndexKind::Normal,
+                index: 0,
+                len: 1
+            })
+        ));
+        assert!(parse("f 1/1/1 2/1/1 1/1/1").is_ok());
+    }
+
+    #[test]
+    fn test_summary() {
+        let obj = "\
+            v 0 0 0\n\
+            v 1 0 0\n\
+            v 0 2 0\n\
+            v 5 5 5\n\
+            vt 0 0\n\
+            vn 0 0 1\n\
+            f 1/1/1 2/1/1 3/1/1\n";
+        let mesh = Mesh::from_obj_file(obj.as_bytes()).unwrap();
+        assert_eq!(
+            mesh.counts,
+            ObjCounts {
+                positions: 4,
+                tex_coords: 1,
+                normals: 1
+            }
+        );
+        assert_eq!(
+            mesh.summary(),
+            "3 vertices, 1 triangles from 4 positions, 1 uvs and 1 normals, bounds (0.000, 0.000, 0.000) to (1.000, 2.000, 0.000)"
+        );
+    }
+
     #[test]
     fn test_face_parse_not_enough_elems() {
         match parse_face("1/1/1 2/2/2".split_whitespace()) {
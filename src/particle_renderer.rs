@@ -0,0 +1,152 @@
+use glow::{HasContext, NativeBuffer, NativeProgram, NativeVertexArray};
+
+use crate::{gl_util, rng::Rng, GlError};
+
+unsafe fn particles_to_u8_slice(input: &[Particle]) -> &[u8] {
+    core::slice::from_raw_parts(input.as_ptr() as *const u8, std::mem::size_of_val(input))
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Particle {
+    // NDC position
+    x: f32,
+    y: f32,
+    // Point size in pixels
+    size: f32,
+}
+
+/// Starfield style background of dots drifting up the screen. Draws with the depth test off,
+/// turning it back on after
+pub struct ParticleRenderer<'a> {
+    program: NativeProgram,
+    vao: NativeVertexArray,
+    vbo: NativeBuffer,
+    gl: &'a glow::Context,
+    color_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    particles: Vec<Particle>,
+    // Per particle multiplier on speed so that bigger dots look closer
+    speeds: Vec<f32>,
+    speed: f32,
+    color: [f32; 3],
+    rng: Rng,
+}
+
+impl<'a> ParticleRenderer<'a> {
+    pub fn new(
+        gl: &'a glow::Context,
+        count: usize,
+        speed: f32,
+        color: [f32; 3],
+        mut rng: Rng,
+    ) -> Result<ParticleRenderer<'a>, GlError> {
+        let mut particles = Vec::with_capacity(count);
+        let mut speeds = Vec::with_capacity(count);
+        for _ in 0..count {
+            let size = rng.range_f32(1.0, 4.0);
+            particles.push(Particle {
+                x: rng.range_f32(-1.0, 1.0),
+                y: rng.range_f32(-1.0, 1.0),
+                size,
+            });
+            speeds.push(size / 4.0);
+        }
+
+        unsafe {
+            let program = gl_util::compile_program(
+                gl,
+                include_str!("glsl/particle_vertex.glsl"),
+                include_str!("glsl/particle_fragment.glsl"),
+            )?;
+
+            let vao = gl.create_vertex_array().map_err(GlError)?;
+            gl.bind_vertex_array(Some(vao));
+
+            let vbo = gl.create_buffer().map_err(GlError)?;
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+            gl.buffer_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                particles_to_u8_slice(&particles),
+                glow::STREAM_DRAW,
+            );
+
+            const STRIDE: i32 = std::mem::size_of::<Particle>() as i32;
+            gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, STRIDE, 0);
+            gl.enable_vertex_attrib_array(0);
+
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.bind_vertex_array(None);
+
+            let color_loc = gl.get_uniform_location(program, "color");
+
+            Ok(ParticleRenderer {
+                program,
+                vao,
+                vbo,
+                gl,
+                color_loc,
+                particles,
+                speeds,
+                speed,
+                color,
+                rng,
+            })
+        }
+    }
+
+    /// Drift particles by elapsed time in seconds. Particles leaving the top of the screen are
+    /// respawned at the bottom
+    pub fn update(&mut self, time_since_last: f32) {
+        for (particle, speed) in self.particles.iter_mut().zip(&self.speeds) {
+            particle.y += self.speed * speed * time_since_last;
+            if particle.y > 1.0 {
+                particle.y = -1.0;
+                particle.x = self.rng.range_f32(-1.0, 1.0);
+            }
+        }
+    }
+
+    pub fn render(&self) {
+        let gl = self.gl;
+
+        unsafe {
+            gl.use_program(Some(self.program));
+            gl.bind_vertex_array(Some(self.vao));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+
+            gl.buffer_sub_data_u8_slice(
+                glow::ARRAY_BUFFER,
+                0,
+                particles_to_u8_slice(&self.particles),
+            );
+
+            gl.uniform_3_f32(
+                self.color_loc.as_ref(),
+                self.color[0],
+                self.color[1],
+                self.color[2],
+            );
+
+            // Background layer, no depth test or writes so everything else draws over the top
+            gl.enable(glow::PROGRAM_POINT_SIZE);
+            gl.disable(glow::DEPTH_TEST);
+            gl.draw_arrays(glow::POINTS, 0, self.particles.len() as i32);
+            gl.enable(glow::DEPTH_TEST);
+            gl.disable(glow::PROGRAM_POINT_SIZE);
+
+            gl.bind_buffer(glow::ARRAY_BUFFER, None);
+            gl.bind_vertex_array(None);
+            gl.use_program(None);
+        }
+    }
+}
+
+impl Drop for ParticleRenderer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_program(self.program);
+            self.gl.delete_buffer(self.vbo);
+            self.gl.delete_vertex_array(self.vao);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Stand in for the glyph and cursor renderers that draws frames as text, so tests can check
+//! what the screen would show without a GL context
+
+use crate::{
+    animation::{self, Animation, AnimationReq},
+    glyph_renderer::SgrChars,
+};
+
+use std::{collections::VecDeque, time::Instant};
+
+pub const CURSOR: char = '_';
+
+/// Lays text out on a fixed width grid, wrapping like GlyphRenderer does at the right edge
+pub struct PlainTextBackend {
+    width: usize,
+}
+
+impl PlainTextBackend {
+    pub fn new(width: usize) -> PlainTextBackend {
+        assert!(width > 0);
+        PlainTextBackend { width }
+    }
+
+    /// The cursor sits after the last character, the same place App draws it. Color escapes are
+    /// stripped like GlyphRenderer does
+    pub fn render(&self, s: &str, cursor_visible: bool) -> String {
+        let (mut rows, (_, row)) = self.layout(s);
+        if cursor_visible {
+            // Always the end of the last row, or the start of a new one
+            if row == rows.len() {
+                rows.push(String::new());
+            }
+            rows[row].push(CURSOR);
+        }
+        rows.join("\n")
+    }
+
+    /// Column and row of the cell the cursor is drawn in for s, counting from the top left
+    pub fn cursor(&self, s: &str) -> (usize, usize) {
+        self.layout(s).1
+    }
+
+    /// Rows of s and where the next character would go
+    fn layout(&self, s: &str) -> (Vec<String>, (usize, usize)) {
+        let mut rows = vec![String::new()];
+        let mut col = 0;
+
+        for (c, _) in SgrChars::new(s) {
+            if c == '\n' {
+                rows.push(String::new());
+                col = 0;
+                continue;
+            }
+
+            if col == self.width {
+                rows.push(String::new());
+                col = 0;
+            }
+            rows.last_mut().unwrap().push(c);
+            col += 1;
+        }
+
+        let next = if col == self.width {
+            (0, rows.len())
+        } else {
+            (col, rows.len() - 1)
+        };
+        (rows, next)
+    }
+}
+
+/// Steps through an animation queue with animation::step like App::update, without ever resetting
+pub struct AnimationPlayer {
+    current: Animation,
+    queue: VecDeque<AnimationReq>,
+}
+
+impl AnimationPlayer {
+    pub fn new(initial: &str, queue: VecDeque<AnimationReq>) -> AnimationPlayer {
+        AnimationPlayer {
+            current: Animation::None(initial.to_string()),
+            queue,
+        }
+    }
+
+    pub fn update(&mut self, now: Instant) -> &str {
+        animation::step(&mut self.current, &mut self.queue, now);
+        self.current.as_str()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::ease::Easing;
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_render_wraps_and_places_cursor() {
+        let backend = PlainTextBackend::new(4);
+        assert_eq!(backend.render("abcdef", true), "abcd\nef_");
+        assert_eq!(backend.render("abcd", true), "abcd\n_");
+        assert_eq!(backend.render("ab\ncd", false), "ab\ncd");
+        assert_eq!(backend.render("", true), "_");
+        assert_eq!(backend.render("\x1b[32mabc\x1b[0mdef", true), "abcd\nef_");
+    }
+
+    #[test]
+    fn test_cursor_position() {
+        let backend = PlainTextBackend::new(4);
+        assert_eq!(backend.cursor(""), (0, 0));
+        assert_eq!(backend.cursor("abcdef"), (2, 1));
+        // A full row pushes the cursor onto the next one
+        assert_eq!(backend.cursor("abcd"), (0, 1));
+        assert_eq!(backend.cursor("ab\n"), (0, 1));
+        assert_eq!(backend.cursor("\x1b[32mab\x1b[0m"), (2, 0));
+    }
+
+    #[test]
+    fn test_typing_frames() {
+        let backend = PlainTextBackend::new(40);
+        let start = Instant::now();
+        let reqs = animation::construct_animation_requests("", "hello", Easing::default());
+        let mut player = AnimationPlayer::new("", reqs);
+
+        let frames: Vec<String> = (0..=10)
+            .map(|i| {
+                let now = start + Duration::from_millis(150 * i);
+                backend.render(player.update(now), true)
+            })
+            .collect();
+
+        assert_eq!(frames[0], "_");
+        assert_eq!(frames[10], "hello_");
+        // Text only ever grows while typing, one prefix of the next
+        for pair in frames.windows(2) {
+            assert!(pair[1].starts_with(pair[0].trim_end_matches(CURSOR)));
+        }
+    }
+
+    #[test]
+    fn test_retype_middle_frames() {
+        let backend = PlainTextBackend::new(40);
+        let start = Instant::now();
+        let current = "topic: foo!";
+        let reqs =
+            animation::construct_animation_requests(current, "topic: bar!", Easing::default());
+        let mut player = AnimationPlayer::new(current, reqs);
+
+        // Step at a steady frame rate like the real loop, each request starts on the frame after
+        // the previous one finishes
+        let mut elapsed = 0;
+        let mut frame_at = |ms| {
+            let mut s = String::new();
+            while elapsed <= ms {
+                s = player
+                    .update(start + Duration::from_millis(elapsed))
+                    .to_string();
+                elapsed += 50;
+            }
+            backend.render(&s, false)
+        };
+
+        // Waits before touching anything
+        assert_eq!(frame_at(0), "topic: foo!");
+        assert_eq!(frame_at(1000), "topic: foo!");
+        // Everything between the shared prefix and suffix is deleted, then typed back
+        assert_eq!(frame_at(3100), "topic: !");
+        assert_eq!(frame_at(4700), "topic: bar!");
+    }
+}
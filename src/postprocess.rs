@@ -0,0 +1,218 @@
+use glow::{HasContext, NativeProgram, NativeTexture, NativeVertexArray};
+
+use crate::{gl_util, lut::Lut, rng::Rng, GlError};
+
+type UniformLocation = <glow::Context as HasContext>::UniformLocation;
+
+/// A full screen fragment shader that reads the previous pass from in_tex. Every pass gets the
+/// aspect_ratio and time uniforms, anything else is set through params
+pub struct PostprocessPass<'a> {
+    program: NativeProgram,
+    aspect_loc: Option<UniformLocation>,
+    time_loc: Option<UniformLocation>,
+    params: Vec<(Option<UniformLocation>, f32)>,
+    // Extra inputs owned by the pass, bound to the units after in_tex
+    textures: Vec<(Option<UniformLocation>, NativeTexture)>,
+    gl: &'a glow::Context,
+}
+
+impl<'a> PostprocessPass<'a> {
+    pub fn new(
+        gl: &'a glow::Context,
+        frag_source: &str,
+        params: &[(&str, f32)],
+    ) -> Result<PostprocessPass<'a>, GlError> {
+        unsafe {
+            let program = gl_util::compile_program(
+                gl,
+                include_str!("glsl/postprocess_vertex.glsl"),
+                frag_source,
+            )?;
+
+            let aspect_loc = gl.get_uniform_location(program, "aspect_ratio");
+            let time_loc = gl.get_uniform_location(program, "time");
+            let params = params
+                .iter()
+                .map(|(name, value)| (gl.get_uniform_location(program, name), *value))
+                .collect();
+
+            Ok(PostprocessPass {
+                program,
+                aspect_loc,
+                time_loc,
+                params,
+                textures: Vec::new(),
+                gl,
+            })
+        }
+    }
+
+    pub fn chromatic_aberration(
+        gl: &'a glow::Context,
+        intensity: f32,
+    ) -> Result<PostprocessPass<'a>, GlError> {
+        Self::new(
+            gl,
+            include_str!("glsl/chromatic_aberration_fragment.glsl"),
+            &[("intensity", intensity)],
+        )
+    }
+
+    pub fn vignette(
+        gl: &'a glow::Context,
+        radius: f32,
+        softness: f32,
+    ) -> Result<PostprocessPass<'a>, GlError> {
+        Self::new(
+            gl,
+            include_str!("glsl/vignette_fragment.glsl"),
+            &[("radius", radius), ("softness", softness)],
+        )
+    }
+
+    /// Animated static, intensity is the largest change to each channel. The grain pattern is
+    /// picked by rng
+    pub fn noise(
+        gl: &'a glow::Context,
+        intensity: f32,
+        mut rng: Rng,
+    ) -> Result<PostprocessPass<'a>, GlError> {
+        Self::new(
+            gl,
+            include_str!("glsl/noise_fragment.glsl"),
+            &[
+                ("intensity", intensity),
+                ("seed", rng.range_f32(0.0, 1000.0)),
+            ],
+        )
+    }
+
+    /// Grades colors through a lookup table
+    pub fn lut(gl: &'a glow::Context, lut: &Lut) -> Result<PostprocessPass<'a>, GlError> {
+        let mut pass = Self::new(
+            gl,
+            include_str!("glsl/lut_fragment.glsl"),
+            &[("lut_size", lut.size as f32)],
+        )?;
+
+        unsafe {
+            let tex =
+                gl_util::create_tex_default_params(gl, glow::CLAMP_TO_EDGE, glow::CLAMP_TO_EDGE)?;
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            gl_util::with_packed_rows(gl, || {
+                gl.tex_image_2d(
+                    glow::TEXTURE_2D,
+                    0,
+                    glow::RGB as i32,
+                    (lut.size * lut.size) as i32,
+                    lut.size as i32,
+                    0,
+                    glow::RGB,
+                    glow::UNSIGNED_BYTE,
+                    Some(&lut.data),
+                )
+            });
+            gl.bind_texture(glow::TEXTURE_2D, None);
+
+            let loc = gl.get_uniform_location(pass.program, "lut_tex");
+            pass.textures.push((loc, tex));
+        }
+
+        Ok(pass)
+    }
+
+    unsafe fn render(&self, tex: NativeTexture, time: f32, aspect: f32) {
+        let gl = self.gl;
+        gl.use_program(Some(self.program));
+
+        gl.uniform_1_f32(self.aspect_loc.as_ref(), aspect);
+        gl.uniform_1_f32(self.time_loc.as_ref(), time);
+        for (loc, value) in &self.params {
+            gl.uniform_1_f32(loc.as_ref(), *value);
+        }
+
+        for (i, (loc, extra_tex)) in self.textures.iter().enumerate() {
+            let unit = i as u32 + 1;
+            gl.uniform_1_i32(loc.as_ref(), unit as i32);
+            gl.active_texture(glow::TEXTURE0 + unit);
+            gl.bind_texture(glow::TEXTURE_2D, Some(*extra_tex));
+        }
+
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+
+        gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+        gl.use_program(None);
+    }
+}
+
+impl Drop for PostprocessPass<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_program(self.program);
+            for (_, tex) in &self.textures {
+                self.gl.delete_texture(*tex);
+            }
+        }
+    }
+}
+
+/// Runs a texture through each pass in order, ping ponging between intermediate textures
+pub struct PostprocessChain<'a> {
+    passes: Vec<PostprocessPass<'a>>,
+    // The quad comes from gl_VertexID, but core profile still wants a vao bound to draw
+    vao: NativeVertexArray,
+    gl: &'a glow::Context,
+}
+
+impl<'a> PostprocessChain<'a> {
+    pub fn new(gl: &'a glow::Context) -> Result<PostprocessChain<'a>, GlError> {
+        let vao = unsafe { gl.create_vertex_array().map_err(GlError)? };
+        Ok(PostprocessChain {
+            passes: Vec::new(),
+            vao,
+            gl,
+        })
+    }
+
+    pub fn push(&mut self, pass: PostprocessPass<'a>) {
+        self.passes.push(pass);
+    }
+
+    /// Takes ownership of tex, the returned texture belongs to the caller. With no passes tex is
+    /// handed straight back
+    pub fn render(
+        &self,
+        tex: NativeTexture,
+        width: i32,
+        height: i32,
+        time: f32,
+        aspect: f32,
+    ) -> Result<NativeTexture, GlError> {
+        let gl = self.gl;
+        let mut tex = tex;
+
+        unsafe {
+            gl.bind_vertex_array(Some(self.vao));
+            for pass in &self.passes {
+                let (output, fb) = gl_util::setup_color_texture_render(gl, width, height)?;
+                pass.render(tex, time, aspect);
+
+                gl.delete_framebuffer(fb);
+                gl.delete_texture(tex);
+                tex = output;
+            }
+            gl.bind_vertex_array(None);
+        }
+
+        Ok(tex)
+    }
+}
+
+impl Drop for PostprocessChain<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_vertex_array(self.vao);
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, SyncSender, TrySendError},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+// Frames waiting to be written before we start dropping them instead of stalling the render loop
+const MAX_QUEUED_FRAMES: usize = 8;
+// First bytes written to a frame pipe, names the pixel format
+const FRAME_PIPE_MAGIC: &[u8; 4] = b"RGBA";
+
+#[derive(Debug, Error)]
+pub enum RecorderError {
+    #[error("failed to start ffmpeg")]
+    Spawn(std::io::Error),
+    #[error("ffmpeg has no stdin")]
+    NoStdin,
+}
+
+/// Pipes raw RGBA frames into an ffmpeg process that encodes them to a file, or straight into a
+/// named pipe for other tools to read
+pub struct Recorder {
+    // None when writing to a frame pipe
+    child: Option<Child>,
+    // Whatever reads the frames, for messages
+    sink: &'static str,
+    sender: Option<SyncSender<Arc<Vec<u8>>>>,
+    writer: Option<JoinHandle<()>>,
+    // Set once the writer is past open, before that it may be waiting on a FIFO nobody reads
+    opened: Arc<AtomicBool>,
+    width: u32,
+    height: u32,
+    frame_interval: Duration,
+    next_frame_time: Option<Instant>,
+    dropped_frames: usize,
+}
+
+impl Recorder {
+    pub fn new(
+        output: &Path,
+        width: u32,
+        height: u32,
+        fps: u32,
+        extra_args: &[String],
+    ) -> Result<Recorder, RecorderError> {
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pixel_format", "rgba"])
+            .arg("-video_size")
+            .arg(format!("{width}x{height}"))
+            .arg("-framerate")
+            .arg(fps.to_string())
+            .args(["-i", "-"])
+            .args(extra_args)
+            .arg(output)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(RecorderError::Spawn)?;
+
+        let stdin = child.stdin.take().ok_or(RecorderError::NoStdin)?;
+        Ok(Self::start(
+            Some(child),
+            "ffmpeg",
+            move || Ok(stdin),
+            Vec::new(),
+            width,
+            height,
+            fps,
+        ))
+    }
+
+    /// Writes frames to path, meant to be a FIFO made with mkfifo so another process can take
+    /// them without a screen capture. The stream starts with a 16 byte header, "RGBA" followed
+    /// by the width, height and fps as little endian u32s. Every frame after it is
+    /// `width * height * 4` bytes of RGBA, rows top to bottom, with nothing in between.
+    ///
+    /// Nothing is written until a reader opens the pipe, if none ever does the writer is left
+    /// waiting on exit rather than holding it up. Frames are sent at fps in real time and dropped
+    /// while the reader falls behind, once the reader closes the pipe output stops for good
+    pub fn to_pipe(path: &Path, width: u32, height: u32, fps: u32) -> Recorder {
+        let path = path.to_owned();
+        Self::start(
+            None,
+            "the frame pipe",
+            move || File::create(path),
+            frame_pipe_header(width, height, fps),
+            width,
+            height,
+            fps,
+        )
+    }
+
+    /// open runs on the writer thread, opening a FIFO blocks until something reads from it
+    fn start<W, F>(
+        child: Option<Child>,
+        sink: &'static str,
+        open: F,
+        header: Vec<u8>,
+        width: u32,
+        height: u32,
+        fps: u32,
+    ) -> Recorder
+    where
+        W: Write,
+        F: FnOnce() -> std::io::Result<W> + Send + 'static,
+    {
+        // Writes block whenever the sink falls behind, keep them off the render thread
+        let (sender, receiver) = mpsc::sync_channel::<Arc<Vec<u8>>>(MAX_QUEUED_FRAMES);
+        let opened = Arc::new(AtomicBool::new(false));
+        let writer_opened = Arc::clone(&opened);
+        let writer = std::thread::spawn(move || {
+            let open_result = open();
+            writer_opened.store(true, Ordering::Release);
+            let mut output = match open_result {
+                Ok(output) => output,
+                Err(e) => {
+                    println!("Failed to open {sink}, stopping recording: {e}");
+                    return;
+                }
+            };
+            if let Err(e) = output.write_all(&header) {
+                println!("Failed to write to {sink}, stopping recording: {e}");
+                return;
+            }
+
+            for frame in receiver {
+                if let Err(e) = output.write_all(&frame) {
+                    println!("Failed to write frame to {sink}, stopping recording: {e}");
+                    break;
+                }
+            }
+        });
+
+        Recorder {
+            child,
+            sink,
+            sender: Some(sender),
+            writer: Some(writer),
+            opened,
+            width,
+            height,
+            frame_interval: Duration::from_secs_f32(1.0 / fps as f32),
+            next_frame_time: None,
+            dropped_frames: 0,
+        }
+    }
+
+    /// How many video frames have elapsed since the last call. More than one means rendering is
+    /// slower than the target fps and the current frame should be repeated to keep time
+    pub fn frames_due(&mut self, now: Instant) -> usize {
+        frames_due(&mut self.next_frame_time, self.frame_interval, now)
+    }
+
+    /// Queue a frame read back from GL count times. Rows are expected bottom to top as
+    /// glReadPixels gives them. Frames are dropped if the sink can't keep up
+    pub fn push_frame(&mut self, mut pixels: Vec<u8>, count: usize) {
+        assert_eq!(pixels.len(), self.width as usize * self.height as usize * 4);
+        flip_rows(&mut pixels, self.width as usize * 4);
+        let frame = Arc::new(pixels);
+
+        for _ in 0..count {
+            let Some(sender) = &self.sender else {
+                return;
+            };
+
+            match sender.try_send(Arc::clone(&frame)) {
+                Ok(()) => (),
+                Err(TrySendError::Full(_)) => {
+                    self.dropped_frames += 1;
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    self.sender = None;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // Closing the channel closes ffmpeg's stdin once the writer drains, letting it finish the
+        // file cleanly
+        self.sender = None;
+        if let Some(writer) = self.writer.take() {
+            if self.opened.load(Ordering::Acquire) {
+                let _ = writer.join();
+            } else {
+                println!("Nothing opened {}, not waiting for it", self.sink);
+            }
+        }
+        if let Some(child) = &mut self.child {
+            let _ = child.wait();
+        }
+
+        if self.dropped_frames > 0 {
+            println!(
+                "Dropped {} frames while recording, {} couldn't keep up",
+                self.dropped_frames, self.sink
+            );
+        }
+    }
+}
+
+fn frame_pipe_header(width: u32, height: u32, fps: u32) -> Vec<u8> {
+    let mut header = FRAME_PIPE_MAGIC.to_vec();
+    for v in [width, height, fps] {
+        header.extend_from_slice(&v.to_le_bytes());
+    }
+    header
+}
+
+fn frames_due(next_frame_time: &mut Option<Instant>, interval: Duration, now: Instant) -> usize {
+    let next_frame_time = next_frame_time.get_or_insert(now);
+
+    let mut due = 0;
+    while *next_frame_time <= now {
+        *next_frame_time += interval;
+        due += 1;
+    }
+    due
+}
+
+/// GL's origin is the bottom left, video frames start at the top
+fn flip_rows(pixels: &mut [u8], stride: usize) {
+    let num_rows = pixels.len() / stride;
+    for y in 0..num_rows / 2 {
+        let (top, bottom) = pixels.split_at_mut((num_rows - y - 1) * stride);
+        top[y * stride..(y + 1) * stride].swap_with_slice(&mut bottom[..stride]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frame_pacing() {
+        let start = Instant::now();
+        let interval = Duration::from_millis(100);
+        let mut next_frame_time = None;
+
+        assert_eq!(frames_due(&mut next_frame_time, interval, start), 1);
+        let now = start + Duration::from_millis(50);
+        assert_eq!(frames_due(&mut next_frame_time, interval, now), 0);
+        // Slow frame, the missing video frames get repeated
+        let now = start + Duration::from_millis(350);
+        assert_eq!(frames_due(&mut next_frame_time, interval, now), 3);
+    }
+
+    #[test]
+    fn test_frame_pipe_header() {
+        let header = frame_pipe_header(1920, 1080, 30);
+        assert_eq!(header.len(), 16);
+        assert_eq!(&header[..4], b"RGBA");
+        assert_eq!(header[4..8], 1920u32.to_le_bytes());
+        assert_eq!(header[8..12], 1080u32.to_le_bytes());
+        assert_eq!(header[12..], 30u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_frame_pipe_stream() {
+        let path = std::env::temp_dir().join(format!("frame_pipe_test_{}", std::process::id()));
+        let mut recorder = Recorder::to_pipe(&path, 2, 1, 30);
+        // Bottom row first like glReadPixels, a single row doesn't flip
+        recorder.push_frame(vec![1, 2, 3, 4, 5, 6, 7, 8], 2);
+        // A regular file opens right away, drop only waits on writers that got that far
+        while !recorder.opened.load(Ordering::Acquire) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        drop(recorder);
+
+        let written = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(written[..16], frame_pipe_header(2, 1, 30));
+        assert_eq!(
+            written[16..],
+            [1, 2, 3, 4, 5, 6, 7, 8, 1, 2, 3, 4, 5, 6, 7, 8]
+        );
+    }
+
+    #[test]
+    fn test_unread_frame_pipe_drops() {
+        let path = std::env::temp_dir().join(format!("frame_pipe_unread_{}", std::process::id()));
+        let status = Command::new("mkfifo").arg(&path).status().unwrap();
+        assert!(status.success());
+
+        let mut recorder = Recorder::to_pipe(&path, 2, 1, 30);
+        recorder.push_frame(vec![0; 8], 1);
+        // Would hang here if drop waited for a reader
+        drop(recorder);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_flip_rows() {
+        let mut pixels = vec![1, 1, 2, 2, 3, 3];
+        flip_rows(&mut pixels, 2);
+        assert_eq!(pixels, [3, 3, 2, 2, 1, 1]);
+
+        let mut pixels = vec![1, 2, 3, 4];
+        flip_rows(&mut pixels, 1);
+        assert_eq!(pixels, [4, 3, 2, 1]);
+    }
+}
@@ -0,0 +1,501 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::{
+    camera_path::{CameraPath, Keyframe},
+    json::{self, JsonParseError, JsonValue},
+    mat::{Axis, Transform, Vec3},
+};
+
+const DEFAULT_SCENE: &str = include_str!("../default_scene.json");
+
+// Assets baked into the binary, scenes on disk can refer to these by name as well
+const EMBEDDED_ASSETS: &[(&str, &[u8])] = &[
+    ("monitor.obj", include_bytes!("../monitor.obj")),
+    (
+        "monitor_texture.png",
+        include_bytes!("../monitor_texture.png"),
+    ),
+    ("screen.obj", include_bytes!("../screen.obj")),
+    (
+        "screen_textuire.png",
+        include_bytes!("../screen_textuire.png"),
+    ),
+    ("table.obj", include_bytes!("../table.obj")),
+    ("table_texture.png", include_bytes!("../table_texture.png")),
+    ("walls.obj", include_bytes!("../walls.obj")),
+    ("wall_texture.png", include_bytes!("../wall_texture.png")),
+];
+
+#[derive(Debug, Error)]
+pub enum SceneError {
+    #[error("failed to read scene file")]
+    ReadScene(#[source] std::io::Error),
+    #[error("failed to parse scene json")]
+    Json(#[from] JsonParseError),
+    #[error("missing or invalid {0}")]
+    InvalidField(&'static str),
+    #[error("unknown object role {0}")]
+    UnknownRole(String),
+    #[error("camera keyframes must be non empty with increasing times")]
+    InvalidCameraPath,
+    #[error("failed to read asset {0}")]
+    ReadAsset(String, #[source] std::io::Error),
+}
+
+/// How an object takes part in the monitor row, see --monitors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectRole {
+    /// Rendered once
+    Static,
+    /// Repeated for every monitor on the desk
+    Monitor,
+    /// Repeated like Monitor, the central copy shows the countdown text
+    Screen,
+}
+
+pub struct SceneObject {
+    pub obj: String,
+    /// Without a texture the object is drawn in color
+    pub texture: Option<String>,
+    pub color: [f32; 3],
+    /// Glow added on top of the lighting, see MeshRenderer::set_emissive
+    pub emissive: [f32; 3],
+    pub transform: Transform,
+    pub role: ObjectRole,
+    /// Alpha below which the texture is cut out, see MeshRenderer::set_alpha_cutoff
+    pub alpha_cutoff: Option<f32>,
+    /// Lower draws first, see App::render_objects
+    pub draw_order: i32,
+    /// Drawn after every opaque object, back to front
+    pub transparent: bool,
+}
+
+pub struct Light {
+    pub direction: Vec3,
+    pub color: [f32; 3],
+    /// Defaults to a fifth of color
+    pub ambient: [f32; 3],
+}
+
+pub struct SceneCamera {
+    pub fov: f32,
+    pub near: f32,
+    pub far: f32,
+    pub path: Option<CameraPath>,
+}
+
+enum AssetSource {
+    Embedded,
+    Dir(PathBuf),
+}
+
+/// Everything drawn in the 3d scene. Asset paths are relative to the scene file
+pub struct Scene {
+    pub objects: Vec<SceneObject>,
+    /// Moves every monitor and screen object together, so tilting the monitor keeps the screen
+    /// on it
+    pub monitor: Transform,
+    pub light: Light,
+    pub camera: SceneCamera,
+    assets: AssetSource,
+}
+
+impl Scene {
+    /// The desk and monitor bundled with the binary
+    pub fn default_scene() -> Scene {
+        Scene::from_json(DEFAULT_SCENE, AssetSource::Embedded)
+            .expect("bundled scene should be valid")
+    }
+
+    pub fn load(path: &Path) -> Result<Scene, SceneError> {
+        let s = std::fs::read_to_string(path).map_err(SceneError::ReadScene)?;
+        let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        Scene::from_json(&s, AssetSource::Dir(dir))
+    }
+
+    /// Files next to the scene win, anything missing falls back to the bundled assets
+    pub fn read_asset(&self, name: &str) -> Result<Cow<'static, [u8]>, SceneError> {
+        let embedded = EMBEDDED_ASSETS
+            .iter()
+            .find(|(embedded_name, _)| *embedded_name == name)
+            .map(|(_, data)| Cow::Borrowed(*data));
+
+        match &self.assets {
+            AssetSource::Embedded => embedded.ok_or_else(|| {
+                SceneError::ReadAsset(name.to_string(), std::io::ErrorKind::NotFound.into())
+            }),
+            AssetSource::Dir(dir) => match std::fs::read(dir.join(name)) {
+                Ok(data) => Ok(Cow::Owned(data)),
+                Err(e) => embedded.ok_or(SceneError::ReadAsset(name.to_string(), e)),
+            },
+        }
+    }
+
+    /// Where object sits in the scene, before any copies for --monitors
+    pub fn object_transform(&self, object: &SceneObject) -> Transform {
+        match object.role {
+            ObjectRole::Static => object.transform.clone(),
+            ObjectRole::Monitor | ObjectRole::Screen => &self.monitor * &object.transform,
+        }
+    }
+
+    fn from_json(s: &str, assets: AssetSource) -> Result<Scene, SceneError> {
+        let root = json::parse(s)?;
+
+        let objects = root
+            .get("objects")
+            .and_then(JsonValue::as_array)
+            .ok_or(SceneError::InvalidField("objects"))?
+            .iter()
+            .map(parse_object)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let monitor = parse_transform(root.get("monitor"))?;
+
+        let light = root.get("light");
+        let color = optional_f32_array(light, "color", [0.8, 0.8, 0.5])?;
+        let light = Light {
+            direction: optional_f32_array(light, "direction", [-0.3, -1.0, -0.6])?.into(),
+            color,
+            ambient: optional_f32_array(light, "ambient", color.map(|c| c * 0.2))?,
+        };
+
+        let camera_json = root.get("camera");
+        let camera = SceneCamera {
+            fov: optional_f32(camera_json, "fov", 50.0)?,
+            near: optional_f32(camera_json, "near", 0.1)?,
+            far: optional_f32(camera_json, "far", 10.0)?,
+            path: parse_camera_path(camera_json)?,
+        };
+
+        Ok(Scene {
+            objects,
+            monitor,
+            light,
+            camera,
+            assets,
+        })
+    }
+}
+
+fn optional_f32(
+    parent: Option<&JsonValue>,
+    key: &'static str,
+    default: f32,
+) -> Result<f32, SceneError> {
+    match parent.and_then(|v| v.get(key)) {
+        Some(v) => v.as_f32().ok_or(SceneError::InvalidField(key)),
+        None => Ok(default),
+    }
+}
+
+fn optional_i32(
+    parent: Option<&JsonValue>,
+    key: &'static str,
+    default: i32,
+) -> Result<i32, SceneError> {
+    match parent.and_then(|v| v.get(key)) {
+        Some(v) => v.as_i32().ok_or(SceneError::InvalidField(key)),
+        None => Ok(default),
+    }
+}
+
+fn optional_f32_array<const N: usize>(
+    parent: Option<&JsonValue>,
+    key: &'static str,
+    default: [f32; N],
+) -> Result<[f32; N], SceneError> {
+    match parent.and_then(|v| v.get(key)) {
+        Some(v) => v.as_f32_array().ok_or(SceneError::InvalidField(key)),
+        None => Ok(default),
+    }
+}
+
+fn optional_str(parent: &JsonValue, key: &'static str) -> Result<Option<String>, SceneError> {
+    match parent.get(key) {
+        Some(v) => v
+            .as_str()
+            .map(|s| Some(s.to_string()))
+            .ok_or(SceneError::InvalidField(key)),
+        None => Ok(None),
+    }
+}
+
+fn required_str(parent: &JsonValue, key: &'static str) -> Result<String, SceneError> {
+    parent
+        .get(key)
+        .and_then(JsonValue::as_str)
+        .map(ToString::to_string)
+        .ok_or(SceneError::InvalidField(key))
+}
+
+fn parse_transform(v: Option<&JsonValue>) -> Result<Transform, SceneError> {
+    let translation = optional_f32_array(v, "translation", [0.0; 3])?;
+    // Degrees around x, then y, then z
+    let rotation = optional_f32_array(v, "rotation", [0.0; 3])?;
+    let scale = optional_f32_array(v, "scale", [1.0; 3])?;
+
+    Ok(
+        Transform::from_translation(translation[0], translation[1], translation[2])
+            * Transform::from_axis_angle(rotation[2].to_radians(), Axis::Z)
+            * Transform::from_axis_angle(rotation[1].to_radians(), Axis::Y)
+            * Transform::from_axis_angle(rotation[0].to_radians(), Axis::X)
+            * Transform::from_scale(scale.into()),
+    )
+}
+
+fn parse_object(v: &JsonValue) -> Result<SceneObject, SceneError> {
+    let transform = parse_transform(Some(v))?;
+
+    let role = match v.get("role") {
+        None => ObjectRole::Static,
+        Some(role) => match role.as_str().ok_or(SceneError::InvalidField("role"))? {
+            "static" => ObjectRole::Static,
+            "monitor" => ObjectRole::Monitor,
+            "screen" => ObjectRole::Screen,
+            s => return Err(SceneError::UnknownRole(s.to_string())),
+        },
+    };
+
+    let alpha_cutoff = match v.get("alpha_cutoff") {
+        Some(cutoff) => Some(
+            cutoff
+                .as_f32()
+                .ok_or(SceneError::InvalidField("alpha_cutoff"))?,
+        ),
+        None => None,
+    };
+
+    let transparent = match v.get("transparent") {
+        Some(transparent) => transparent
+            .as_bool()
+            .ok_or(SceneError::InvalidField("transparent"))?,
+        None => false,
+    };
+
+    Ok(SceneObject {
+        obj: required_str(v, "obj")?,
+        texture: optional_str(v, "texture")?,
+        color: optional_f32_array(Some(v), "color", [1.0; 3])?,
+        emissive: optional_f32_array(Some(v), "emissive", [0.0; 3])?,
+        transform,
+        role,
+        alpha_cutoff,
+        draw_order: optional_i32(Some(v), "draw_order", 0)?,
+        transparent,
+    })
+}
+
+fn parse_camera_path(camera: Option<&JsonValue>) -> Result<Option<CameraPath>, SceneError> {
+    let Some(keyframes) = camera.and_then(|v| v.get("keyframes")) else {
+        return Ok(None);
+    };
+
+    let keyframes = keyframes
+        .as_array()
+        .ok_or(SceneError::InvalidField("keyframes"))?
+        .iter()
+        .map(|k| {
+            let field = |key| k.get(key).ok_or(SceneError::InvalidField(key));
+            let vec3_field = |key| {
+                field(key)?
+                    .as_f32_array::<3>()
+                    .ok_or(SceneError::InvalidField(key))
+            };
+            Ok(Keyframe {
+                time: field("time")?
+                    .as_f32()
+                    .ok_or(SceneError::InvalidField("time"))?,
+                position: vec3_field("position")?.into(),
+                target: vec3_field("target")?.into(),
+                easing: k
+                    .get("easing")
+                    .map(|v| {
+                        v.as_str()
+                            .and_then(|s| s.parse().ok())
+                            .ok_or(SceneError::InvalidField("easing"))
+                    })
+                    .transpose()?,
+            })
+        })
+        .collect::<Result<Vec<_>, SceneError>>()?;
+
+    let looped = match camera.and_then(|v| v.get("loop")) {
+        Some(v) => v.as_bool().ok_or(SceneError::InvalidField("loop"))?,
+        None => false,
+    };
+
+    CameraPath::new(keyframes, looped)
+        .map(Some)
+        .ok_or(SceneError::InvalidCameraPath)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util::assert_near;
+
+    #[test]
+    fn test_default_scene_loads() {
+        let scene = Scene::default_scene();
+        assert_eq!(scene.objects.len(), 4);
+        assert_eq!(
+            scene
+                .objects
+                .iter()
+                .filter(|o| o.role == ObjectRole::Screen)
+                .count(),
+            1
+        );
+        for object in &scene.objects {
+            scene.read_asset(&object.obj).unwrap();
+            scene.read_asset(object.texture.as_ref().unwrap()).unwrap();
+            // Only the screen lights itself
+            let expected = match object.role {
+                ObjectRole::Screen => [1.0; 3],
+                _ => [0.0; 3],
+            };
+            assert_eq!(object.emissive, expected);
+        }
+        assert!(scene.camera.path.is_none());
+    }
+
+    #[test]
+    fn test_object_transform() {
+        let scene = Scene::from_json(
+            r#"{"objects": [{"obj": "a.obj", "texture": "a.png",
+                "translation": [1, 2, 3], "rotation": [0, 90, 0], "scale": [2, 2, 2]}]}"#,
+            AssetSource::Embedded,
+        )
+        .unwrap();
+
+        // Scale, then rotate +x onto -z, then translate
+        let p = scene.objects[0]
+            .transform
+            .transform_point([1.0, 0.0, 0.0].into());
+        assert_near(p, [1.0, 2.0, 1.0]);
+        assert_eq!(scene.objects[0].role, ObjectRole::Static);
+        assert_eq!(scene.objects[0].alpha_cutoff, None);
+        assert_eq!(scene.objects[0].draw_order, 0);
+        assert!(!scene.objects[0].transparent);
+    }
+
+    #[test]
+    fn test_untextured_object() {
+        let scene = Scene::from_json(
+            r#"{"objects": [{"obj": "a.obj", "color": [0.2, 0.4, 0.6]}, {"obj": "b.obj"}]}"#,
+            AssetSource::Embedded,
+        )
+        .unwrap();
+        assert_eq!(scene.objects[0].texture, None);
+        assert_eq!(scene.objects[0].color, [0.2, 0.4, 0.6]);
+        assert_eq!(scene.objects[1].color, [1.0; 3]);
+    }
+
+    #[test]
+    fn test_monitor_transform() {
+        let scene = Scene::from_json(
+            r#"{"monitor": {"translation": [0, 1, 0], "rotation": [-10, 0, 0]},
+                "objects": [
+                    {"obj": "a", "texture": "a", "translation": [1, 0, 0]},
+                    {"obj": "m", "texture": "m", "role": "monitor"},
+                    {"obj": "s", "texture": "s", "role": "screen", "translation": [0, 0, 0.1]}
+                ]}"#,
+            AssetSource::Embedded,
+        )
+        .unwrap();
+
+        let origin = |i: usize| {
+            scene
+                .object_transform(&scene.objects[i])
+                .transform_point([0.0, 0.0, 0.0].into())
+        };
+        // Static objects ignore the monitor transform
+        assert!((origin(0).x() - 1.0).abs() < 0.001);
+        assert!(origin(0).y().abs() < 0.001);
+
+        assert!((origin(1).y() - 1.0).abs() < 0.001);
+        // The screen tilts with the monitor instead of staying upright at z = 0.1
+        let screen = origin(2);
+        assert!((screen.z() - 0.1 * 10f32.to_radians().cos()).abs() < 0.001);
+        assert!((screen.y() - 1.0).abs() > 0.01);
+    }
+
+    #[test]
+    fn test_light_ambient() {
+        let scene = Scene::from_json(
+            r#"{"objects": [], "light": {"color": [1, 0.5, 0]}}"#,
+            AssetSource::Embedded,
+        )
+        .unwrap();
+        assert_eq!(scene.light.ambient, [0.2, 0.1, 0.0]);
+
+        let scene = Scene::from_json(
+            r#"{"objects": [], "light": {"ambient": [0.05, 0.05, 0.1]}}"#,
+            AssetSource::Embedded,
+        )
+        .unwrap();
+        assert_eq!(scene.light.ambient, [0.05, 0.05, 0.1]);
+    }
+
+    #[test]
+    fn test_invalid_scenes() {
+        assert!(matches!(
+            Scene::from_json(
+                r#"{"objects": [{"obj": "a.obj", "texture": 1}]}"#,
+                AssetSource::Embedded
+            ),
+            Err(SceneError::InvalidField("texture"))
+        ));
+        assert!(matches!(
+            Scene::from_json(
+                r#"{"objects": [{"obj": "a", "texture": "b", "role": "lamp"}]}"#,
+                AssetSource::Embedded
+            ),
+            Err(SceneError::UnknownRole(_))
+        ));
+        assert!(matches!(
+            Scene::from_json(
+                r#"{"objects": [{"obj": "a", "texture": "b", "alpha_cutoff": "half"}]}"#,
+                AssetSource::Embedded
+            ),
+            Err(SceneError::InvalidField("alpha_cutoff"))
+        ));
+        assert!(matches!(
+            Scene::from_json(
+                r#"{"objects": [{"obj": "a", "texture": "b", "transparent": 1}]}"#,
+                AssetSource::Embedded
+            ),
+            Err(SceneError::InvalidField("transparent"))
+        ));
+        assert!(matches!(
+            Scene::from_json(
+                r#"{"objects": [{"obj": "a", "texture": "b", "draw_order": 1.5}]}"#,
+                AssetSource::Embedded
+            ),
+            Err(SceneError::InvalidField("draw_order"))
+        ));
+        assert!(matches!(
+            Scene::from_json(
+                r#"{"objects": [], "camera": {"keyframes": []}}"#,
+                AssetSource::Embedded
+            ),
+            Err(SceneError::InvalidCameraPath)
+        ));
+        assert!(matches!(
+            Scene::from_json(
+                r#"{"objects": [], "camera": {"keyframes": [
+                    {"time": 0, "position": [0, 0, 0], "target": [0, 0, 1], "easing": "fast"}
+                ]}}"#,
+                AssetSource::Embedded
+            ),
+            Err(SceneError::InvalidField("easing"))
+        ));
+    }
+}
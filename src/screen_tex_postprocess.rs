@@ -63,13 +63,23 @@ unsafe fn generate_square_buffer(gl: &glow::Context) -> NativeBuffer {
     vbo
 }
 
+pub const DEFAULT_SCANLINE_INTENSITY: f32 = 0.2;
+pub const DEFAULT_SCANLINE_FREQUENCY: f32 = 200.0;
+pub const DEFAULT_CURVATURE: f32 = 0.0;
+
 pub struct ScreenTexPostprocessor<'a> {
     program: NativeProgram,
     vao: NativeVertexArray,
     vbo: NativeBuffer,
     gl: &'a glow::Context,
     aspect_loc: <glow::Context as HasContext>::UniformLocation,
-    time_loc: <glow::Context as HasContext>::UniformLocation,
+    time_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    scanline_intensity_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    scanline_frequency_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    curvature_loc: Option<<glow::Context as HasContext>::UniformLocation>,
+    scanline_intensity: f32,
+    scanline_frequency: f32,
+    curvature: f32,
 }
 
 impl<'a> ScreenTexPostprocessor<'a> {
@@ -79,7 +89,7 @@ impl<'a> ScreenTexPostprocessor<'a> {
                 gl,
                 include_str!("glsl/vertex.glsl"),
                 include_str!("glsl/screen_fragment.glsl"),
-            );
+            )?;
 
             let vao = gl.create_vertex_array().map_err(GlError)?;
             gl.bind_vertex_array(Some(vao));
@@ -102,9 +112,11 @@ impl<'a> ScreenTexPostprocessor<'a> {
                 .get_uniform_location(program, "aspect_ratio")
                 .expect("Invalid vertex shader");
 
-            let time_loc = gl
-                .get_uniform_location(program, "time")
-                .expect("Invalid vertex shader");
+            let time_loc = gl.get_uniform_location(program, "time");
+
+            let scanline_intensity_loc = gl.get_uniform_location(program, "scanline_intensity");
+            let scanline_frequency_loc = gl.get_uniform_location(program, "scanline_frequency");
+            let curvature_loc = gl.get_uniform_location(program, "curvature");
 
             Ok(ScreenTexPostprocessor {
                 program,
@@ -113,10 +125,56 @@ impl<'a> ScreenTexPostprocessor<'a> {
                 gl,
                 aspect_loc,
                 time_loc,
+                scanline_intensity_loc,
+                scanline_frequency_loc,
+                curvature_loc,
+                scanline_intensity: DEFAULT_SCANLINE_INTENSITY,
+                scanline_frequency: DEFAULT_SCANLINE_FREQUENCY,
+                curvature: DEFAULT_CURVATURE,
             })
         }
     }
 
+    /// Swaps in a new screen fragment shader, the current one stays if it doesn't build
+    pub fn reload_fragment_shader(&mut self, source: &str) -> Result<(), GlError> {
+        unsafe {
+            let program =
+                gl_util::compile_program(self.gl, include_str!("glsl/vertex.glsl"), source)?;
+            let Some(aspect_loc) = self.gl.get_uniform_location(program, "aspect_ratio") else {
+                self.gl.delete_program(program);
+                return Err(GlError(
+                    "aspect_ratio uniform was optimized out".to_string(),
+                ));
+            };
+            self.gl.delete_program(self.program);
+
+            self.program = program;
+            self.aspect_loc = aspect_loc;
+            self.time_loc = self.gl.get_uniform_location(program, "time");
+            self.scanline_intensity_loc =
+                self.gl.get_uniform_location(program, "scanline_intensity");
+            self.scanline_frequency_loc =
+                self.gl.get_uniform_location(program, "scanline_frequency");
+            self.curvature_loc = self.gl.get_uniform_location(program, "curvature");
+        }
+        Ok(())
+    }
+
+    /// Brightness of the scanlines behind the text, 0 turns them off and 1 is full green
+    pub fn set_scanline_intensity(&mut self, intensity: f32) {
+        self.scanline_intensity = intensity;
+    }
+
+    /// Scanlines per texture height, larger values give thinner lines. Must be positive
+    pub fn set_scanline_frequency(&mut self, frequency: f32) {
+        self.scanline_frequency = frequency;
+    }
+
+    /// Barrel distortion of the content, 0 is flat and 1 is a heavily bulged CRT
+    pub fn set_curvature(&mut self, curvature: f32) {
+        self.curvature = curvature;
+    }
+
     pub fn render(&self, tex: NativeTexture, time: f32, aspect: f32) {
         let gl = self.gl;
 
@@ -126,7 +184,16 @@ impl<'a> ScreenTexPostprocessor<'a> {
             gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
 
             gl.uniform_1_f32(Some(&self.aspect_loc), aspect);
-            gl.uniform_1_f32(Some(&self.time_loc), time * 5.0);
+            gl.uniform_1_f32(self.time_loc.as_ref(), time * 5.0);
+            gl.uniform_1_f32(
+                self.scanline_intensity_loc.as_ref(),
+                self.scanline_intensity,
+            );
+            gl.uniform_1_f32(
+                self.scanline_frequency_loc.as_ref(),
+                self.scanline_frequency,
+            );
+            gl.uniform_1_f32(self.curvature_loc.as_ref(), self.curvature);
 
             gl.active_texture(glow::TEXTURE0);
             gl.bind_texture(glow::TEXTURE_2D, Some(tex));
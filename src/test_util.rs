@@ -0,0 +1,26 @@
+//! Helpers shared by the test modules
+
+use crate::mat::{Transform, Vec3};
+
+/// Every element within 0.001, enough for transforms built from a few rotations
+pub fn assert_transforms_eq(a: &Transform, b: &Transform) {
+    for y in 0..4 {
+        for x in 0..4 {
+            assert!(
+                (a.arr[y][x] - b.arr[y][x]).abs() < 0.001,
+                "{:?} != {:?}",
+                a.arr,
+                b.arr
+            );
+        }
+    }
+}
+
+pub fn assert_near(actual: Vec3, expected: [f32; 3]) {
+    for (a, e) in [actual.x(), actual.y(), actual.z()]
+        .into_iter()
+        .zip(expected)
+    {
+        assert!((a - e).abs() < 0.0001, "{actual:?} != {expected:?}");
+    }
+}
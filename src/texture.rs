@@ -0,0 +1,111 @@
+use glow::{HasContext, NativeTexture};
+use thiserror::Error;
+
+use crate::{gl_util, jpeg, GlError};
+
+#[derive(Debug, Error)]
+pub enum LoadTextureError {
+    #[error("unrecognized image format, expected png or jpeg")]
+    UnknownFormat,
+    #[error("failed to decode png")]
+    DecodePng(#[from] png::DecodingError),
+    #[error("unsupported png: {0}")]
+    UnsupportedPng(&'static str),
+    #[error("failed to decode jpeg")]
+    DecodeJpeg(#[from] jpeg::JpegError),
+    #[error("failed to create texture")]
+    CreateTexture(GlError),
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Picks the decoder from the file's magic bytes. Mesh textures repeat so uvs are free to tile
+pub fn load_texture(gl: &glow::Context, data: &[u8]) -> Result<NativeTexture, LoadTextureError> {
+    if data.starts_with(PNG_SIGNATURE) {
+        load_png(gl, data)
+    } else if jpeg::is_jpeg(data) {
+        let image = jpeg::decode(data)?;
+        unsafe {
+            upload(
+                gl,
+                image.width,
+                image.height,
+                glow::RGB,
+                glow::UNSIGNED_BYTE,
+                &image.rgb,
+            )
+        }
+    } else {
+        Err(LoadTextureError::UnknownFormat)
+    }
+}
+
+/// PNGs go straight to GL in whatever format they're stored in
+fn load_png(gl: &glow::Context, data: &[u8]) -> Result<NativeTexture, LoadTextureError> {
+    let mut png_reader = png::Decoder::new(data).read_info()?;
+
+    let mut img_data = vec![0; png_reader.output_buffer_size()];
+    let img_info = png_reader.next_frame(&mut img_data)?;
+
+    let color_format = match img_info.color_type {
+        png::ColorType::Grayscale => glow::RED,
+        png::ColorType::Rgb => glow::RGB,
+        png::ColorType::Indexed => {
+            return Err(LoadTextureError::UnsupportedPng("indexed colors"));
+        }
+        png::ColorType::GrayscaleAlpha => {
+            return Err(LoadTextureError::UnsupportedPng("grayscale alpha"));
+        }
+        png::ColorType::Rgba => glow::RGBA,
+    };
+
+    let bit_depth = match img_info.bit_depth {
+        png::BitDepth::One | png::BitDepth::Two | png::BitDepth::Four => {
+            return Err(LoadTextureError::UnsupportedPng("bit depth below 8"));
+        }
+        png::BitDepth::Eight => glow::UNSIGNED_BYTE,
+        png::BitDepth::Sixteen => glow::UNSIGNED_SHORT,
+    };
+
+    unsafe {
+        upload(
+            gl,
+            img_info.width,
+            img_info.height,
+            color_format,
+            bit_depth,
+            &img_data,
+        )
+    }
+}
+
+unsafe fn upload(
+    gl: &glow::Context,
+    width: u32,
+    height: u32,
+    format: u32,
+    ty: u32,
+    pixels: &[u8],
+) -> Result<NativeTexture, LoadTextureError> {
+    let tex = gl_util::create_tex_default_params(gl, glow::REPEAT, glow::REPEAT)
+        .map_err(LoadTextureError::CreateTexture)?;
+
+    gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+    // Decoded rows are width * channels * bytes per channel long, often not a multiple of 4
+    gl_util::with_packed_rows(gl, || {
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA as i32,
+            width as i32,
+            height as i32,
+            0,
+            format,
+            ty,
+            Some(pixels),
+        )
+    });
+    gl.bind_texture(glow::TEXTURE_2D, None);
+
+    Ok(tex)
+}
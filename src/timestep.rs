@@ -0,0 +1,124 @@
+use std::time::{Duration, Instant};
+
+// Past this many steps in one frame the simulation gives up catching up and drops the backlog,
+// otherwise a slow update would make every following frame slower still
+const MAX_STEPS_PER_FRAME: usize = 8;
+
+/// Hands out simulation times a constant step apart, however often it's polled.
+///
+/// The time between the last step and real time acts as the accumulator. Each frame adds its real
+/// delta to it and a step is taken for every whole interval it holds, the leftover carries over
+/// to the next frame. Update code only ever sees multiples of the step, so the same input gives
+/// the same animation on any frame rate
+pub struct FixedTimestep {
+    step: Duration,
+    sim_time: Instant,
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration, start: Instant) -> FixedTimestep {
+        assert!(!step.is_zero());
+        FixedTimestep {
+            step,
+            sim_time: start,
+        }
+    }
+
+    /// Simulation times to update with, in order, to catch up with now
+    pub fn steps(&mut self, now: Instant) -> Vec<Instant> {
+        let mut steps = Vec::new();
+        while self.sim_time + self.step <= now {
+            if steps.len() == MAX_STEPS_PER_FRAME {
+                let behind = now - self.sim_time;
+                let whole_steps = (behind.as_nanos() / self.step.as_nanos()) as u32;
+                self.sim_time += self.step * whole_steps;
+                break;
+            }
+
+            self.sim_time += self.step;
+            steps.push(self.sim_time);
+        }
+        steps
+    }
+}
+
+/// Real time minus every stretch spent paused. Anything stamped from this clock, like App's
+/// last_update and cursor_flip_time, needs no adjusting on resume. The clock carries on from the
+/// instant it stopped at, so the first delta after resuming is an ordinary frame's worth
+#[derive(Default)]
+pub struct PausableClock {
+    paused_total: Duration,
+    paused_at: Option<Instant>,
+}
+
+impl PausableClock {
+    pub fn now(&self, real: Instant) -> Instant {
+        self.paused_at.unwrap_or(real) - self.paused_total
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn toggle_pause(&mut self, real: Instant) {
+        match self.paused_at.take() {
+            Some(paused_at) => self.paused_total += real - paused_at,
+            None => self.paused_at = Some(real),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_steps_carry_remainder() {
+        let start = Instant::now();
+        let step = Duration::from_millis(10);
+        let mut timestep = FixedTimestep::new(step, start);
+
+        assert!(timestep.steps(start + Duration::from_millis(5)).is_empty());
+        // The 5ms left over from the first frame adds to the second
+        assert_eq!(
+            timestep.steps(start + Duration::from_millis(25)),
+            [start + step, start + step * 2]
+        );
+        assert_eq!(
+            timestep.steps(start + Duration::from_millis(30)),
+            [start + step * 3]
+        );
+    }
+
+    #[test]
+    fn test_backlog_dropped() {
+        let start = Instant::now();
+        let step = Duration::from_millis(10);
+        let mut timestep = FixedTimestep::new(step, start);
+
+        let steps = timestep.steps(start + Duration::from_millis(1005));
+        assert_eq!(steps.len(), MAX_STEPS_PER_FRAME);
+        // Picks back up from real time instead of replaying the stall
+        assert_eq!(
+            timestep.steps(start + Duration::from_millis(1010)),
+            [start + step * 101]
+        );
+    }
+
+    #[test]
+    fn test_pause_has_no_time_jump() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut clock = PausableClock::default();
+
+        assert_eq!(clock.now(at(100)), at(100));
+        clock.toggle_pause(at(100));
+        assert!(clock.is_paused());
+        assert_eq!(clock.now(at(5000)), at(100));
+
+        clock.toggle_pause(at(5000));
+        assert!(!clock.is_paused());
+        assert_eq!(clock.now(at(5000)), at(100));
+        assert_eq!(clock.now(at(5016)), at(116));
+    }
+}